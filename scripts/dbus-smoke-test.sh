@@ -0,0 +1,54 @@
+#!/usr/bin/env bash
+# Exercise the com.timbo.WayvibesUi D-Bus interface against a private bus,
+# so the test never touches the desktop session. Pausing still stops any
+# wayvibes process of the current user.
+#
+# Usage: scripts/dbus-smoke-test.sh [path-to-wayvibes-ui-binary]
+set -euo pipefail
+
+BIN="${1:-src-tauri/target/debug/wayvibes-ui}"
+NAME="com.timbo.WayvibesUi"
+OBJECT="/com/timbo/WayvibesUi"
+
+WORKDIR="$(mktemp -d)"
+BUS_INFO="$(dbus-daemon --session --fork --print-address=1 --print-pid=1)"
+export DBUS_SESSION_BUS_ADDRESS="$(echo "$BUS_INFO" | sed -n 1p)"
+BUS_PID="$(echo "$BUS_INFO" | sed -n 2p)"
+
+# Isolate config, packs and runtime files from the real installation
+export XDG_CONFIG_HOME="$WORKDIR/config"
+export XDG_DATA_HOME="$WORKDIR/data"
+export XDG_RUNTIME_DIR="$WORKDIR/run"
+mkdir -p "$XDG_RUNTIME_DIR" && chmod 700 "$XDG_RUNTIME_DIR"
+
+"$BIN" &
+APP_PID=$!
+
+cleanup() {
+  kill "$APP_PID" 2>/dev/null || true
+  kill "$BUS_PID" 2>/dev/null || true
+  rm -rf "$WORKDIR"
+}
+trap cleanup EXIT
+
+busctl() { command busctl --address="$DBUS_SESSION_BUS_ADDRESS" "$@"; }
+
+for _ in $(seq 1 50); do
+  busctl status "$NAME" >/dev/null 2>&1 && break
+  sleep 0.2
+done
+
+busctl introspect "$NAME" "$OBJECT" "$NAME"
+
+busctl call "$NAME" "$OBJECT" "$NAME" SetVolume d 0.25
+test "$(busctl get-property "$NAME" "$OBJECT" "$NAME" Volume)" = "d 0.25"
+
+busctl call "$NAME" "$OBJECT" "$NAME" Pause
+test "$(busctl get-property "$NAME" "$OBJECT" "$NAME" Paused)" = "b true"
+
+busctl call "$NAME" "$OBJECT" "$NAME" Toggle
+test "$(busctl get-property "$NAME" "$OBJECT" "$NAME" Paused)" = "b false"
+
+busctl get-property "$NAME" "$OBJECT" "$NAME" Running ActivePack Packs
+
+echo "D-Bus interface OK"
@@ -0,0 +1,31 @@
+#!/bin/sh
+# Stand-in for wayvibes used by the supervisor tests in
+# src-tauri/src/services/wayvibes-service.rs and scripts/restart-storm-test.sh.
+# Appends one "launch" line per start to $FAKE_WAYVIBES_LOG. With
+# FAKE_WAYVIBES_CONTROL=1 it also advertises --control and appends a
+# "control" line for every command received on stdin.
+log="${FAKE_WAYVIBES_LOG:-/dev/null}"
+
+case "$1" in
+  --version)
+    echo "wayvibes 0.0.1"
+    exit 0
+    ;;
+  --help | -h)
+    echo "Usage: wayvibes [soundpack_path] -v <volume 0.0-10.0> [--background]"
+    if [ "${FAKE_WAYVIBES_CONTROL:-0}" = 1 ]; then
+      echo "  --control   read 'pack <path>' and 'volume <value>' lines from stdin"
+    fi
+    exit 0
+    ;;
+esac
+
+echo "launch $$ $*" >> "$log"
+echo "fake wayvibes playing $1"
+
+if [ "${FAKE_WAYVIBES_CONTROL:-0}" = 1 ]; then
+  while IFS= read -r line; do
+    echo "control $$ $line" >> "$log"
+  done
+fi
+while :; do sleep 1; done
@@ -0,0 +1,86 @@
+#!/usr/bin/env bash
+# Fire a burst of set_volume calls at the control socket, as a slider drag
+# would, and check that the backend relaunches wayvibes at most twice.
+# wayvibes is replaced by scripts/fake-wayvibes.sh, which logs every launch.
+#
+# Usage: scripts/restart-storm-test.sh [--control] [events] [path-to-wayvibes-ui-binary]
+#   --control  let the fake accept commands on stdin; expects zero relaunches
+set -euo pipefail
+
+CONTROL=0
+if [ "${1:-}" = "--control" ]; then
+  CONTROL=1
+  shift
+fi
+EVENTS="${1:-40}"
+BIN="${2:-src-tauri/target/debug/wayvibes-ui}"
+CLIENT="src-tauri/target/debug/examples/control-client"
+FAKE="$(realpath scripts/fake-wayvibes.sh)"
+
+test -x "$CLIENT" || (cd src-tauri && cargo build --example control-client)
+
+WORKDIR="$(mktemp -d)"
+LAUNCH_LOG="$WORKDIR/launches.log"
+touch "$LAUNCH_LOG"
+
+# Isolate config, packs and runtime files from the real installation
+export XDG_CONFIG_HOME="$WORKDIR/config"
+export XDG_DATA_HOME="$WORKDIR/data"
+export XDG_RUNTIME_DIR="$WORKDIR/run"
+mkdir -p "$XDG_RUNTIME_DIR" && chmod 700 "$XDG_RUNTIME_DIR"
+mkdir -p "$XDG_CONFIG_HOME/wayvibes-ui" "$XDG_DATA_HOME/wayvibes-ui/packs/fake-pack"
+echo '{"name": "Fake pack"}' > "$XDG_DATA_HOME/wayvibes-ui/packs/fake-pack/config.json"
+cat > "$XDG_CONFIG_HOME/wayvibes-ui/config.json" <<JSON
+{
+  "activePackId": "fake-pack",
+  "volume": 0.5,
+  "paused": false,
+  "wayvibes": {
+    "binaryPath": "$FAKE",
+    "extraArgs": [],
+    "env": { "FAKE_WAYVIBES_LOG": "$LAUNCH_LOG", "FAKE_WAYVIBES_CONTROL": "$CONTROL" }
+  }
+}
+JSON
+
+"$BIN" &
+APP_PID=$!
+
+cleanup() {
+  kill "$APP_PID" 2>/dev/null || true
+  pkill -x fake-wayvibes.s 2>/dev/null || true
+  rm -rf "$WORKDIR"
+}
+trap cleanup EXIT
+
+# Wait for the socket and for the launch that restores playback on startup
+for _ in $(seq 1 50); do
+  test -S "$XDG_RUNTIME_DIR/wayvibes-ui/control.sock" && grep -q '^launch' "$LAUNCH_LOG" && break
+  sleep 0.2
+done
+BEFORE="$(grep -c '^launch' "$LAUNCH_LOG" || true)"
+
+CLIENT_PIDS=()
+for i in $(seq 1 "$EVENTS"); do
+  volume="$(awk -v i="$i" -v n="$EVENTS" 'BEGIN { printf "%.3f", i / n }')"
+  "$CLIENT" set_volume "{\"volume\": $volume}" >/dev/null &
+  CLIENT_PIDS+=($!)
+  sleep 0.01
+done
+wait "${CLIENT_PIDS[@]}" || true
+sleep 1
+
+AFTER="$(grep -c '^launch' "$LAUNCH_LOG" || true)"
+RELAUNCHES=$((AFTER - BEFORE))
+echo "$EVENTS volume events -> $RELAUNCHES relaunch(es)"
+grep '^control' "$LAUNCH_LOG" || true
+
+LIMIT=2
+if [ "$CONTROL" = 1 ]; then
+  LIMIT=0
+fi
+if [ "$RELAUNCHES" -gt "$LIMIT" ]; then
+  echo "expected at most $LIMIT relaunch(es)" >&2
+  exit 1
+fi
+echo "Restart coalescing OK"
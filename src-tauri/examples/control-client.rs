@@ -0,0 +1,80 @@
+//! Minimal client for the control socket, used to exercise the JSON-RPC API by hand.
+//!
+//! Usage:
+//!   cargo run --example control-client -- get_wayvibes_status
+//!   cargo run --example control-client -- set_volume '{"volume": 0.4}'
+//!   cargo run --example control-client -- subscribe
+
+use std::env;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+fn socket_path() -> PathBuf {
+  if let Ok(path) = env::var("WAYVIBES_UI_SOCKET") {
+    return PathBuf::from(path);
+  }
+  match env::var("XDG_RUNTIME_DIR") {
+    Ok(runtime_dir) => PathBuf::from(runtime_dir).join("wayvibes-ui").join("control.sock"),
+    Err(_) => {
+      let user = env::var("USER").unwrap_or_else(|_| "default".into());
+      env::temp_dir()
+        .join(format!("wayvibes-ui-{}", user))
+        .join("control.sock")
+    }
+  }
+}
+
+fn main() -> ExitCode {
+  let mut args = env::args().skip(1);
+  let Some(method) = args.next() else {
+    eprintln!("usage: control-client <method> [params-json]");
+    return ExitCode::FAILURE;
+  };
+  let params = match args.next() {
+    Some(raw) => match serde_json::from_str::<serde_json::Value>(&raw) {
+      Ok(params) => params,
+      Err(err) => {
+        eprintln!("invalid params: {}", err);
+        return ExitCode::FAILURE;
+      }
+    },
+    None => serde_json::json!({}),
+  };
+
+  let path = socket_path();
+  let mut stream = match UnixStream::connect(&path) {
+    Ok(stream) => stream,
+    Err(err) => {
+      eprintln!("failed to connect to {}: {}", path.display(), err);
+      return ExitCode::FAILURE;
+    }
+  };
+
+  let request = serde_json::json!({
+    "jsonrpc": "2.0",
+    "id": 1,
+    "method": method,
+    "params": params,
+  });
+  if let Err(err) = writeln!(stream, "{}", request) {
+    eprintln!("failed to send request: {}", err);
+    return ExitCode::FAILURE;
+  }
+
+  // `subscribe` keeps the connection open and prints every event notification
+  let follow = method == "subscribe";
+  for line in BufReader::new(stream).lines() {
+    let Ok(line) = line else { break };
+    println!("{}", line);
+    if !follow {
+      let failed = serde_json::from_str::<serde_json::Value>(&line)
+        .map(|response| response.get("error").is_some())
+        .unwrap_or(true);
+      return if failed { ExitCode::FAILURE } else { ExitCode::SUCCESS };
+    }
+  }
+
+  ExitCode::SUCCESS
+}
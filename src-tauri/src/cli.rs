@@ -0,0 +1,261 @@
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+use serde_json::{json, Value};
+
+use crate::control;
+use crate::error::AppError;
+use crate::models::{AppConfig, AppEvent, PreviewInput, PreviewRequest, WayvibesStatus};
+use crate::paths;
+use crate::services::{audio_service, preview_service};
+
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+const USAGE: &str = "uso: wayvibes-ui status [--follow] [--format text|waybar]";
+const PREVIEW_USAGE: &str =
+  "uso: wayvibes-ui preview <pacote> [--text TEXTO] [--interval MS] --output ARQUIVO.wav";
+const PREVIEW_TEXT: &str = "hello world";
+
+/// Last known app state, or `None` when the app is not reachable
+type StateView<'a> = Option<(&'a AppConfig, &'a WayvibesStatus)>;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Format {
+  Text,
+  Waybar,
+}
+
+/// Run a CLI subcommand, or return `None` to start the GUI
+pub fn run(args: &[String]) -> Option<i32> {
+  match args.first().map(String::as_str) {
+    Some("status") => Some(status(&args[1..])),
+    Some("preview") => Some(preview(&args[1..])),
+    _ => None,
+  }
+}
+
+fn status(args: &[String]) -> i32 {
+  let mut follow = false;
+  let mut format = Format::Text;
+  let mut args = args.iter();
+  while let Some(arg) = args.next() {
+    match arg.as_str() {
+      "--follow" | "-f" => follow = true,
+      "--format" => match args.next().map(String::as_str) {
+        Some("text") => format = Format::Text,
+        Some("waybar") => format = Format::Waybar,
+        _ => {
+          eprintln!("{}", USAGE);
+          return 2;
+        }
+      },
+      _ => {
+        eprintln!("{}", USAGE);
+        return 2;
+      }
+    }
+  }
+
+  let mut last_line = None;
+  loop {
+    let result = watch(follow, &mut |state| {
+      print_state(format, state, &mut last_line)
+    });
+
+    match result {
+      Ok(()) if !follow => return 0,
+      Ok(()) => {}
+      Err(err) => {
+        print_state(format, None, &mut last_line);
+        if !follow {
+          eprintln!("{}", err);
+          return 1;
+        }
+      }
+    }
+
+    // The app went away; wait for it to come back instead of exiting the module
+    thread::sleep(RECONNECT_DELAY);
+  }
+}
+
+/// Render typed text with an installed pack to a WAV file. Works without the app running.
+fn preview(args: &[String]) -> i32 {
+  let mut pack_id = None;
+  let mut text = PREVIEW_TEXT.to_string();
+  let mut interval_ms = None;
+  let mut output = None;
+  let usage = || {
+    eprintln!("{}", PREVIEW_USAGE);
+    2
+  };
+  let mut args = args.iter();
+  while let Some(arg) = args.next() {
+    match (arg.as_str(), args.clone().next()) {
+      ("--text" | "-t", Some(value)) => text = value.clone(),
+      ("--interval" | "-i", Some(value)) => match value.parse::<f64>() {
+        Ok(value) => interval_ms = Some(value),
+        Err(_) => return usage(),
+      },
+      ("--output" | "-o", Some(value)) => output = Some(value.clone()),
+      (_, _) if pack_id.is_none() && !arg.starts_with('-') => {
+        pack_id = Some(arg.clone());
+        continue;
+      }
+      _ => return usage(),
+    }
+    args.next();
+  }
+  let (Some(pack_id), Some(output)) = (pack_id, output) else {
+    return usage();
+  };
+
+  let request = PreviewRequest {
+    pack_id,
+    input: PreviewInput::Text { text, interval_ms },
+  };
+  let result = paths::packs_dir()
+    .and_then(|packs_dir| preview_service::render(&packs_dir, &request))
+    .and_then(|buffer| audio_service::write_wav(Path::new(&output), &buffer));
+  match result {
+    Ok(()) => 0,
+    Err(err) => {
+      eprintln!("{}", err);
+      1
+    }
+  }
+}
+
+/// Read the current state from the running app, then keep reporting every change when following
+fn watch(
+  follow: bool,
+  on_state: &mut dyn FnMut(StateView),
+) -> Result<(), AppError> {
+  let mut stream = UnixStream::connect(control::socket_path()?)?;
+  let mut requests = Vec::new();
+  if follow {
+    requests.push(json!({ "jsonrpc": "2.0", "id": "subscribe", "method": "subscribe" }));
+  }
+  requests.push(json!({ "jsonrpc": "2.0", "id": "config", "method": "get_config" }));
+  requests.push(json!({ "jsonrpc": "2.0", "id": "status", "method": "get_wayvibes_status" }));
+  for request in requests {
+    writeln!(stream, "{}", request)?;
+  }
+
+  let mut config: Option<AppConfig> = None;
+  let mut status: Option<WayvibesStatus> = None;
+
+  for line in BufReader::new(stream).lines() {
+    let message: Value = serde_json::from_str(&line?)?;
+
+    if let Some(error) = message.get("error") {
+      return Err(AppError::WayvibesCommand(error.to_string()));
+    }
+
+    match message.get("id").and_then(Value::as_str) {
+      Some("config") => config = Some(serde_json::from_value(message["result"].clone())?),
+      Some("status") => status = Some(serde_json::from_value(message["result"].clone())?),
+      Some(_) => continue,
+      None => {
+        let event: AppEvent = serde_json::from_value(message["params"].clone())?;
+        match event {
+          AppEvent::StateChanged {
+            config: next_config,
+            status: next_status,
+          } => {
+            config = Some(next_config);
+            status = Some(next_status);
+          }
+          _ => continue,
+        }
+      }
+    }
+
+    if let (Some(config), Some(status)) = (&config, &status) {
+      on_state(Some((config, status)));
+      if !follow {
+        return Ok(());
+      }
+    }
+  }
+
+  Err(AppError::WayvibesCommand("Conexão com o WayVibes encerrada".into()))
+}
+
+fn print_state(
+  format: Format,
+  state: StateView,
+  last_line: &mut Option<String>,
+) {
+  let line = match format {
+    Format::Text => text_line(state),
+    Format::Waybar => waybar_line(state),
+  };
+
+  // Waybar redraws on every line, so only print actual changes
+  if last_line.as_deref() == Some(line.as_str()) {
+    return;
+  }
+
+  let mut stdout = io::stdout().lock();
+  let _ = writeln!(stdout, "{}", line);
+  let _ = stdout.flush();
+  *last_line = Some(line);
+}
+
+fn state_class(state: StateView) -> &'static str {
+  match state {
+    Some((_, status)) if status.installed && status.running => "running",
+    Some((_, status)) if status.installed => "paused",
+    _ => "missing",
+  }
+}
+
+fn text_line(state: StateView) -> String {
+  match state {
+    Some((config, status)) => format!(
+      "{} {}% {}",
+      state_class(Some((config, status))),
+      percentage(config),
+      config.active_pack_id.as_deref().unwrap_or("-"),
+    ),
+    None => "missing".into(),
+  }
+}
+
+fn waybar_line(state: StateView) -> String {
+  let class = state_class(state);
+  let payload = match state {
+    Some((config, status)) => {
+      let pack = config.active_pack_id.as_deref().unwrap_or("nenhum");
+      let text = match class {
+        "running" => format!("{}%", percentage(config)),
+        "paused" => "pausado".to_string(),
+        _ => "wayvibes ausente".to_string(),
+      };
+      let mut tooltip = format!("WayVibes\nPacote: {}\nVolume: {}%", pack, percentage(config));
+      if let Some(version) = &status.version {
+        tooltip.push_str(&format!("\nVersão: {}", version));
+      }
+      json!({
+        "text": text,
+        "tooltip": tooltip,
+        "class": class,
+        "percentage": percentage(config),
+      })
+    }
+    None => json!({
+      "text": "",
+      "tooltip": "WayVibes UI não está em execução",
+      "class": class,
+      "percentage": 0,
+    }),
+  };
+  payload.to_string()
+}
+
+fn percentage(config: &AppConfig) -> u32 {
+  (config.volume.clamp(0.0, 1.0) * 100.0).round() as u32
+}
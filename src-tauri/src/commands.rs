@@ -1,5 +1,9 @@
 #[path = "commands/config-commands.rs"]
 pub mod config_commands;
+#[path = "commands/diagnostics-commands.rs"]
+pub mod diagnostics_commands;
+#[path = "commands/schedule-commands.rs"]
+pub mod schedule_commands;
 #[path = "commands/sound-packs-commands.rs"]
 pub mod sound_packs_commands;
 #[path = "commands/wayvibes-commands.rs"]
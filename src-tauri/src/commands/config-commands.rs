@@ -1,6 +1,9 @@
-use tauri::State;
+use tauri::{AppHandle, State};
+use tauri_plugin_opener::OpenerExt;
 
-use crate::models::AppConfig;
+use crate::logging;
+use crate::models::{AppConfig, LogLevel};
+use crate::paths;
 use crate::state::AppState;
 
 #[tauri::command]
@@ -11,3 +14,32 @@ pub fn get_config(state: State<'_, AppState>) -> Result<AppConfig, String> {
     .map_err(|_| "Falha ao acessar configuração".to_string())?;
   Ok(config.clone())
 }
+
+#[tauri::command]
+pub fn set_log_level(state: State<'_, AppState>, level: LogLevel) -> Result<(), String> {
+  {
+    let mut config = state
+      .config
+      .lock()
+      .map_err(|_| "Falha ao acessar configuração".to_string())?;
+    config.log_level = level;
+    state
+      .save_config(&config)
+      .map_err(|err| err.to_string())?;
+  }
+
+  logging::set_level(level);
+  state.publish_state();
+  Ok(())
+}
+
+/// Open the folder holding `app.log` and `wayvibes.log` in the file manager
+#[tauri::command]
+pub fn open_log_folder(app: AppHandle) -> Result<(), String> {
+  let dir = paths::logs_dir().map_err(|err| err.to_string())?;
+  std::fs::create_dir_all(&dir).map_err(|err| err.to_string())?;
+  app
+    .opener()
+    .open_path(dir.to_string_lossy(), None::<&str>)
+    .map_err(|err| err.to_string())
+}
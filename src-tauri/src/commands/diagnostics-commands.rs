@@ -0,0 +1,63 @@
+use std::path::PathBuf;
+
+use directories::UserDirs;
+use tauri::State;
+
+use crate::logging;
+use crate::paths;
+use crate::services::diagnostics_service::{self, Diagnostics};
+use crate::services::{sound_pack_service, wayvibes_locator, wayvibes_service};
+use crate::state::AppState;
+
+/// Lines of the in-memory wayvibes log included in the bundle
+const WAYVIBES_LOG_TAIL: usize = 500;
+
+/// Write a diagnostics zip to `path` (or the downloads folder) and return where it went
+#[tauri::command]
+pub async fn export_diagnostics(
+  state: State<'_, AppState>,
+  path: Option<String>,
+) -> Result<String, String> {
+  let config = state
+    .config
+    .lock()
+    .map_err(|_| "Falha ao acessar configuração".to_string())?
+    .clone();
+
+  let status = wayvibes_service::get_status(&config.wayvibes, state.wayvibes.lifecycle())
+    .await
+    .map_err(|err| err.to_string())?;
+  let diagnostics = Diagnostics {
+    packs: sound_pack_service::list_packs(&state.packs_dir).unwrap_or_default(),
+    location: wayvibes_locator::locate(&config.wayvibes),
+    status,
+    wayvibes_logs: state
+      .wayvibes
+      .logs
+      .query(Some(WAYVIBES_LOG_TAIL), None, None, None),
+    errors: logging::recent_errors(),
+    config,
+  };
+
+  let path = path.map(PathBuf::from).unwrap_or_else(default_path);
+  let logs_dir = paths::logs_dir().map_err(|err| err.to_string())?;
+  let target = path.clone();
+  tokio::task::spawn_blocking(move || diagnostics_service::export(&target, &diagnostics, &logs_dir))
+    .await
+    .map_err(|err| err.to_string())?
+    .map_err(|err| err.to_string())?;
+
+  log::info!("Diagnostics written to {}", path.display());
+  Ok(path.to_string_lossy().to_string())
+}
+
+fn default_path() -> PathBuf {
+  let dir = UserDirs::new()
+    .and_then(|dirs| dirs.download_dir().map(|dir| dir.to_path_buf()))
+    .or_else(|| paths::project_dirs().ok().map(|dirs| dirs.data_dir().to_path_buf()))
+    .unwrap_or_else(std::env::temp_dir);
+  dir.join(format!(
+    "wayvibes-ui-diagnostics-{}.zip",
+    chrono::Local::now().format("%Y%m%d-%H%M%S")
+  ))
+}
@@ -0,0 +1,41 @@
+use tauri::{AppHandle, State};
+
+use crate::models::{ActiveSchedule, ScheduleRule};
+use crate::scheduler;
+use crate::services::scheduler_service;
+use crate::state::AppState;
+
+#[tauri::command]
+pub async fn set_schedules(
+  app: AppHandle,
+  state: State<'_, AppState>,
+  schedules: Vec<ScheduleRule>,
+) -> Result<(), String> {
+  {
+    let mut config = state
+      .config
+      .lock()
+      .map_err(|_| "Falha ao acessar configuração".to_string())?;
+    scheduler_service::validate_rules(&schedules, &config.profiles)
+      .map_err(|err| err.to_string())?;
+    config.schedules = schedules;
+    state
+      .save_config(&config)
+      .map_err(|err| err.to_string())?;
+  }
+
+  state.publish_state();
+  scheduler::evaluate(&app).await;
+  Ok(())
+}
+
+#[tauri::command]
+pub async fn get_active_schedule(
+  state: State<'_, AppState>,
+) -> Result<Option<ActiveSchedule>, String> {
+  let current = state
+    .schedule_override
+    .lock()
+    .map_err(|_| "Falha ao acessar agenda".to_string())?;
+  Ok(current.as_ref().map(|schedule| schedule.active.clone()))
+}
@@ -1,25 +1,136 @@
 use std::path::PathBuf;
 
+use tauri::ipc::Response;
 use tauri::State;
 
-use crate::models::SoundPack;
-use crate::services::sound_pack_service;
+use crate::commands::wayvibes_commands;
+use crate::error::AppError;
+use crate::models::{
+  ComposedPack, KeyChange, KeyboardLayout, NewPack, PackCoverage, PackLoudness, PackMapping,
+  PackSettings, PreviewRequest, ProcessOptions, ProcessReport, SoundPack, TranscodeOptions,
+  TranscodeReport,
+};
+use crate::services::{
+  pack_builder_service, pack_editor_service, pack_processing_service, pack_settings_service,
+  preview_service, sound_pack_service, transcode_service, wayvibes_locator, wayvibes_service,
+};
+use crate::services::pack_manifest::PackManifest;
+use crate::services::sound_pack_service::StagedPack;
+use crate::services::wayvibes_locator::WayvibesBinary;
 use crate::state::AppState;
 
 #[tauri::command]
 pub fn get_sound_packs(state: State<'_, AppState>) -> Result<Vec<SoundPack>, String> {
-  sound_pack_service::list_packs(&state.packs_dir).map_err(|err| err.to_string())
+  state.list_packs().map_err(|err| err.to_string())
 }
 
+/// Install a pack archive, converting its sounds to WAV when `transcode` is given
 #[tauri::command]
-pub fn import_sound_pack(state: State<'_, AppState>, path: String) -> Result<SoundPack, String> {
-  println!("is executing here? after invoking import_sound_pack");
+pub async fn import_sound_pack(
+  state: State<'_, AppState>,
+  path: String,
+  transcode: Option<TranscodeOptions>,
+) -> Result<SoundPack, String> {
+  let mut pack = stage_and_install(&state, Check::Wayvibes, move |packs_dir| {
+    sound_pack_service::import_pack(&PathBuf::from(path), &packs_dir, transcode.as_ref())
+  })
+  .await?;
+  pack.warnings = sound_pack_service::unsupported_features(&pack, &state.wayvibes_capabilities());
+  for warning in &pack.warnings {
+    log::warn!("Pack {}: {}", pack.id, warning);
+  }
 
-  let pack = sound_pack_service::import_pack(&PathBuf::from(path), &state.packs_dir)
-    .map_err(|err| err.to_string())?;
+  adopt_if_first(&state, &pack)?;
+  state.publish_packs();
+  state.publish_state();
+  Ok(pack)
+}
+
+/// Build a pack from loose audio files
+#[tauri::command]
+pub async fn create_sound_pack(
+  state: State<'_, AppState>,
+  request: NewPack,
+) -> Result<SoundPack, String> {
+  let pack = stage_and_install(&state, Check::Manifest, move |packs_dir| {
+    pack_builder_service::create_pack(&packs_dir, &request)
+  })
+  .await?;
+
+  adopt_if_first(&state, &pack)?;
+  state.publish_packs();
+  state.publish_state();
+  Ok(pack)
+}
+
+/// Build a pack from a base pack with some keys taken from other installed packs
+#[tauri::command]
+pub async fn compose_sound_pack(
+  state: State<'_, AppState>,
+  request: ComposedPack,
+) -> Result<SoundPack, String> {
+  let pack = stage_and_install(&state, Check::Manifest, move |packs_dir| {
+    pack_builder_service::compose_pack(&packs_dir, &request)
+  })
+  .await?;
+
+  state.publish_packs();
+  Ok(pack)
+}
+
+/// Copy a pack with its sprite split into per-key files, or its files joined into a sprite
+#[tauri::command]
+pub async fn convert_sound_pack(
+  state: State<'_, AppState>,
+  pack_id: String,
+) -> Result<SoundPack, String> {
+  let pack = stage_and_install(&state, Check::Manifest, move |packs_dir| {
+    sound_pack_service::convert_pack(&packs_dir, &pack_id)
+  })
+  .await?;
+
+  state.publish_packs();
+  Ok(pack)
+}
+
+/// Convert an installed pack's sounds to WAV and relaunch wayvibes if it is playing
+#[tauri::command]
+pub async fn normalize_sound_pack(
+  state: State<'_, AppState>,
+  pack_id: String,
+  options: TranscodeOptions,
+) -> Result<TranscodeReport, String> {
+  let id = pack_id.clone();
+  let report = stage_and_install(&state, Check::Manifest, move |packs_dir| {
+    transcode_service::normalize_pack(&packs_dir, &id, &options)
+  })
+  .await?;
+
+  if is_active(&state, &pack_id)? {
+    wayvibes_commands::reload_playback(state.clone()).await?;
+  }
+  state.publish_packs();
+  Ok(report)
+}
+
+/// Trim and normalize a pack's sounds into a new pack
+#[tauri::command]
+pub async fn process_sound_pack(
+  state: State<'_, AppState>,
+  pack_id: String,
+  options: ProcessOptions,
+) -> Result<ProcessReport, String> {
+  let report = stage_and_install(&state, Check::Manifest, move |packs_dir| {
+    pack_processing_service::process_pack(&packs_dir, &pack_id, &options)
+  })
+  .await?;
 
-  println!("is dying here?");
+  state.publish_packs();
+  Ok(report)
+}
 
+/// Make a freshly added pack the active one when none is selected yet
+fn adopt_if_first(state: &AppState, pack: &SoundPack) -> Result<(), String> {
   let mut config = state
     .config
     .lock()
@@ -30,8 +141,63 @@ pub fn import_sound_pack(state: State<'_, AppState>, path: String) -> Result<Sou
       .save_config(&config)
       .map_err(|err| err.to_string())?;
   }
+  Ok(())
+}
 
-  Ok(pack)
+/// What a staged pack must pass before it is installed
+enum Check {
+  /// Its manifest agrees with its files; enough for packs the app wrote itself, which
+  /// doesn't need wayvibes at all
+  Manifest,
+  /// wayvibes loads it, for packs from elsewhere
+  Wayvibes,
+}
+
+/// Build a pack off the main thread, check it, and install it only if it passes. The
+/// wayvibes trial runs through the supervisor so it can't interleave with a start or stop.
+async fn stage_and_install<T: Send + 'static>(
+  state: &AppState,
+  check: Check,
+  build: impl FnOnce(PathBuf) -> Result<StagedPack<T>, AppError> + Send + 'static,
+) -> Result<T, String> {
+  let binary = match check {
+    Check::Wayvibes => Some(wayvibes_binary(state)?),
+    Check::Manifest => None,
+  };
+  let check_manifest = binary.is_none();
+  let packs_dir = state.packs_dir.clone();
+  let staged = tauri::async_runtime::spawn_blocking(move || {
+    let staged = build(packs_dir)?;
+    if check_manifest {
+      let checked =
+        PackManifest::load(&staged.dir).and_then(|manifest| manifest.validate(&staged.dir));
+      if let Err(err) = checked {
+        return Err(staged.reject(err));
+      }
+    }
+    Ok(staged)
+  })
+  .await
+  .map_err(|err| err.to_string())?
+  .map_err(|err| err.to_string())?;
+
+  if let Some(binary) = binary {
+    let validation = wayvibes_service::validate_pack(&state.wayvibes, &binary, &staged.dir).await;
+    if let Err(err) = validation {
+      return Err(staged.reject(err).to_string());
+    }
+  }
+  staged.install().map_err(|err| err.to_string())
+}
+
+fn wayvibes_binary(state: &AppState) -> Result<WayvibesBinary, String> {
+  let settings = state
+    .config
+    .lock()
+    .map_err(|_| "Falha ao acessar configuração".to_string())?
+    .wayvibes
+    .clone();
+  wayvibes_locator::resolve(&settings).map_err(|err| err.to_string())
 }
 
 #[tauri::command]
@@ -39,17 +205,21 @@ pub fn delete_sound_pack(state: State<'_, AppState>, pack_id: String) -> Result<
   sound_pack_service::delete_pack(&pack_id, &state.packs_dir)
     .map_err(|err| err.to_string())?;
 
-  let mut config = state
-    .config
-    .lock()
-    .map_err(|_| "Falha ao acessar configuração".to_string())?;
-  if config.active_pack_id.as_deref() == Some(pack_id.as_str()) {
-    config.active_pack_id = None;
-    state
-      .save_config(&config)
-      .map_err(|err| err.to_string())?;
+  {
+    let mut config = state
+      .config
+      .lock()
+      .map_err(|_| "Falha ao acessar configuração".to_string())?;
+    if config.active_pack_id.as_deref() == Some(pack_id.as_str()) {
+      config.active_pack_id = None;
+      state
+        .save_config(&config)
+        .map_err(|err| err.to_string())?;
+    }
   }
 
+  state.publish_packs();
+  state.publish_state();
   Ok(())
 }
 
@@ -64,3 +234,111 @@ pub fn get_pack_path(state: State<'_, AppState>, pack_id: String) -> Result<Stri
     .map(|s| s.to_string())
     .ok_or_else(|| "Caminho inválido".into())
 }
+
+#[tauri::command]
+pub async fn set_pack_settings(
+  state: State<'_, AppState>,
+  pack_id: String,
+  settings: PackSettings,
+) -> Result<(), String> {
+  if !state.packs_dir.join(&pack_id).exists() {
+    return Err("Pacote não encontrado".into());
+  }
+  pack_settings_service::save(&state.packs_dir, &pack_id, &settings)
+    .map_err(|err| err.to_string())?;
+
+  let result = if is_active(&state, &pack_id)? {
+    wayvibes_commands::refresh_playback(state.clone()).await
+  } else {
+    Ok(())
+  };
+
+  state.publish_packs();
+  state.publish_state();
+  result
+}
+
+/// Measure the loudness of a pack's sounds and suggest a gain for it
+#[tauri::command]
+pub async fn analyze_pack_loudness(
+  state: State<'_, AppState>,
+  pack_id: String,
+) -> Result<PackLoudness, String> {
+  let packs_dir = state.packs_dir.clone();
+  tauri::async_runtime::spawn_blocking(move || {
+    pack_settings_service::analyze(&packs_dir, &pack_id)
+  })
+  .await
+  .map_err(|err| err.to_string())?
+  .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub fn get_pack_mapping(
+  state: State<'_, AppState>,
+  pack_id: String,
+) -> Result<PackMapping, String> {
+  pack_editor_service::mapping(&state.packs_dir, &pack_id).map_err(|err| err.to_string())
+}
+
+/// Which keys of a layout the pack maps, leaves to its fallback sound or keeps silent
+#[tauri::command]
+pub fn pack_coverage(
+  state: State<'_, AppState>,
+  pack_id: String,
+  layout: KeyboardLayout,
+) -> Result<PackCoverage, String> {
+  pack_editor_service::coverage(&state.packs_dir, &pack_id, layout).map_err(|err| err.to_string())
+}
+
+/// Render keystrokes with a pack into a WAV file, without touching the running wayvibes
+#[tauri::command]
+pub async fn render_pack_preview(
+  state: State<'_, AppState>,
+  request: PreviewRequest,
+) -> Result<Response, String> {
+  let packs_dir = state.packs_dir.clone();
+  tauri::async_runtime::spawn_blocking(move || {
+    preview_service::render_wav(&packs_dir, &request)
+  })
+  .await
+  .map_err(|err| err.to_string())?
+  .map(Response::new)
+  .map_err(|err| err.to_string())
+}
+
+/// Change the sounds of individual keys and hot-restart wayvibes if the pack is playing
+#[tauri::command]
+pub async fn set_key_sounds(
+  state: State<'_, AppState>,
+  pack_id: String,
+  changes: Vec<KeyChange>,
+) -> Result<PackMapping, String> {
+  let mapping = pack_editor_service::apply_changes(&state.packs_dir, &pack_id, changes)
+    .map_err(|err| err.to_string())?;
+
+  if is_active(&state, &pack_id)? {
+    wayvibes_commands::reload_playback(state.clone()).await?;
+  }
+  Ok(mapping)
+}
+
+/// Copy audio files into a pack so they can be assigned to keys
+#[tauri::command]
+pub fn add_pack_sounds(
+  state: State<'_, AppState>,
+  pack_id: String,
+  files: Vec<String>,
+) -> Result<Vec<String>, String> {
+  pack_editor_service::add_sounds(&state.packs_dir, &pack_id, &files)
+    .map_err(|err| err.to_string())
+}
+
+/// Whether the pack is the one playing, picked by the user or by a schedule in effect
+fn is_active(state: &AppState, pack_id: &str) -> Result<bool, String> {
+  let config = state
+    .config
+    .lock()
+    .map_err(|_| "Falha ao acessar configuração".to_string())?;
+  Ok(state.playback(&config).pack_id.as_deref() == Some(pack_id))
+}
@@ -1,91 +1,173 @@
+use std::path::PathBuf;
+
 use tauri::State;
 
 use crate::error::AppError;
-use crate::models::WayvibesStatus;
-use crate::services::wayvibes_service;
+use crate::models::{
+  AppConfig, LogLine, LogStream, PreflightReport, Profile, VolumeCurve, WayvibesLocation,
+  WayvibesSettings, WayvibesStatus,
+};
+use crate::services::{
+  pack_settings_service, profile_service, scheduler_service, volume_service, wayvibes_locator,
+  wayvibes_service,
+};
 use crate::state::AppState;
 
 #[tauri::command]
-pub async fn get_wayvibes_status() -> Result<WayvibesStatus, String> {
-  wayvibes_service::get_status()
+pub async fn get_wayvibes_status(state: State<'_, AppState>) -> Result<WayvibesStatus, String> {
+  let settings = wayvibes_settings(&state)?;
+  wayvibes_service::get_status(&settings, state.wayvibes.lifecycle())
     .await
     .map_err(|err| err.to_string())
 }
 
 #[tauri::command]
-pub async fn set_volume(state: State<'_, AppState>, volume: f32) -> Result<(), String> {
-  let volume = volume.clamp(0.0, 1.0);
+pub async fn run_preflight(state: State<'_, AppState>) -> Result<PreflightReport, String> {
+  let settings = wayvibes_settings(&state)?;
+  Ok(wayvibes_service::preflight(settings).await)
+}
 
-  let (active_pack_id, paused) = {
+/// Captured wayvibes output, newest last
+#[tauri::command]
+pub fn get_wayvibes_logs(
+  state: State<'_, AppState>,
+  tail: Option<usize>,
+  filter: Option<String>,
+  stream: Option<LogStream>,
+) -> Result<Vec<LogLine>, String> {
+  Ok(state.wayvibes.logs.query(tail, filter.as_deref(), stream, None))
+}
+
+#[tauri::command]
+pub fn locate_wayvibes(state: State<'_, AppState>) -> Result<WayvibesLocation, String> {
+  let settings = wayvibes_settings(&state)?;
+  Ok(wayvibes_locator::locate(&settings))
+}
+
+#[tauri::command]
+pub fn set_wayvibes_settings(
+  state: State<'_, AppState>,
+  settings: WayvibesSettings,
+) -> Result<WayvibesLocation, String> {
+  {
     let mut config = state
       .config
       .lock()
       .map_err(|_| "Falha ao acessar configuração".to_string())?;
-    config.volume = volume;
+    config.wayvibes = settings.clone();
     state
       .save_config(&config)
       .map_err(|err| err.to_string())?;
-    (config.active_pack_id.clone(), config.paused)
-  };
-
-  // If not paused and there's an active pack, restart with new volume
-  if !paused {
-    if let Some(pack_id) = active_pack_id {
-      let pack_path = state.packs_dir.join(&pack_id);
-      if pack_path.exists() {
-        if let Err(err) = wayvibes_service::restart(&pack_path, volume).await {
-          if !matches!(err, AppError::WayvibesMissing) {
-            return Err(err.to_string());
-          }
-        }
+  }
+
+  state.publish_state();
+  Ok(wayvibes_locator::locate(&settings))
+}
+
+// Lifecycle requests are queued while the config lock is held, so wayvibes applies them in
+// the same order the config changes were saved.
+
+#[tauri::command]
+pub async fn set_volume(state: State<'_, AppState>, volume: f32) -> Result<(), String> {
+  let volume = volume.clamp(0.0, 1.0);
+
+  let (pending, pack_changed) = {
+    let mut config = state
+      .config
+      .lock()
+      .map_err(|_| "Falha ao acessar configuração".to_string())?;
+
+    // A pack with its own volume keeps it; the global volume is left for the other packs
+    let playing = state.playback(&config).pack_id;
+    let pack = playing.and_then(|pack_id| {
+      let settings = pack_settings_service::get(&state.packs_dir, &pack_id);
+      settings.default_volume.map(|_| (pack_id, settings))
+    });
+    let pack_changed = pack.is_some();
+    match pack {
+      Some((pack_id, mut settings)) => {
+        settings.default_volume = Some(volume);
+        pack_settings_service::save(&state.packs_dir, &pack_id, &settings)
+          .map_err(|err| err.to_string())?;
+      }
+      None => {
+        config.volume = volume;
+        state
+          .save_config(&config)
+          .map_err(|err| err.to_string())?;
       }
     }
-  }
 
-  Ok(())
+    // If not paused and there's an active pack, restart with new volume
+    (queue_restart(&state, &config), pack_changed)
+  };
+
+  let result = match pending {
+    Some(pending) => ignore_missing(pending.await),
+    None => Ok(()),
+  };
+
+  if pack_changed {
+    state.publish_packs();
+  }
+  state.publish_state();
+  result
 }
 
 #[tauri::command]
 pub async fn toggle_pause(state: State<'_, AppState>) -> Result<(), String> {
-  let (paused, active_pack_id, volume) = {
+  change_paused(state, |paused| !paused).await
+}
+
+/// Pause or resume; a no-op when playback is already in that state, so repeated or racing
+/// calls can't flip it back
+#[tauri::command]
+pub async fn set_paused(state: State<'_, AppState>, paused: bool) -> Result<(), String> {
+  change_paused(state, |_| paused).await
+}
+
+/// Derive `paused` from its current value under the config lock, so concurrent callers
+/// never act on a stale read
+async fn change_paused(
+  state: State<'_, AppState>,
+  next: impl FnOnce(bool) -> bool,
+) -> Result<(), String> {
+  let pending = {
     let mut config = state
       .config
       .lock()
       .map_err(|_| "Falha ao acessar configuração".to_string())?;
-    config.paused = !config.paused;
+    let paused = next(config.paused);
+    if paused == config.paused {
+      return Ok(());
+    }
+    config.paused = paused;
     state
       .save_config(&config)
       .map_err(|err| err.to_string())?;
-    (config.paused, config.active_pack_id.clone(), config.volume)
-  };
 
-  if paused {
-    // Stop wayvibes
-    if let Err(err) = wayvibes_service::stop().await {
-      if !matches!(err, AppError::WayvibesMissing) {
-        return Err(err.to_string());
-      }
-    }
-  } else {
-    // Resume: start wayvibes with active pack and volume
-    if let Some(pack_id) = active_pack_id {
-      let pack_path = state.packs_dir.join(&pack_id);
-      if pack_path.exists() {
-        if let Err(err) = wayvibes_service::start(&pack_path, volume).await {
-          if !matches!(err, AppError::WayvibesMissing) {
-            return Err(err.to_string());
-          }
-        }
-      }
+    if config.paused {
+      Some(wayvibes_service::stop(&state.wayvibes, &config.wayvibes))
+    } else {
+      // Resume: start wayvibes with active pack and volume
+      playback_target(&state, &config).map(|(pack_path, volume)| {
+        wayvibes_service::start(&state.wayvibes, &config.wayvibes, &pack_path, volume)
+      })
     }
-  }
+  };
 
-  Ok(())
+  let result = match pending {
+    Some(pending) => ignore_missing(pending.await),
+    None => Ok(()),
+  };
+
+  state.publish_state();
+  result
 }
 
 #[tauri::command]
 pub async fn stop_wayvibes(state: State<'_, AppState>) -> Result<(), String> {
-  {
+  let pending = {
     let mut config = state
       .config
       .lock()
@@ -94,25 +176,22 @@ pub async fn stop_wayvibes(state: State<'_, AppState>) -> Result<(), String> {
     state
       .save_config(&config)
       .map_err(|err| err.to_string())?;
-  }
+    wayvibes_service::stop(&state.wayvibes, &config.wayvibes)
+  };
 
-  if let Err(err) = wayvibes_service::stop().await {
-    if !matches!(err, AppError::WayvibesMissing) {
-      return Err(err.to_string());
-    }
-  }
+  let result = ignore_missing(pending.await);
 
-  Ok(())
+  state.publish_state();
+  result
 }
 
 #[tauri::command]
 pub async fn set_active_pack(state: State<'_, AppState>, pack_id: String) -> Result<(), String> {
-  let pack_path = state.packs_dir.join(&pack_id);
-  if !pack_path.exists() {
+  if !state.packs_dir.join(&pack_id).exists() {
     return Err("Pacote não encontrado".into());
   }
 
-  let (paused, volume) = {
+  let pending = {
     let mut config = state
       .config
       .lock()
@@ -121,17 +200,285 @@ pub async fn set_active_pack(state: State<'_, AppState>, pack_id: String) -> Res
     state
       .save_config(&config)
       .map_err(|err| err.to_string())?;
-    (config.paused, config.volume)
+
+    // Switch wayvibes to the new pack if not paused; rapid switches are coalesced
+    queue_restart(&state, &config)
+  };
+
+  let result = match pending {
+    Some(pending) => ignore_missing(pending.await),
+    None => Ok(()),
   };
 
-  // Start wayvibes with the new pack if not paused
-  if !paused {
-    if let Err(err) = wayvibes_service::start(&pack_path, volume).await {
-      if !matches!(err, AppError::WayvibesMissing) {
-        return Err(err.to_string());
+  state.publish_state();
+  result
+}
+
+/// Replace the profiles; one still used by a schedule can't be removed
+#[tauri::command]
+pub async fn set_profiles(
+  state: State<'_, AppState>,
+  profiles: Vec<Profile>,
+) -> Result<(), String> {
+  profile_service::validate_profiles(&profiles).map_err(|err| err.to_string())?;
+
+  let pending = {
+    let mut config = state
+      .config
+      .lock()
+      .map_err(|_| "Falha ao acessar configuração".to_string())?;
+    scheduler_service::validate_rules(&config.schedules, &profiles)
+      .map_err(|err| err.to_string())?;
+    let active = config.active_profile_id.take();
+    config.active_profile_id =
+      active.filter(|id| profile_service::find(&profiles, id).is_some());
+    config.profiles = profiles;
+    state
+      .save_config(&config)
+      .map_err(|err| err.to_string())?;
+    queue_restart(&state, &config)
+  };
+
+  let result = match pending {
+    Some(pending) => ignore_missing(pending.await),
+    None => Ok(()),
+  };
+
+  state.publish_state();
+  result
+}
+
+/// Switch to a profile, and to its pack when it has one; `None` leaves profiles off
+#[tauri::command]
+pub async fn set_active_profile(
+  state: State<'_, AppState>,
+  profile_id: Option<String>,
+) -> Result<(), String> {
+  let pending = {
+    let mut config = state
+      .config
+      .lock()
+      .map_err(|_| "Falha ao acessar configuração".to_string())?;
+    if let Some(profile_id) = &profile_id {
+      let profile = profile_service::find(&config.profiles, profile_id)
+        .ok_or_else(|| "Perfil não encontrado".to_string())?;
+      if let Some(pack_id) = profile.pack_id.clone() {
+        if !state.packs_dir.join(&pack_id).exists() {
+          return Err("Pacote do perfil não encontrado".into());
+        }
+        config.active_pack_id = Some(pack_id);
       }
     }
+    config.active_profile_id = profile_id;
+    state
+      .save_config(&config)
+      .map_err(|err| err.to_string())?;
+    queue_restart(&state, &config)
+  };
+
+  let result = match pending {
+    Some(pending) => ignore_missing(pending.await),
+    None => Ok(()),
+  };
+
+  state.publish_state();
+  result
+}
+
+/// Silence playback without pausing. wayvibes keeps running at volume zero, changed in
+/// place when it supports live control, so unmuting is immediate.
+#[tauri::command]
+pub async fn set_muted(state: State<'_, AppState>, muted: bool) -> Result<(), String> {
+  change_muted(state, |_| muted).await
+}
+
+#[tauri::command]
+pub async fn toggle_mute(state: State<'_, AppState>) -> Result<(), String> {
+  change_muted(state, |muted| !muted).await
+}
+
+/// Derive `muted` from its current value under the config lock, like `change_paused`
+async fn change_muted(
+  state: State<'_, AppState>,
+  next: impl FnOnce(bool) -> bool,
+) -> Result<(), String> {
+  let pending = {
+    let mut config = state
+      .config
+      .lock()
+      .map_err(|_| "Falha ao acessar configuração".to_string())?;
+    config.muted = next(config.muted);
+    state
+      .save_config(&config)
+      .map_err(|err| err.to_string())?;
+    queue_restart(&state, &config)
+  };
+
+  let result = match pending {
+    Some(pending) => ignore_missing(pending.await),
+    None => Ok(()),
+  };
+
+  state.publish_state();
+  result
+}
+
+#[tauri::command]
+pub async fn set_volume_curve(
+  state: State<'_, AppState>,
+  curve: VolumeCurve,
+) -> Result<(), String> {
+  volume_service::validate_curve(curve).map_err(|err| err.to_string())?;
+
+  let pending = {
+    let mut config = state
+      .config
+      .lock()
+      .map_err(|_| "Falha ao acessar configuração".to_string())?;
+    config.volume_curve = curve;
+    state
+      .save_config(&config)
+      .map_err(|err| err.to_string())?;
+    queue_restart(&state, &config)
+  };
+
+  let result = match pending {
+    Some(pending) => ignore_missing(pending.await),
+    None => Ok(()),
+  };
+
+  state.publish_state();
+  result
+}
+
+/// Start the active pack again after launch unless playback was paused
+pub async fn restore_playback(state: State<'_, AppState>) -> Result<(), String> {
+  let pending = {
+    let config = state
+      .config
+      .lock()
+      .map_err(|_| "Falha ao acessar configuração".to_string())?;
+    playback_target(&state, &config).map(|(pack_path, volume)| {
+      wayvibes_service::start(&state.wayvibes, &config.wayvibes, &pack_path, volume)
+    })
+  };
+
+  let result = match pending {
+    Some(pending) => ignore_missing(pending.await),
+    None => Ok(()),
+  };
+
+  state.publish_state();
+  result
+}
+
+/// Re-apply the active pack after something that changes its effective volume, e.g. its gain
+pub async fn refresh_playback(state: State<'_, AppState>) -> Result<(), String> {
+  let pending = {
+    let config = state
+      .config
+      .lock()
+      .map_err(|_| "Falha ao acessar configuração".to_string())?;
+    queue_restart(&state, &config)
+  };
+
+  match pending {
+    Some(pending) => ignore_missing(pending.await),
+    None => Ok(()),
   }
+}
 
-  Ok(())
+/// Relaunch the active pack so wayvibes picks up changes to its files
+pub async fn reload_playback(state: State<'_, AppState>) -> Result<(), String> {
+  let pending = {
+    let config = state
+      .config
+      .lock()
+      .map_err(|_| "Falha ao acessar configuração".to_string())?;
+    playback_target(&state, &config).map(|(pack_path, volume)| {
+      wayvibes_service::start(&state.wayvibes, &config.wayvibes, &pack_path, volume)
+    })
+  };
+
+  let result = match pending {
+    Some(pending) => ignore_missing(pending.await),
+    None => Ok(()),
+  };
+
+  state.publish_state();
+  result
+}
+
+/// Bring wayvibes in line after the schedule in effect changed: start or stop it when that
+/// changed whether anything plays, switch pack and volume in place otherwise
+pub fn queue_schedule_change(
+  state: &AppState,
+  config: &AppConfig,
+  was_playing: bool,
+) -> Option<wayvibes_service::Pending> {
+  match playback_target(state, config) {
+    Some((pack_path, volume)) if was_playing => Some(wayvibes_service::restart(
+      &state.wayvibes,
+      &config.wayvibes,
+      &pack_path,
+      volume,
+    )),
+    Some((pack_path, volume)) => Some(wayvibes_service::start(
+      &state.wayvibes,
+      &config.wayvibes,
+      &pack_path,
+      volume,
+    )),
+    None if was_playing => Some(wayvibes_service::stop(&state.wayvibes, &config.wayvibes)),
+    None => None,
+  }
+}
+
+pub fn is_playing(state: &AppState, config: &AppConfig) -> bool {
+  playback_target(state, config).is_some()
+}
+
+/// Queue a switch to the current playback target, if anything should be playing
+fn queue_restart(state: &AppState, config: &AppConfig) -> Option<wayvibes_service::Pending> {
+  playback_target(state, config).map(|(pack_path, volume)| {
+    wayvibes_service::restart(&state.wayvibes, &config.wayvibes, &pack_path, volume)
+  })
+}
+
+/// Pack wayvibes should be playing and its effective volume, or `None` when paused or
+/// nothing is selected. A schedule in effect takes precedence over the manual state.
+fn playback_target(state: &AppState, config: &AppConfig) -> Option<(PathBuf, f32)> {
+  let playback = state.playback(config);
+  if playback.paused {
+    return None;
+  }
+  let pack_id = playback.pack_id.as_ref()?;
+  let pack_path = state.packs_dir.join(pack_id);
+  if !pack_path.exists() {
+    return None;
+  }
+  let settings = pack_settings_service::get(&state.packs_dir, pack_id);
+  let volume = playback
+    .volume
+    .unwrap_or_else(|| volume_service::pack_volume(config, &settings));
+  let profile = playback
+    .profile_id
+    .and_then(|profile_id| profile_service::find(&config.profiles, &profile_id));
+  Some((pack_path, volume_service::effective_volume(config, volume, &settings, profile)))
+}
+
+fn wayvibes_settings(state: &AppState) -> Result<WayvibesSettings, String> {
+  state
+    .config
+    .lock()
+    .map(|config| config.wayvibes.clone())
+    .map_err(|_| "Falha ao acessar configuração".to_string())
+}
+
+/// A missing wayvibes binary is reported by the status, not as a command failure
+fn ignore_missing(result: Result<(), AppError>) -> Result<(), String> {
+  match result {
+    Err(AppError::WayvibesMissing) | Ok(()) => Ok(()),
+    Err(err) => Err(err.to_string()),
+  }
 }
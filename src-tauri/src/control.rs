@@ -0,0 +1,147 @@
+#[path = "control/handlers.rs"]
+mod handlers;
+#[path = "control/protocol.rs"]
+mod protocol;
+
+use std::fs;
+use std::os::unix::net::UnixListener as StdUnixListener;
+use std::path::PathBuf;
+
+use serde::Serialize;
+use serde_json::Value;
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Manager};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::{broadcast, mpsc};
+
+use crate::error::AppError;
+use crate::paths;
+use crate::state::AppState;
+use protocol::{Notification, Request, Response, RpcError, INVALID_REQUEST, PARSE_ERROR};
+
+const SOCKET_FILE: &str = "control.sock";
+
+pub fn socket_path() -> Result<PathBuf, AppError> {
+  Ok(paths::runtime_dir()?.join(SOCKET_FILE))
+}
+
+/// Serve line-delimited JSON-RPC on the control socket in the runtime dir
+pub fn start(app: AppHandle) -> Result<(), AppError> {
+  let path = socket_path()?;
+  // Only one instance runs at a time, so an existing socket is stale
+  let _ = fs::remove_file(&path);
+  let listener = StdUnixListener::bind(&path)?;
+  listener.set_nonblocking(true)?;
+
+  tauri::async_runtime::spawn(async move {
+    let listener = match UnixListener::from_std(listener) {
+      Ok(listener) => listener,
+      Err(err) => {
+        log::error!("Failed to listen: {}", err);
+        return;
+      }
+    };
+
+    loop {
+      match listener.accept().await {
+        Ok((stream, _)) => {
+          tauri::async_runtime::spawn(serve(app.clone(), stream));
+        }
+        Err(err) => log::error!("Failed to accept connection: {}", err),
+      }
+    }
+  });
+
+  Ok(())
+}
+
+async fn serve(app: AppHandle, stream: UnixStream) {
+  let (reader, mut writer) = stream.into_split();
+  let (tx, mut rx) = mpsc::unbounded_channel::<String>();
+
+  // Responses and event notifications share the socket, so one task owns the writes
+  let writer_task = tauri::async_runtime::spawn(async move {
+    while let Some(line) = rx.recv().await {
+      if writer.write_all(line.as_bytes()).await.is_err() {
+        break;
+      }
+    }
+  });
+
+  let mut subscription: Option<JoinHandle<()>> = None;
+  let mut lines = BufReader::new(reader).lines();
+
+  while let Ok(Some(line)) = lines.next_line().await {
+    if line.trim().is_empty() {
+      continue;
+    }
+
+    let request = match serde_json::from_str::<Request>(&line) {
+      Ok(request) => request,
+      Err(err) => {
+        let error = RpcError::new(PARSE_ERROR, err.to_string());
+        send(&tx, &Response::failure(Value::Null, error));
+        continue;
+      }
+    };
+
+    let result = if request.jsonrpc != "2.0" {
+      Err(RpcError::new(INVALID_REQUEST, "Esperado jsonrpc \"2.0\""))
+    } else {
+      match request.method.as_str() {
+        "subscribe" => {
+          if subscription.is_none() {
+            let events = app.state::<AppState>().subscribe();
+            subscription = Some(tauri::async_runtime::spawn(forward_events(events, tx.clone())));
+          }
+          Ok(Value::Bool(true))
+        }
+        "unsubscribe" => {
+          if let Some(handle) = subscription.take() {
+            handle.abort();
+          }
+          Ok(Value::Bool(true))
+        }
+        method => handlers::dispatch(&app, method, &request.params).await,
+      }
+    };
+
+    if let Some(id) = request.id {
+      send(&tx, &Response::from_result(id, result));
+    }
+  }
+
+  if let Some(handle) = subscription {
+    handle.abort();
+  }
+  drop(tx);
+  let _ = writer_task.await;
+}
+
+async fn forward_events<T: Clone + Serialize>(
+  mut events: broadcast::Receiver<T>,
+  tx: mpsc::UnboundedSender<String>,
+) {
+  loop {
+    match events.recv().await {
+      Ok(event) => {
+        if !send(&tx, &Notification::new("event", event)) {
+          break;
+        }
+      }
+      Err(broadcast::error::RecvError::Lagged(_)) => continue,
+      Err(broadcast::error::RecvError::Closed) => break,
+    }
+  }
+}
+
+fn send<T: Serialize>(tx: &mpsc::UnboundedSender<String>, message: &T) -> bool {
+  match serde_json::to_string(message) {
+    Ok(mut line) => {
+      line.push('\n');
+      tx.send(line).is_ok()
+    }
+    Err(_) => false,
+  }
+}
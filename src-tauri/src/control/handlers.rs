@@ -0,0 +1,88 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use tauri::{AppHandle, Manager};
+
+use super::protocol::{RpcError, APP_ERROR, INVALID_PARAMS, METHOD_NOT_FOUND};
+use crate::commands::{config_commands, sound_packs_commands, wayvibes_commands};
+use crate::state::AppState;
+
+/// Run a control method through the same command functions the frontend invokes
+pub async fn dispatch(app: &AppHandle, method: &str, params: &Value) -> Result<Value, RpcError> {
+  match method {
+    "get_wayvibes_status" => {
+      to_value(wayvibes_commands::get_wayvibes_status(app.state::<AppState>()).await)
+    }
+    "get_config" => to_value(config_commands::get_config(app.state::<AppState>())),
+    "get_sound_packs" => to_value(sound_packs_commands::get_sound_packs(app.state::<AppState>())),
+    "set_volume" => {
+      let volume = param::<f32>(params, "volume")?;
+      to_value(wayvibes_commands::set_volume(app.state::<AppState>(), volume).await)
+    }
+    "toggle_pause" => to_value(wayvibes_commands::toggle_pause(app.state::<AppState>()).await),
+    "set_paused" => {
+      let paused = param::<bool>(params, "paused")?;
+      to_value(wayvibes_commands::set_paused(app.state::<AppState>(), paused).await)
+    }
+    "toggle_mute" => to_value(wayvibes_commands::toggle_mute(app.state::<AppState>()).await),
+    "set_muted" => {
+      let muted = param::<bool>(params, "muted")?;
+      to_value(wayvibes_commands::set_muted(app.state::<AppState>(), muted).await)
+    }
+    "stop_wayvibes" => to_value(wayvibes_commands::stop_wayvibes(app.state::<AppState>()).await),
+    "set_active_pack" => {
+      let pack_id = param::<String>(params, "packId")?;
+      to_value(wayvibes_commands::set_active_pack(app.state::<AppState>(), pack_id).await)
+    }
+    "import_sound_pack" => {
+      let path = param::<String>(params, "path")?;
+      let transcode = optional_param(params, "transcode")?;
+      to_value(
+        sound_packs_commands::import_sound_pack(app.state::<AppState>(), path, transcode).await,
+      )
+    }
+    "get_wayvibes_logs" => {
+      let tail = optional_param::<usize>(params, "tail")?;
+      let filter = optional_param::<String>(params, "filter")?;
+      let stream = optional_param(params, "stream")?;
+      to_value(wayvibes_commands::get_wayvibes_logs(
+        app.state::<AppState>(),
+        tail,
+        filter,
+        stream,
+      ))
+    }
+    "run_preflight" => to_value(wayvibes_commands::run_preflight(app.state::<AppState>()).await),
+    "locate_wayvibes" => to_value(wayvibes_commands::locate_wayvibes(app.state::<AppState>())),
+    _ => Err(RpcError::new(
+      METHOD_NOT_FOUND,
+      format!("Método desconhecido: {}", method),
+    )),
+  }
+}
+
+fn param<T: DeserializeOwned>(params: &Value, name: &str) -> Result<T, RpcError> {
+  let value = params
+    .get(name)
+    .cloned()
+    .ok_or_else(|| RpcError::new(INVALID_PARAMS, format!("Parâmetro ausente: {}", name)))?;
+  serde_json::from_value(value).map_err(|err| {
+    RpcError::new(INVALID_PARAMS, format!("Parâmetro inválido '{}': {}", name, err))
+  })
+}
+
+fn optional_param<T: DeserializeOwned>(params: &Value, name: &str) -> Result<Option<T>, RpcError> {
+  match params.get(name) {
+    None | Some(Value::Null) => Ok(None),
+    Some(_) => param(params, name).map(Some),
+  }
+}
+
+fn to_value<T: Serialize>(result: Result<T, String>) -> Result<Value, RpcError> {
+  let value = result.map_err(app_error)?;
+  serde_json::to_value(value).map_err(|err| app_error(err.to_string()))
+}
+
+fn app_error(message: String) -> RpcError {
+  RpcError::new(APP_ERROR, message)
+}
@@ -0,0 +1,88 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+pub const APP_ERROR: i64 = -32000;
+
+/// A JSON-RPC 2.0 request; requests without an `id` are notifications
+#[derive(Debug, Deserialize)]
+pub struct Request {
+  pub jsonrpc: String,
+  pub id: Option<Value>,
+  pub method: String,
+  #[serde(default)]
+  pub params: Value,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Response {
+  pub jsonrpc: &'static str,
+  pub id: Value,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub result: Option<Value>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub error: Option<RpcError>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RpcError {
+  pub code: i64,
+  pub message: String,
+}
+
+/// Server-initiated message pushed to subscribers
+#[derive(Debug, Serialize)]
+pub struct Notification<T: Serialize> {
+  pub jsonrpc: &'static str,
+  pub method: &'static str,
+  pub params: T,
+}
+
+impl Response {
+  pub fn success(id: Value, result: Value) -> Self {
+    Self {
+      jsonrpc: "2.0",
+      id,
+      result: Some(result),
+      error: None,
+    }
+  }
+
+  pub fn failure(id: Value, error: RpcError) -> Self {
+    Self {
+      jsonrpc: "2.0",
+      id,
+      result: None,
+      error: Some(error),
+    }
+  }
+
+  pub fn from_result(id: Value, result: Result<Value, RpcError>) -> Self {
+    match result {
+      Ok(value) => Self::success(id, value),
+      Err(error) => Self::failure(id, error),
+    }
+  }
+}
+
+impl RpcError {
+  pub fn new(code: i64, message: impl Into<String>) -> Self {
+    Self {
+      code,
+      message: message.into(),
+    }
+  }
+}
+
+impl<T: Serialize> Notification<T> {
+  pub fn new(method: &'static str, params: T) -> Self {
+    Self {
+      jsonrpc: "2.0",
+      method,
+      params,
+    }
+  }
+}
@@ -0,0 +1,196 @@
+use tauri::{AppHandle, Manager};
+use tokio::sync::broadcast;
+use zbus::fdo;
+use zbus::object_server::{InterfaceRef, SignalEmitter};
+
+use crate::commands::{sound_packs_commands, wayvibes_commands};
+use crate::models::{AppConfig, AppEvent, WayvibesStatus};
+use crate::services::wayvibes_service;
+use crate::state::AppState;
+
+pub const BUS_NAME: &str = "com.timbo.WayvibesUi";
+pub const OBJECT_PATH: &str = "/com/timbo/WayvibesUi";
+
+/// Session bus object exposing playback control and state
+pub struct WayvibesUiBus {
+  app: AppHandle,
+}
+
+#[zbus::interface(name = "com.timbo.WayvibesUi")]
+impl WayvibesUiBus {
+  async fn pause(&self) -> fdo::Result<()> {
+    wayvibes_commands::set_paused(self.app.state::<AppState>(), true)
+      .await
+      .map_err(fdo::Error::Failed)
+  }
+
+  async fn resume(&self) -> fdo::Result<()> {
+    wayvibes_commands::set_paused(self.app.state::<AppState>(), false)
+      .await
+      .map_err(fdo::Error::Failed)
+  }
+
+  async fn toggle(&self) -> fdo::Result<()> {
+    wayvibes_commands::toggle_pause(self.app.state::<AppState>())
+      .await
+      .map_err(fdo::Error::Failed)
+  }
+
+  #[zbus(name = "SetVolume")]
+  async fn set_volume_method(&self, volume: f64) -> fdo::Result<()> {
+    wayvibes_commands::set_volume(self.app.state::<AppState>(), volume as f32)
+      .await
+      .map_err(fdo::Error::Failed)
+  }
+
+  async fn toggle_mute(&self) -> fdo::Result<()> {
+    wayvibes_commands::toggle_mute(self.app.state::<AppState>())
+      .await
+      .map_err(fdo::Error::Failed)
+  }
+
+  async fn set_pack(&self, pack_id: String) -> fdo::Result<()> {
+    wayvibes_commands::set_active_pack(self.app.state::<AppState>(), pack_id)
+      .await
+      .map_err(fdo::Error::Failed)
+  }
+
+  #[zbus(property)]
+  async fn volume(&self) -> fdo::Result<f64> {
+    Ok(self.config()?.volume as f64)
+  }
+
+  #[zbus(property)]
+  async fn paused(&self) -> fdo::Result<bool> {
+    Ok(self.config()?.paused)
+  }
+
+  #[zbus(property)]
+  async fn muted(&self) -> fdo::Result<bool> {
+    Ok(self.config()?.muted)
+  }
+
+  /// Id of the active pack, or an empty string when none is selected
+  #[zbus(property)]
+  async fn active_pack(&self) -> fdo::Result<String> {
+    Ok(self.config()?.active_pack_id.unwrap_or_default())
+  }
+
+  #[zbus(property)]
+  async fn running(&self) -> fdo::Result<bool> {
+    let lifecycle = self.app.state::<AppState>().wayvibes.lifecycle();
+    let status = wayvibes_service::get_status(&self.config()?.wayvibes, lifecycle)
+      .await
+      .map_err(|err| fdo::Error::Failed(err.to_string()))?;
+    Ok(status.running)
+  }
+
+  /// Installed packs as (id, name) pairs
+  #[zbus(property)]
+  async fn packs(&self) -> fdo::Result<Vec<(String, String)>> {
+    let packs = sound_packs_commands::get_sound_packs(self.app.state::<AppState>())
+      .map_err(fdo::Error::Failed)?;
+    Ok(packs.into_iter().map(|pack| (pack.id, pack.name)).collect())
+  }
+}
+
+impl WayvibesUiBus {
+  fn config(&self) -> fdo::Result<AppConfig> {
+    self
+      .app
+      .state::<AppState>()
+      .config
+      .lock()
+      .map(|config| config.clone())
+      .map_err(|_| fdo::Error::Failed("Falha ao acessar configuração".into()))
+  }
+}
+
+/// Own the well-known name on the session bus and mirror state changes as PropertiesChanged
+pub fn start(app: AppHandle) {
+  let events = app.state::<AppState>().subscribe();
+
+  tauri::async_runtime::spawn(async move {
+    let service = WayvibesUiBus { app };
+    let connection = match zbus::connection::Builder::session()
+      .and_then(|builder| builder.name(BUS_NAME))
+      .and_then(|builder| builder.serve_at(OBJECT_PATH, service))
+    {
+      Ok(builder) => builder.build().await,
+      Err(err) => Err(err),
+    };
+    let connection = match connection {
+      Ok(connection) => connection,
+      Err(err) => {
+        log::warn!("Session bus unavailable: {}", err);
+        return;
+      }
+    };
+
+    let iface = match connection
+      .object_server()
+      .interface::<_, WayvibesUiBus>(OBJECT_PATH)
+      .await
+    {
+      Ok(iface) => iface,
+      Err(err) => {
+        log::error!("Failed to access interface: {}", err);
+        return;
+      }
+    };
+
+    emit_changes(iface, events).await;
+  });
+}
+
+async fn emit_changes(iface: InterfaceRef<WayvibesUiBus>, mut events: broadcast::Receiver<AppEvent>) {
+  let mut last: Option<(AppConfig, WayvibesStatus)> = None;
+
+  loop {
+    let event = match events.recv().await {
+      Ok(event) => event,
+      Err(broadcast::error::RecvError::Lagged(_)) => continue,
+      Err(broadcast::error::RecvError::Closed) => break,
+    };
+
+    let bus = iface.get().await;
+    let emitter = iface.signal_emitter();
+    let result = match event {
+      AppEvent::StateChanged { config, status } => {
+        let result = emit_state_changes(&bus, emitter, last.as_ref(), &config, &status).await;
+        last = Some((config, status));
+        result
+      }
+      AppEvent::PacksChanged { .. } => bus.packs_changed(emitter).await,
+    };
+
+    if let Err(err) = result {
+      log::error!("Failed to emit PropertiesChanged: {}", err);
+    }
+  }
+}
+
+async fn emit_state_changes(
+  bus: &WayvibesUiBus,
+  emitter: &SignalEmitter<'_>,
+  last: Option<&(AppConfig, WayvibesStatus)>,
+  config: &AppConfig,
+  status: &WayvibesStatus,
+) -> zbus::Result<()> {
+  if last.is_none_or(|(prev, _)| prev.volume != config.volume) {
+    bus.volume_changed(emitter).await?;
+  }
+  if last.is_none_or(|(prev, _)| prev.paused != config.paused) {
+    bus.paused_changed(emitter).await?;
+  }
+  if last.is_none_or(|(prev, _)| prev.muted != config.muted) {
+    bus.muted_changed(emitter).await?;
+  }
+  if last.is_none_or(|(prev, _)| prev.active_pack_id != config.active_pack_id) {
+    bus.active_pack_changed(emitter).await?;
+  }
+  if last.is_none_or(|(_, prev)| prev.running != status.running) {
+    bus.running_changed(emitter).await?;
+  }
+  Ok(())
+}
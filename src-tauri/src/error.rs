@@ -12,8 +12,12 @@ pub enum AppError {
   InvalidConfig(String),
   #[error("Pacote inválido: {0}")]
   InvalidPack(String),
+  #[error("Falha ao processar áudio: {0}")]
+  Audio(String),
   #[error("Wayvibes não encontrado")]
   WayvibesMissing,
   #[error("Comando do Wayvibes falhou: {0}")]
   WayvibesCommand(String),
+  #[error("A versão instalada do Wayvibes não suporta {0}. Atualize o wayvibes.")]
+  WayvibesUnsupported(String),
 }
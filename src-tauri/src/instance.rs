@@ -0,0 +1,182 @@
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::commands::sound_packs_commands;
+use crate::error::AppError;
+use crate::paths;
+use crate::services::sound_pack_service;
+use crate::state::AppState;
+use crate::tray;
+
+const LOCK_FILE: &str = "instance.lock";
+const SOCKET_FILE: &str = "instance.sock";
+/// How long a second launch waits for a primary that holds the lock but hasn't bound its
+/// socket yet
+const FORWARD_ATTEMPTS: u32 = 10;
+const FORWARD_RETRY: Duration = Duration::from_millis(100);
+/// How long the primary waits for a connected launch to send its request, so a client that
+/// never writes can't hold up the ones after it
+const READ_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Result of trying to become the only running instance
+pub enum Instance {
+  Primary(InstanceGuard),
+  Secondary,
+}
+
+/// Holds the instance lock for the lifetime of the app
+pub struct InstanceGuard {
+  lock: File,
+  listener: UnixListener,
+}
+
+/// Arguments of a launch, forwarded from a second instance to the primary one
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LaunchRequest {
+  pub args: Vec<String>,
+  pub cwd: Option<PathBuf>,
+}
+
+impl LaunchRequest {
+  pub fn current() -> Self {
+    Self {
+      args: std::env::args().skip(1).collect(),
+      cwd: std::env::current_dir().ok(),
+    }
+  }
+}
+
+/// Take the instance lock, or report that another instance already holds it
+pub fn acquire() -> Result<Instance, AppError> {
+  let dir = paths::runtime_dir()?;
+  let lock = OpenOptions::new()
+    .create(true)
+    .truncate(false)
+    .write(true)
+    .open(dir.join(LOCK_FILE))?;
+
+  match lock.try_lock() {
+    Ok(()) => {}
+    Err(TryLockError::WouldBlock) => return Ok(Instance::Secondary),
+    Err(TryLockError::Error(err)) => return Err(AppError::Io(err)),
+  }
+
+  // The lock is ours, so any socket left behind belongs to a dead instance
+  let socket_path = dir.join(SOCKET_FILE);
+  let _ = fs::remove_file(&socket_path);
+  let listener = UnixListener::bind(&socket_path)?;
+
+  Ok(Instance::Primary(InstanceGuard {
+    lock,
+    listener,
+  }))
+}
+
+/// Send this launch's arguments to the primary instance
+pub fn forward(request: &LaunchRequest) -> Result<(), AppError> {
+  let socket_path = paths::runtime_dir()?.join(SOCKET_FILE);
+  let mut stream = connect(&socket_path)?;
+  let mut payload = serde_json::to_vec(request)?;
+  payload.push(b'\n');
+  stream.write_all(&payload)?;
+  Ok(())
+}
+
+/// Connect to the primary, retrying while its socket is missing or not listening yet
+fn connect(socket_path: &Path) -> Result<UnixStream, AppError> {
+  let mut attempt = 1;
+  loop {
+    match UnixStream::connect(socket_path) {
+      Ok(stream) => return Ok(stream),
+      Err(err)
+        if attempt < FORWARD_ATTEMPTS
+          && matches!(err.kind(), ErrorKind::NotFound | ErrorKind::ConnectionRefused) =>
+      {
+        log::debug!(target: "instance", "Primary not listening yet ({}), retrying", err);
+        attempt += 1;
+        thread::sleep(FORWARD_RETRY);
+      }
+      Err(err) => return Err(err.into()),
+    }
+  }
+}
+
+/// Accept launches forwarded by later instances until the app exits
+pub fn listen(guard: InstanceGuard, app: AppHandle) {
+  thread::spawn(move || {
+    let _lock = guard.lock;
+    for stream in guard.listener.incoming() {
+      let stream = match stream {
+        Ok(stream) => stream,
+        Err(err) => {
+          log::error!("Failed to accept connection: {}", err);
+          continue;
+        }
+      };
+
+      if let Err(err) = stream.set_read_timeout(Some(READ_TIMEOUT)) {
+        log::warn!("Failed to set launch request timeout: {}", err);
+        continue;
+      }
+      let mut line = String::new();
+      if let Err(err) = BufReader::new(stream).read_line(&mut line) {
+        log::warn!("Failed to read launch request: {}", err);
+        continue;
+      }
+
+      match serde_json::from_str::<LaunchRequest>(&line) {
+        Ok(request) => {
+          tray::show_main_window(&app);
+          handle_launch(&app, request);
+        }
+        Err(err) => log::warn!("Invalid launch request: {}", err),
+      }
+    }
+  });
+}
+
+/// Act on launch arguments: pack archives are imported into the library
+pub fn handle_launch(app: &AppHandle, request: LaunchRequest) {
+  let archives: Vec<PathBuf> = request
+    .args
+    .iter()
+    .map(|arg| resolve_arg(arg, request.cwd.as_deref()))
+    .filter(|path| sound_pack_service::is_supported_archive(path) && path.is_file())
+    .collect();
+
+  if archives.is_empty() {
+    return;
+  }
+
+  tray::show_main_window(app);
+
+  for archive in archives {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+      let path = archive.to_string_lossy().to_string();
+      match sound_packs_commands::import_sound_pack(app.state::<AppState>(), path, None).await {
+        Ok(pack) => {
+          let _ = app.emit("sound-pack-imported", pack);
+        }
+        Err(err) => {
+          let _ = app.emit("app-error", err);
+        }
+      }
+    });
+  }
+}
+
+fn resolve_arg(arg: &str, cwd: Option<&Path>) -> PathBuf {
+  let path = PathBuf::from(arg.strip_prefix("file://").unwrap_or(arg));
+  match cwd {
+    Some(cwd) if path.is_relative() => cwd.join(path),
+    _ => path,
+  }
+}
@@ -1,14 +1,43 @@
+mod cli;
 mod commands;
+mod control;
+mod dbus;
 mod error;
+mod instance;
+mod logging;
 mod models;
+mod paths;
+mod rotating_file;
+mod scheduler;
 mod services;
 mod state;
 mod tray;
 
-use tauri::Manager;
+use tauri::{Emitter, Manager};
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+  let args: Vec<String> = std::env::args().skip(1).collect();
+  if let Some(code) = cli::run(&args) {
+    std::process::exit(code);
+  }
+  logging::init(paths::logs_dir().ok().as_deref());
+
+  let guard = match instance::acquire() {
+    Ok(instance::Instance::Primary(guard)) => Some(guard),
+    Ok(instance::Instance::Secondary) => {
+      // Another instance owns the tray and the config; hand it our arguments
+      if let Err(err) = instance::forward(&instance::LaunchRequest::current()) {
+        log::error!(target: "instance", "Failed to reach running instance: {}", err);
+      }
+      return;
+    }
+    Err(err) => {
+      log::warn!(target: "instance", "Single-instance lock unavailable: {}", err);
+      None
+    }
+  };
+
   let builder = tauri::Builder::default()
     .plugin(tauri_plugin_opener::init())
     .plugin(tauri_plugin_dialog::init());
@@ -25,13 +54,72 @@ pub fn run() {
   builder
     .setup(|app| {
       let state = state::AppState::new()?;
+      if let Ok(config) = state.config.lock() {
+        logging::set_level(config.log_level);
+      }
+      let mut events = state.subscribe();
       app.manage(state);
 
+      let handle = app.handle().clone();
+      tauri::async_runtime::spawn(async move {
+        loop {
+          match events.recv().await {
+            Ok(event) => {
+              let _ = handle.emit("app-event", event);
+            }
+            Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+            Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+          }
+        }
+      });
+
+      let mut logs = app.state::<state::AppState>().wayvibes.logs.subscribe();
+      let handle = app.handle().clone();
+      tauri::async_runtime::spawn(async move {
+        loop {
+          match logs.recv().await {
+            Ok(line) => {
+              let _ = handle.emit("wayvibes-log", line);
+            }
+            Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+            Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+          }
+        }
+      });
+
+      // Transitions the supervisor makes on its own, e.g. a crash, reach the UI too
+      let mut lifecycle = app.state::<state::AppState>().wayvibes.watch_lifecycle();
+      let handle = app.handle().clone();
+      tauri::async_runtime::spawn(async move {
+        while lifecycle.changed().await.is_ok() {
+          handle.state::<state::AppState>().publish_state();
+        }
+      });
+
+      // wayvibes runs as our child now, so bring back what was playing before the last exit
+      let handle = app.handle().clone();
+      tauri::async_runtime::spawn(async move {
+        if let Err(err) = commands::wayvibes_commands::restore_playback(handle.state()).await {
+          log::error!(target: "wayvibes_service", "Failed to restore playback: {}", err);
+        }
+      });
+
+      if let Err(err) = control::start(app.handle().clone()) {
+        log::warn!(target: "control", "Control socket unavailable: {}", err);
+      }
+      dbus::start(app.handle().clone());
+
       if let Some(window) = app.get_webview_window("main") {
         let _ = window.hide();
       }
 
       tray::setup_tray(app)?;
+      scheduler::start(app.handle().clone());
+
+      if let Some(guard) = guard {
+        instance::listen(guard, app.handle().clone());
+      }
+      instance::handle_launch(app.handle(), instance::LaunchRequest::current());
       Ok(())
     })
     .on_window_event(|window, event| {
@@ -42,16 +130,56 @@ pub fn run() {
     })
     .invoke_handler(tauri::generate_handler![
       commands::wayvibes_commands::get_wayvibes_status,
+      commands::wayvibes_commands::get_wayvibes_logs,
+      commands::wayvibes_commands::run_preflight,
       commands::sound_packs_commands::get_sound_packs,
       commands::sound_packs_commands::import_sound_pack,
+      commands::sound_packs_commands::create_sound_pack,
+      commands::sound_packs_commands::compose_sound_pack,
+      commands::sound_packs_commands::convert_sound_pack,
+      commands::sound_packs_commands::normalize_sound_pack,
+      commands::sound_packs_commands::process_sound_pack,
       commands::sound_packs_commands::delete_sound_pack,
       commands::sound_packs_commands::get_pack_path,
+      commands::sound_packs_commands::set_pack_settings,
+      commands::sound_packs_commands::analyze_pack_loudness,
+      commands::sound_packs_commands::get_pack_mapping,
+      commands::sound_packs_commands::pack_coverage,
+      commands::sound_packs_commands::render_pack_preview,
+      commands::sound_packs_commands::set_key_sounds,
+      commands::sound_packs_commands::add_pack_sounds,
       commands::wayvibes_commands::set_active_pack,
       commands::wayvibes_commands::set_volume,
       commands::wayvibes_commands::toggle_pause,
+      commands::wayvibes_commands::set_paused,
       commands::wayvibes_commands::stop_wayvibes,
+      commands::wayvibes_commands::set_profiles,
+      commands::wayvibes_commands::set_active_profile,
+      commands::wayvibes_commands::set_muted,
+      commands::wayvibes_commands::toggle_mute,
+      commands::wayvibes_commands::set_volume_curve,
+      commands::wayvibes_commands::locate_wayvibes,
+      commands::wayvibes_commands::set_wayvibes_settings,
       commands::config_commands::get_config,
+      commands::config_commands::set_log_level,
+      commands::config_commands::open_log_folder,
+      commands::diagnostics_commands::export_diagnostics,
+      commands::schedule_commands::set_schedules,
+      commands::schedule_commands::get_active_schedule,
     ])
-    .run(tauri::generate_context!())
-    .expect("error while running tauri application");
+    .build(tauri::generate_context!())
+    .expect("error while running tauri application")
+    .run(|app, event| {
+      if let tauri::RunEvent::Exit = event {
+        let state = app.state::<state::AppState>();
+        let settings = match state.config.lock() {
+          Ok(config) => config.wayvibes.clone(),
+          Err(_) => return,
+        };
+        let _ = tauri::async_runtime::block_on(services::wayvibes_service::stop(
+          &state.wayvibes,
+          &settings,
+        ));
+      }
+    });
 }
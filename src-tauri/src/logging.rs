@@ -0,0 +1,196 @@
+use std::collections::VecDeque;
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock, RwLock};
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+use crate::models::LogLevel;
+use crate::rotating_file::RotatingFile;
+
+/// Overrides the configured level, e.g. `debug` or `info,wayvibes_service=trace,zbus=warn`
+pub const LOG_ENV: &str = "WAYVIBES_UI_LOG";
+
+const LOG_FILE_MAX_BYTES: u64 = 2 * 1024 * 1024;
+const LOG_FILE_KEEP: usize = 5;
+/// Warnings and errors kept in memory for the diagnostics bundle
+const RECENT_ERRORS: usize = 50;
+
+static LOGGER: OnceLock<AppLogger> = OnceLock::new();
+
+struct AppLogger {
+  filter: RwLock<Filter>,
+  /// Set when `WAYVIBES_UI_LOG` is present, so the config can't lower it
+  from_env: bool,
+  file: Option<Mutex<RotatingFile>>,
+  errors: Mutex<VecDeque<String>>,
+}
+
+/// A default level plus per-target overrides
+#[derive(Debug, Clone)]
+struct Filter {
+  default: LevelFilter,
+  targets: Vec<(String, LevelFilter)>,
+}
+
+impl Filter {
+  fn new(default: LevelFilter) -> Self {
+    Self {
+      default,
+      targets: Vec::new(),
+    }
+  }
+
+  /// Parse `level` and `target=level` directives separated by commas
+  fn parse(spec: &str) -> Self {
+    let mut filter = Self::new(LevelFilter::Info);
+    for directive in spec.split(',').map(str::trim).filter(|value| !value.is_empty()) {
+      match directive.split_once('=') {
+        Some((target, level)) => {
+          if let Ok(level) = level.trim().parse() {
+            filter.targets.push((target.trim().to_string(), level));
+          }
+        }
+        None => {
+          if let Ok(level) = directive.parse() {
+            filter.default = level;
+          }
+        }
+      }
+    }
+    filter
+  }
+
+  fn level_for(&self, target: &str) -> LevelFilter {
+    self
+      .targets
+      .iter()
+      .rev()
+      .find(|(name, _)| name == target)
+      .map(|(_, level)| *level)
+      .unwrap_or(self.default)
+  }
+
+  fn max_level(&self) -> LevelFilter {
+    self
+      .targets
+      .iter()
+      .map(|(_, level)| *level)
+      .fold(self.default, Ord::max)
+  }
+}
+
+impl Log for AppLogger {
+  fn enabled(&self, metadata: &Metadata) -> bool {
+    let target = short_target(metadata.target());
+    match self.filter.read() {
+      Ok(filter) => metadata.level() <= filter.level_for(target),
+      Err(_) => metadata.level() <= Level::Warn,
+    }
+  }
+
+  fn log(&self, record: &Record) {
+    if !self.enabled(record.metadata()) {
+      return;
+    }
+
+    let line = format!(
+      "{} {:<5} [{}] {}",
+      chrono::Local::now().format("%Y-%m-%dT%H:%M:%S%.3f%:z"),
+      record.level(),
+      short_target(record.target()),
+      record.args()
+    );
+
+    let _ = writeln!(std::io::stderr(), "{}", line);
+    if record.level() <= Level::Warn {
+      if let Ok(mut errors) = self.errors.lock() {
+        if errors.len() == RECENT_ERRORS {
+          errors.pop_front();
+        }
+        errors.push_back(line.clone());
+      }
+    }
+    if let Some(file) = &self.file {
+      if let Ok(mut file) = file.lock() {
+        let _ = file.write_line(&line);
+      }
+    }
+  }
+
+  fn flush(&self) {
+    let _ = std::io::stderr().flush();
+  }
+}
+
+/// Install the logger; messages go to stderr and, when `logs_dir` is known, to `app.log` there
+pub fn init(logs_dir: Option<&Path>) {
+  let env_spec = std::env::var(LOG_ENV).ok().filter(|value| !value.trim().is_empty());
+  let filter = match &env_spec {
+    Some(spec) => Filter::parse(spec),
+    None => Filter::new(LevelFilter::Info),
+  };
+  let max_level = filter.max_level();
+
+  let logger = LOGGER.get_or_init(|| AppLogger {
+    filter: RwLock::new(filter),
+    from_env: env_spec.is_some(),
+    file: logs_dir.map(|dir| {
+      Mutex::new(RotatingFile::new(
+        dir.join("app.log"),
+        LOG_FILE_MAX_BYTES,
+        LOG_FILE_KEEP,
+      ))
+    }),
+    errors: Mutex::new(VecDeque::with_capacity(RECENT_ERRORS)),
+  });
+
+  if log::set_logger(logger).is_ok() {
+    log::set_max_level(max_level);
+  }
+}
+
+/// Apply the level from `AppConfig` unless the environment variable already chose one
+pub fn set_level(level: LogLevel) {
+  let Some(logger) = LOGGER.get() else {
+    return;
+  };
+  if logger.from_env {
+    return;
+  }
+  if let Ok(mut filter) = logger.filter.write() {
+    *filter = Filter::new(level_filter(level));
+    log::set_max_level(filter.max_level());
+  }
+}
+
+/// Most recent warnings and errors, oldest first
+pub fn recent_errors() -> Vec<String> {
+  LOGGER
+    .get()
+    .and_then(|logger| logger.errors.lock().ok().map(|errors| errors.iter().cloned().collect()))
+    .unwrap_or_default()
+}
+
+fn level_filter(level: LogLevel) -> LevelFilter {
+  match level {
+    LogLevel::Error => LevelFilter::Error,
+    LogLevel::Warn => LevelFilter::Warn,
+    LogLevel::Info => LevelFilter::Info,
+    LogLevel::Debug => LevelFilter::Debug,
+    LogLevel::Trace => LevelFilter::Trace,
+  }
+}
+
+/// `wayvibes_ui_lib::services::wayvibes_service` -> `wayvibes_service`,
+/// `wayvibes_ui_lib::tray::events` -> `tray`, `zbus::connection` -> `zbus`
+fn short_target(target: &str) -> &str {
+  let Some(rest) = target.strip_prefix(concat!(env!("CARGO_CRATE_NAME"), "::")) else {
+    return target.split("::").next().unwrap_or(target);
+  };
+  let rest = rest
+    .strip_prefix("services::")
+    .or_else(|| rest.strip_prefix("commands::"))
+    .unwrap_or(rest);
+  rest.split("::").next().unwrap_or(rest)
+}
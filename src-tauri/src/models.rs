@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -6,6 +8,21 @@ pub struct AppConfig {
   pub active_pack_id: Option<String>,
   pub volume: f32,
   pub paused: bool,
+  #[serde(default)]
+  pub schedules: Vec<ScheduleRule>,
+  #[serde(default)]
+  pub wayvibes: WayvibesSettings,
+  #[serde(default)]
+  pub log_level: LogLevel,
+  /// Silences playback without pausing; `volume` is kept as is
+  #[serde(default)]
+  pub muted: bool,
+  #[serde(default)]
+  pub volume_curve: VolumeCurve,
+  #[serde(default)]
+  pub profiles: Vec<Profile>,
+  #[serde(default)]
+  pub active_profile_id: Option<String>,
 }
 
 impl Default for AppConfig {
@@ -14,17 +31,444 @@ impl Default for AppConfig {
       active_pack_id: None,
       volume: 0.7,
       paused: false,
+      schedules: Vec::new(),
+      wayvibes: WayvibesSettings::default(),
+      log_level: LogLevel::default(),
+      muted: false,
+      volume_curve: VolumeCurve::default(),
+      profiles: Vec::new(),
+      active_profile_id: None,
     }
   }
 }
 
+/// How the 0-1 slider value maps to the level handed to wayvibes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum VolumeCurve {
+  #[default]
+  Linear,
+  /// `log10(1 + 9x)`: more resolution at the quiet end of the slider
+  Logarithmic,
+  /// `x^exponent`; below 1 lifts the quiet end, above 1 lowers it
+  #[serde(rename_all = "camelCase")]
+  Exponent { exponent: f32 },
+}
+
+/// Minimum level written to the app log; `WAYVIBES_UI_LOG` takes precedence
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+  Error,
+  Warn,
+  #[default]
+  Info,
+  Debug,
+  Trace,
+}
+
+/// How to launch wayvibes; the binary is auto-detected when `binary_path` is unset
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WayvibesSettings {
+  pub binary_path: Option<String>,
+  #[serde(default)]
+  pub extra_args: Vec<String>,
+  #[serde(default)]
+  pub env: BTreeMap<String, String>,
+}
+
+/// Named playback preset the user or a schedule can switch to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Profile {
+  pub id: String,
+  pub name: String,
+  /// Pack played while the profile is active; `None` keeps the active pack
+  #[serde(default)]
+  pub pack_id: Option<String>,
+  /// Level in dB applied on top of the volume and the gain of whichever pack plays
+  #[serde(default)]
+  pub gain_db: f32,
+}
+
+/// Time window during which an action overrides the manual state.
+/// `start`/`end` are local "HH:MM" times; when `end` is not after `start`
+/// the window crosses midnight and `days` refers to the day it starts on.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduleRule {
+  pub id: String,
+  pub name: String,
+  #[serde(default = "default_enabled")]
+  pub enabled: bool,
+  pub days: Vec<Weekday>,
+  pub start: String,
+  pub end: String,
+  pub action: ScheduleAction,
+}
+
+fn default_enabled() -> bool {
+  true
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Weekday {
+  Mon,
+  Tue,
+  Wed,
+  Thu,
+  Fri,
+  Sat,
+  Sun,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ScheduleAction {
+  Pause,
+  #[serde(rename_all = "camelCase")]
+  SetVolume { volume: f32 },
+  #[serde(rename_all = "camelCase")]
+  SetPack { pack_id: String },
+  #[serde(rename_all = "camelCase")]
+  SwitchProfile { profile_id: String },
+}
+
+/// Schedule rule currently overriding the manual state
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActiveSchedule {
+  pub rule_id: String,
+  pub rule_name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct SoundPack {
   pub id: String,
   pub name: String,
   pub version: String,
   pub author: Option<String>,
   pub description: Option<String>,
+  /// Mechvibes `config_version`, e.g. `"2"`; `None` for original Mechvibes packs
+  #[serde(default)]
+  pub config_version: Option<String>,
+  /// The pack has sounds for key release
+  #[serde(default)]
+  pub supports_keyup: bool,
+  /// Keys that pick one of several sounds on each press
+  #[serde(default)]
+  pub variant_keys: usize,
+  /// Most sounds any single key picks from
+  #[serde(default)]
+  pub max_variants: usize,
+  /// Features of the pack the installed wayvibes can't play
+  #[serde(default)]
+  pub warnings: Vec<String>,
+  #[serde(default)]
+  pub settings: PackSettings,
+  /// Set on a freshly imported pack whose audio was converted during the import, and on a
+  /// freshly converted pack made from compressed sounds
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub transcode: Option<TranscodeReport>,
+}
+
+/// Per-pack adjustments applied on top of the global volume
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackSettings {
+  /// Loudness correction in dB, applied whenever the pack plays
+  #[serde(default)]
+  pub gain_db: f32,
+  /// Volume the pack plays at in place of the global volume; the slider adjusts it while
+  /// the pack plays
+  #[serde(default)]
+  pub default_volume: Option<f32>,
+}
+
+/// Whether a pack plays slices of one sprite or one file per key
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyDefineType {
+  /// Every key is a slice of `sound`
+  #[default]
+  Single,
+  /// Every key has its own file
+  Multi,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum KeySound {
+  File { file: String },
+  #[serde(rename_all = "camelCase")]
+  Slice { offset_ms: f64, duration_ms: f64 },
+  /// One of these, picked at random on every press
+  Variants { sounds: Vec<KeySound> },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyMapping {
+  pub code: u32,
+  pub label: String,
+  /// Linux key name, e.g. `ENTER`; `None` for codes the app doesn't know
+  pub evdev_name: Option<String>,
+  /// Whether the sound is the key's own define or the pack's fallback
+  pub coverage: Coverage,
+  /// What the key plays; `None` when it is silent
+  pub sound: Option<KeySound>,
+}
+
+/// Physical keyboard a coverage report is measured against
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum KeyboardLayout {
+  /// Full-size ANSI, 104 keys
+  Ansi104,
+  /// Full-size ISO, 105 keys
+  Iso105,
+  /// ANSI without the numpad, 87 keys
+  Tkl,
+  /// ANSI 60%, 61 keys
+  Sixty,
+  /// Linux input event codes, e.g. from `evtest`
+  #[serde(rename_all = "camelCase")]
+  Custom { evdev_codes: Vec<u16> },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Coverage {
+  /// The pack defines a sound for the key
+  Mapped,
+  /// No define; the key plays the `sound` of a multi pack
+  Fallback,
+  /// Defined as `null`, or no define and nothing to fall back to
+  Silent,
+  /// The key has no Mechvibes keycode, so no pack can play a sound for it
+  Unmapped,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyCoverage {
+  pub evdev: u16,
+  /// `None` for custom codes Linux doesn't name or the app doesn't know
+  pub evdev_name: Option<String>,
+  pub label: String,
+  /// Mechvibes keycode; `None` when the key has none the app knows of
+  pub code: Option<u32>,
+  pub coverage: Coverage,
+  pub sound: Option<KeySound>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackCoverage {
+  pub pack_id: String,
+  pub layout: KeyboardLayout,
+  pub mapped: usize,
+  pub fallback: usize,
+  pub silent: usize,
+  pub unmapped: usize,
+  pub keys: Vec<KeyCoverage>,
+}
+
+/// Every key of a pack with the sound it plays
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackMapping {
+  pub pack_id: String,
+  pub key_define_type: KeyDefineType,
+  /// Sprite of a single pack
+  pub sound: Option<String>,
+  /// Audio files in the pack directory, whether mapped or not
+  pub files: Vec<String>,
+  pub keys: Vec<KeyMapping>,
+}
+
+/// Assign, replace or (with `sound: None`) silence the sound of one key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyChange {
+  pub code: u32,
+  pub sound: Option<KeySound>,
+}
+
+/// How `create_sound_pack` assigns the given files to keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MappingStrategy {
+  /// The only file plays for every key
+  Single,
+  /// Files are dealt out to the keys in keyboard order
+  RoundRobin,
+  /// `enter.wav`, `space.wav`, `a.wav`... go to their key; the rest are dealt out to the
+  /// keys left over
+  ByName,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewPack {
+  pub name: String,
+  #[serde(default)]
+  pub author: Option<String>,
+  #[serde(default)]
+  pub description: Option<String>,
+  /// Absolute paths of the audio files to copy into the pack
+  pub files: Vec<String>,
+  pub strategy: MappingStrategy,
+}
+
+/// A new pack made of a base pack with some keys taken from other installed packs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComposedPack {
+  pub name: String,
+  pub base_pack_id: String,
+  /// Applied in order, so a later override wins over an earlier one
+  pub overrides: Vec<KeyOverride>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyOverride {
+  pub pack_id: String,
+  pub keys: KeyRange,
+}
+
+/// Mechvibes keycodes an override applies to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum KeyRange {
+  Codes { codes: Vec<u32> },
+  /// Every code from `first` to `last`, both included
+  Range { first: u32, last: u32 },
+}
+
+impl KeyRange {
+  pub fn contains(&self, code: u32) -> bool {
+    match self {
+      KeyRange::Codes { codes } => codes.contains(&code),
+      KeyRange::Range { first, last } => (*first..=*last).contains(&code),
+    }
+  }
+}
+
+/// Keystrokes to render with a pack, without wayvibes or an audio device
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreviewRequest {
+  pub pack_id: String,
+  pub input: PreviewInput,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum PreviewInput {
+  /// Typed at a steady pace; characters without a key of their own are skipped
+  #[serde(rename_all = "camelCase")]
+  Text {
+    text: String,
+    #[serde(default)]
+    interval_ms: Option<f64>,
+  },
+  Keys { strokes: Vec<KeyStroke> },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyStroke {
+  /// Mechvibes keycode
+  pub code: u32,
+  pub at_ms: f64,
+  /// Time until the key-up sound; `None` plays only the press
+  #[serde(default)]
+  pub hold_ms: Option<f64>,
+}
+
+/// Measured loudness of a pack's sounds, in dBFS
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackLoudness {
+  pub pack_id: String,
+  pub files: usize,
+  pub peak_db: f32,
+  pub rms_db: f32,
+  pub suggested_gain_db: f32,
+}
+
+/// Target of a pack conversion to 16-bit PCM WAV
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscodeOptions {
+  /// Resample every sound to this rate; `None` keeps each file's own rate
+  #[serde(default)]
+  pub sample_rate: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscodeReport {
+  pub pack_id: String,
+  /// Audio files the pack refers to
+  pub files: usize,
+  /// Files that were rewritten; the rest already had the target format
+  pub converted: usize,
+  pub bytes_before: u64,
+  pub bytes_after: u64,
+}
+
+/// Steps of `process_sound_pack`; each one is skipped when left out
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessOptions {
+  #[serde(default)]
+  pub trim: Option<TrimOptions>,
+  #[serde(default)]
+  pub normalize: Option<NormalizeTarget>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrimOptions {
+  /// Samples at or below this level count as silence
+  pub threshold_db: f32,
+  /// Silence kept before and after the sound so the attack isn't cut
+  #[serde(default)]
+  pub padding_ms: f64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum NormalizeTarget {
+  /// Highest sample, in dBFS
+  Peak { db: f32 },
+  /// Gated RMS in dBFS, limited so the peak stays under full scale
+  Rms { db: f32 },
+}
+
+/// What processing did to one sound of the source pack
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SoundChange {
+  pub source: KeySound,
+  pub keys: usize,
+  pub trimmed_start_ms: f64,
+  pub trimmed_end_ms: f64,
+  pub gain_db: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessReport {
+  pub source_pack_id: String,
+  pub pack: SoundPack,
+  pub changes: Vec<SoundChange>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -33,4 +477,130 @@ pub struct WayvibesStatus {
   pub running: bool,
   pub version: Option<String>,
   pub pid: Option<u32>,
+  #[serde(default)]
+  pub capabilities: Option<WayvibesCapabilities>,
+  #[serde(default)]
+  pub preflight: Option<PreflightSummary>,
+  /// Where the app-owned process is in its lifecycle, independent of strays found by pgrep
+  #[serde(default)]
+  pub lifecycle: Option<LifecycleState>,
+}
+
+/// Lifecycle of the wayvibes child owned by the app
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LifecycleState {
+  Stopped,
+  Starting,
+  Running,
+  Stopping,
+  /// The last start failed or the process exited on its own
+  Failed,
+}
+
+/// What the installed wayvibes supports, parsed from `--version` and `--help`.
+/// When `probed` is false nothing could be parsed and every feature is assumed available.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WayvibesCapabilities {
+  pub probed: bool,
+  pub version: Option<String>,
+  pub flags: Vec<String>,
+  pub volume: bool,
+  pub volume_max: Option<f32>,
+  pub background: bool,
+  pub device_selection: bool,
+  /// Accepts `pack <path>` and `volume <value>` lines on stdin when started with `--control`
+  #[serde(default)]
+  pub live_control: bool,
+  /// Plays the Mechvibes++ key-up table
+  #[serde(default)]
+  pub keyup: bool,
+  /// Picks among per-key variations
+  #[serde(default)]
+  pub variations: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+  Pass,
+  Warn,
+  Fail,
+}
+
+/// One prerequisite of wayvibes, with what to do about it when it isn't met
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreflightCheck {
+  pub id: String,
+  pub label: String,
+  pub status: CheckStatus,
+  pub detail: String,
+  pub remediation: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreflightSummary {
+  /// Worst status among the checks
+  pub status: CheckStatus,
+  pub passed: usize,
+  pub warnings: usize,
+  pub failures: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreflightReport {
+  pub summary: PreflightSummary,
+  pub checks: Vec<PreflightCheck>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WayvibesCandidate {
+  pub path: String,
+  pub source: String,
+  pub executable: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WayvibesLocation {
+  pub resolved: Option<String>,
+  pub candidates: Vec<WayvibesCandidate>,
+}
+
+/// Which output of the supervised wayvibes a log line came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LogStream {
+  Stdout,
+  Stderr,
+  /// Lines written by the app itself about the process (start, stop, exit)
+  Supervisor,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogLine {
+  pub seq: u64,
+  pub timestamp: String,
+  pub stream: LogStream,
+  pub text: String,
+}
+
+/// State changes broadcast to the frontend and to control socket subscribers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+#[allow(clippy::large_enum_variant)]
+pub enum AppEvent {
+  StateChanged {
+    config: AppConfig,
+    status: WayvibesStatus,
+  },
+  PacksChanged {
+    packs: Vec<SoundPack>,
+  },
 }
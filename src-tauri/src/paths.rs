@@ -0,0 +1,37 @@
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::PathBuf;
+
+use directories::ProjectDirs;
+
+use crate::error::AppError;
+
+pub fn project_dirs() -> Result<ProjectDirs, AppError> {
+  ProjectDirs::from("", "", "wayvibes-ui")
+    .ok_or_else(|| AppError::InvalidConfig("Diretório do app indisponível".into()))
+}
+
+/// Directory installed packs live in, one folder per pack
+pub fn packs_dir() -> Result<PathBuf, AppError> {
+  Ok(project_dirs()?.data_dir().join("packs"))
+}
+
+/// Directory for the app and wayvibes log files
+pub fn logs_dir() -> Result<PathBuf, AppError> {
+  Ok(project_dirs()?.data_dir().join("logs"))
+}
+
+/// Per-user directory for sockets and lock files ($XDG_RUNTIME_DIR/wayvibes-ui)
+pub fn runtime_dir() -> Result<PathBuf, AppError> {
+  let dir = match project_dirs()?.runtime_dir() {
+    Some(dir) => dir.to_path_buf(),
+    None => {
+      let user = std::env::var("USER").unwrap_or_else(|_| "default".into());
+      std::env::temp_dir().join(format!("wayvibes-ui-{}", user))
+    }
+  };
+
+  fs::create_dir_all(&dir)?;
+  fs::set_permissions(&dir, fs::Permissions::from_mode(0o700))?;
+  Ok(dir)
+}
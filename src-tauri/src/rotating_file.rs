@@ -0,0 +1,81 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Append-only log file that rolls over to `name.1`, `name.2`, ... once it reaches `max_bytes`
+pub struct RotatingFile {
+  path: PathBuf,
+  max_bytes: u64,
+  keep: usize,
+  file: Option<File>,
+  size: u64,
+}
+
+impl RotatingFile {
+  pub fn new(path: PathBuf, max_bytes: u64, keep: usize) -> Self {
+    Self {
+      path,
+      max_bytes,
+      keep,
+      file: None,
+      size: 0,
+    }
+  }
+
+  pub fn path(&self) -> &Path {
+    &self.path
+  }
+
+  pub fn write_line(&mut self, line: &str) -> io::Result<()> {
+    let len = line.len() as u64 + 1;
+    if self.file.is_none() {
+      self.open()?;
+    }
+    if self.size > 0 && self.size + len > self.max_bytes {
+      self.rotate()?;
+    }
+
+    let file = match self.file.as_mut() {
+      Some(file) => file,
+      None => return Ok(()),
+    };
+    writeln!(file, "{}", line)?;
+    self.size += len;
+    Ok(())
+  }
+
+  fn open(&mut self) -> io::Result<()> {
+    if let Some(parent) = self.path.parent() {
+      fs::create_dir_all(parent)?;
+    }
+    let file = OpenOptions::new()
+      .create(true)
+      .append(true)
+      .open(&self.path)?;
+    self.size = file.metadata().map(|meta| meta.len()).unwrap_or(0);
+    self.file = Some(file);
+    Ok(())
+  }
+
+  fn rotate(&mut self) -> io::Result<()> {
+    self.file = None;
+    for index in (1..self.keep).rev() {
+      let from = self.rotated_path(index);
+      if from.exists() {
+        fs::rename(&from, self.rotated_path(index + 1))?;
+      }
+    }
+    if self.keep > 0 {
+      fs::rename(&self.path, self.rotated_path(1))?;
+    } else {
+      fs::remove_file(&self.path)?;
+    }
+    self.open()
+  }
+
+  fn rotated_path(&self, index: usize) -> PathBuf {
+    let mut name = self.path.as_os_str().to_os_string();
+    name.push(format!(".{}", index));
+    PathBuf::from(name)
+  }
+}
@@ -0,0 +1,85 @@
+use std::time::Duration;
+
+use chrono::Local;
+use tauri::{AppHandle, Manager};
+
+use crate::commands::wayvibes_commands;
+use crate::error::AppError;
+use crate::models::{ActiveSchedule, ScheduleAction};
+use crate::services::scheduler_service;
+use crate::state::AppState;
+use crate::tray;
+
+const TICK: Duration = Duration::from_secs(30);
+
+/// A schedule rule in effect. Its action is applied on top of the manual state and never
+/// saved, so the manual state is back as soon as the window ends.
+pub struct ScheduleOverride {
+  pub active: ActiveSchedule,
+  pub action: ScheduleAction,
+}
+
+pub fn start(app: AppHandle) {
+  tauri::async_runtime::spawn(async move {
+    let mut interval = tokio::time::interval(TICK);
+    loop {
+      interval.tick().await;
+      evaluate(&app).await;
+    }
+  });
+}
+
+/// Apply or release schedule overrides for the current local time
+pub async fn evaluate(app: &AppHandle) {
+  let state = app.state::<AppState>();
+
+  // Like the commands, queue the lifecycle change while holding the config lock
+  let (pending, active) = {
+    let Ok(config) = state.config.lock() else {
+      return;
+    };
+    let rule = scheduler_service::active_rule(&config.schedules, Local::now().naive_local());
+    let was_playing = wayvibes_commands::is_playing(&state, &config);
+
+    let Ok(mut current) = state.schedule_override.lock() else {
+      return;
+    };
+    let unchanged = match (rule, current.as_ref()) {
+      (Some(rule), Some(active)) => {
+        rule.id == active.active.rule_id && rule.action == active.action
+      }
+      (None, None) => true,
+      _ => false,
+    };
+    if unchanged {
+      return;
+    }
+
+    if let Some(previous) = current.take() {
+      log::info!("Schedule '{}' ended", previous.active.rule_name);
+    }
+    *current = rule.map(|rule| {
+      log::info!("Schedule '{}' started", rule.name);
+      ScheduleOverride {
+        active: ActiveSchedule {
+          rule_id: rule.id.clone(),
+          rule_name: rule.name.clone(),
+        },
+        action: rule.action.clone(),
+      }
+    });
+    let active = current.as_ref().map(|schedule| schedule.active.clone());
+    drop(current);
+
+    (wayvibes_commands::queue_schedule_change(&state, &config, was_playing), active)
+  };
+
+  tray::set_schedule_status(app, active.as_ref());
+  if let Some(pending) = pending {
+    match pending.await {
+      Ok(()) | Err(AppError::WayvibesMissing) => {}
+      Err(err) => log::error!("Failed to apply schedule change: {}", err),
+    }
+  }
+  state.publish_state();
+}
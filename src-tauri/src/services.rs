@@ -1,6 +1,42 @@
+#[path = "services/audio-service.rs"]
+pub mod audio_service;
 #[path = "services/config-service.rs"]
 pub mod config_service;
+#[path = "services/diagnostics-service.rs"]
+pub mod diagnostics_service;
+#[path = "services/keymap.rs"]
+pub mod keymap;
+#[path = "services/pack-builder-service.rs"]
+pub mod pack_builder_service;
+#[path = "services/pack-editor-service.rs"]
+pub mod pack_editor_service;
+#[path = "services/pack-formats.rs"]
+pub mod pack_formats;
+#[path = "services/pack-manifest.rs"]
+pub mod pack_manifest;
+#[path = "services/pack-processing-service.rs"]
+pub mod pack_processing_service;
+#[path = "services/pack-settings-service.rs"]
+pub mod pack_settings_service;
+#[path = "services/preflight-service.rs"]
+pub mod preflight_service;
+#[path = "services/preview-service.rs"]
+pub mod preview_service;
+#[path = "services/profile-service.rs"]
+pub mod profile_service;
+#[path = "services/scheduler-service.rs"]
+pub mod scheduler_service;
 #[path = "services/sound-pack-service.rs"]
 pub mod sound_pack_service;
+#[path = "services/transcode-service.rs"]
+pub mod transcode_service;
+#[path = "services/volume-service.rs"]
+pub mod volume_service;
+#[path = "services/wayvibes-capabilities.rs"]
+pub mod wayvibes_capabilities;
+#[path = "services/wayvibes-locator.rs"]
+pub mod wayvibes_locator;
+#[path = "services/wayvibes-logs.rs"]
+pub mod wayvibes_logs;
 #[path = "services/wayvibes-service.rs"]
 pub mod wayvibes_service;
@@ -0,0 +1,315 @@
+use std::fs::File;
+use std::io::{Cursor, ErrorKind, Seek, Write};
+use std::path::Path;
+
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{DecoderOptions, CODEC_TYPE_NULL};
+use symphonia::core::errors::Error as SymphoniaError;
+use symphonia::core::formats::FormatOptions;
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+
+use crate::error::AppError;
+
+/// Extensions wayvibes and Mechvibes packs use for their sounds
+pub const AUDIO_EXTENSIONS: &[&str] = &["wav", "ogg", "mp3", "flac"];
+
+/// Level reported for digital silence
+pub const SILENCE_DB: f32 = -120.0;
+/// Windows quieter than this don't count towards the RMS, so gaps between clicks are ignored
+const RMS_GATE_DB: f32 = -50.0;
+const RMS_WINDOW_MS: u32 = 10;
+
+/// Decoded audio as interleaved samples in -1.0..=1.0
+#[derive(Debug, Clone)]
+pub struct AudioBuffer {
+  pub sample_rate: u32,
+  pub channels: usize,
+  pub samples: Vec<f32>,
+  /// Integer resolution of a lossless source; decides how `write_wav` stores the samples
+  pub bits_per_sample: Option<u32>,
+}
+
+impl AudioBuffer {
+  pub fn empty(sample_rate: u32, channels: usize) -> Self {
+    Self {
+      sample_rate,
+      channels,
+      samples: Vec::new(),
+      bits_per_sample: Some(16),
+    }
+  }
+
+  pub fn frames(&self) -> usize {
+    self.samples.len() / self.channels.max(1)
+  }
+
+  pub fn ms_to_frame(&self, ms: f64) -> usize {
+    (ms.max(0.0) * self.sample_rate as f64 / 1000.0).round() as usize
+  }
+
+  pub fn frame_to_ms(&self, frame: usize) -> f64 {
+    frame as f64 * 1000.0 / self.sample_rate as f64
+  }
+
+  /// Copy of `len` frames from `start`, cut short at the end of the buffer
+  pub fn slice(&self, start: usize, len: usize) -> AudioBuffer {
+    let channels = self.channels.max(1);
+    let start = start.min(self.frames());
+    let end = start.saturating_add(len).min(self.frames());
+    AudioBuffer {
+      sample_rate: self.sample_rate,
+      channels: self.channels,
+      samples: self.samples[start * channels..end * channels].to_vec(),
+      bits_per_sample: self.bits_per_sample,
+    }
+  }
+
+  /// Append another buffer with the same rate. Mono is widened to the channel count of
+  /// `self` by copying the channel, which keeps the samples exact.
+  pub fn append(&mut self, other: &AudioBuffer) -> Result<(), AppError> {
+    if other.sample_rate != self.sample_rate {
+      return Err(AppError::Audio(format!(
+        "taxas de amostragem diferentes ({} Hz e {} Hz)",
+        self.sample_rate, other.sample_rate
+      )));
+    }
+    if other.channels == self.channels {
+      self.samples.extend_from_slice(&other.samples);
+    } else if other.channels == 1 {
+      for sample in &other.samples {
+        self.samples.extend(std::iter::repeat(*sample).take(self.channels));
+      }
+    } else {
+      return Err(AppError::Audio(format!(
+        "número de canais incompatível ({} e {})",
+        self.channels, other.channels
+      )));
+    }
+    self.bits_per_sample = match (self.bits_per_sample, other.bits_per_sample) {
+      (Some(a), Some(b)) => Some(a.max(b)),
+      _ => None,
+    };
+    Ok(())
+  }
+
+  /// Copy at another sample rate, by linear interpolation between neighbouring frames.
+  /// Good enough for short clicks; the result is no longer bit-exact.
+  pub fn resample(&self, sample_rate: u32) -> AudioBuffer {
+    if sample_rate == self.sample_rate || self.samples.is_empty() {
+      return AudioBuffer {
+        sample_rate,
+        ..self.clone()
+      };
+    }
+    let channels = self.channels.max(1);
+    let frames = self.frames();
+    let ratio = self.sample_rate as f64 / sample_rate as f64;
+    let out_frames = ((frames as f64) / ratio).round().max(1.0) as usize;
+
+    let mut samples = Vec::with_capacity(out_frames * channels);
+    for frame in 0..out_frames {
+      let position = frame as f64 * ratio;
+      let index = (position.floor() as usize).min(frames - 1);
+      let next = (index + 1).min(frames - 1);
+      let weight = (position - index as f64) as f32;
+      for channel in 0..channels {
+        let a = self.samples[index * channels + channel];
+        let b = self.samples[next * channels + channel];
+        samples.push(a + (b - a) * weight);
+      }
+    }
+
+    AudioBuffer {
+      sample_rate,
+      channels: self.channels,
+      samples,
+      bits_per_sample: None,
+    }
+  }
+
+  /// First and one-past-last frame with a sample above `threshold_db`; `None` when the
+  /// whole buffer is below it
+  pub fn audible_range(&self, threshold_db: f32) -> Option<(usize, usize)> {
+    let channels = self.channels.max(1);
+    let threshold = db_to_linear(threshold_db);
+    let audible = |frame: &[f32]| frame.iter().any(|sample| sample.abs() > threshold);
+    let start = self.samples.chunks(channels).position(audible)?;
+    let end = self.frames() - self.samples.chunks(channels).rev().position(audible)?;
+    Some((start, end))
+  }
+
+  pub fn apply_gain(&mut self, gain_db: f32) {
+    let factor = db_to_linear(gain_db);
+    for sample in &mut self.samples {
+      *sample *= factor;
+    }
+  }
+
+  /// Highest absolute sample, in dBFS
+  pub fn peak_db(&self) -> f32 {
+    let peak = self
+      .samples
+      .iter()
+      .fold(0.0_f32, |peak, sample| peak.max(sample.abs()));
+    linear_to_db(peak)
+  }
+
+  /// RMS of the windows above the gate, in dBFS; `SILENCE_DB` when nothing passes it
+  pub fn gated_rms_db(&self) -> f32 {
+    let window = (self.sample_rate * RMS_WINDOW_MS / 1000) as usize * self.channels.max(1);
+    if window == 0 {
+      return SILENCE_DB;
+    }
+    let gate = db_to_linear(RMS_GATE_DB);
+
+    let (mut sum, mut count) = (0.0_f64, 0_usize);
+    for chunk in self.samples.chunks(window) {
+      let energy: f64 = chunk.iter().map(|sample| (*sample as f64).powi(2)).sum();
+      let rms = (energy / chunk.len() as f64).sqrt() as f32;
+      if rms >= gate {
+        sum += energy;
+        count += chunk.len();
+      }
+    }
+    if count == 0 {
+      return SILENCE_DB;
+    }
+    linear_to_db((sum / count as f64).sqrt() as f32)
+  }
+}
+
+pub fn is_audio_file(path: &Path) -> bool {
+  path
+    .extension()
+    .and_then(|ext| ext.to_str())
+    .map(|ext| AUDIO_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+    .unwrap_or(false)
+}
+
+/// Decode the first audio track of a WAV, OGG, MP3 or FLAC file
+pub fn decode(path: &Path) -> Result<AudioBuffer, AppError> {
+  let audio_error =
+    |err: SymphoniaError| AppError::Audio(format!("{}: {}", path.display(), err));
+
+  let source = MediaSourceStream::new(Box::new(File::open(path)?), Default::default());
+  let mut hint = Hint::new();
+  if let Some(ext) = path.extension().and_then(|ext| ext.to_str()) {
+    hint.with_extension(ext);
+  }
+  let probed = symphonia::default::get_probe()
+    .format(&hint, source, &FormatOptions::default(), &MetadataOptions::default())
+    .map_err(audio_error)?;
+  let mut format = probed.format;
+
+  let track = format
+    .tracks()
+    .iter()
+    .find(|track| track.codec_params.codec != CODEC_TYPE_NULL)
+    .ok_or_else(|| AppError::Audio(format!("{}: nenhuma faixa de áudio", path.display())))?;
+  let track_id = track.id;
+  let mut sample_rate = track.codec_params.sample_rate.unwrap_or(0);
+  let mut channels = track
+    .codec_params
+    .channels
+    .map(|channels| channels.count())
+    .unwrap_or(1);
+  let bits_per_sample = track.codec_params.bits_per_sample;
+  let mut decoder = symphonia::default::get_codecs()
+    .make(&track.codec_params, &DecoderOptions::default())
+    .map_err(audio_error)?;
+
+  let mut samples = Vec::new();
+  loop {
+    let packet = match format.next_packet() {
+      Ok(packet) => packet,
+      Err(SymphoniaError::IoError(err)) if err.kind() == ErrorKind::UnexpectedEof => break,
+      Err(SymphoniaError::ResetRequired) => break,
+      Err(err) => return Err(audio_error(err)),
+    };
+    if packet.track_id() != track_id {
+      continue;
+    }
+    match decoder.decode(&packet) {
+      Ok(decoded) => {
+        let spec = *decoded.spec();
+        let mut buffer = SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+        buffer.copy_interleaved_ref(decoded);
+        samples.extend_from_slice(buffer.samples());
+        sample_rate = spec.rate;
+        channels = spec.channels.count();
+      }
+      // A corrupt packet only loses a few milliseconds; keep going
+      Err(SymphoniaError::DecodeError(err)) => {
+        log::debug!("Skipping bad packet in {}: {}", path.display(), err);
+      }
+      Err(err) => return Err(audio_error(err)),
+    }
+  }
+
+  if sample_rate == 0 {
+    return Err(AppError::Audio(format!(
+      "{}: taxa de amostragem desconhecida",
+      path.display()
+    )));
+  }
+
+  Ok(AudioBuffer {
+    sample_rate,
+    channels,
+    samples,
+    bits_per_sample,
+  })
+}
+
+/// Write a WAV file: 16-bit PCM when the source had at most 16 bits, so a decoded 16-bit
+/// file is reproduced exactly, and 32-bit float otherwise
+pub fn write_wav(path: &Path, buffer: &AudioBuffer) -> Result<(), AppError> {
+  let file = std::io::BufWriter::new(File::create(path)?);
+  write_wav_to(file, buffer)
+    .map_err(|err| AppError::Audio(format!("{}: {}", path.display(), err)))
+}
+
+/// The WAV file `write_wav` would write, in memory
+pub fn wav_bytes(buffer: &AudioBuffer) -> Result<Vec<u8>, AppError> {
+  let mut cursor = Cursor::new(Vec::new());
+  write_wav_to(&mut cursor, buffer).map_err(|err| AppError::Audio(err.to_string()))?;
+  Ok(cursor.into_inner())
+}
+
+fn write_wav_to<W: Write + Seek>(writer: W, buffer: &AudioBuffer) -> Result<(), hound::Error> {
+  let as_int = matches!(buffer.bits_per_sample, Some(bits) if bits <= 16);
+  let spec = hound::WavSpec {
+    channels: buffer.channels as u16,
+    sample_rate: buffer.sample_rate,
+    bits_per_sample: if as_int { 16 } else { 32 },
+    sample_format: if as_int {
+      hound::SampleFormat::Int
+    } else {
+      hound::SampleFormat::Float
+    },
+  };
+
+  let mut writer = hound::WavWriter::new(writer, spec)?;
+  for sample in &buffer.samples {
+    if as_int {
+      let value = (sample * 32768.0).round().clamp(i16::MIN as f32, i16::MAX as f32) as i16;
+      writer.write_sample(value)?;
+    } else {
+      writer.write_sample(*sample)?;
+    }
+  }
+  writer.finalize()
+}
+
+pub fn db_to_linear(db: f32) -> f32 {
+  10.0_f32.powf(db / 20.0)
+}
+
+pub fn linear_to_db(value: f32) -> f32 {
+  if value <= 0.0 {
+    return SILENCE_DB;
+  }
+  (20.0 * value.log10()).max(SILENCE_DB)
+}
@@ -4,20 +4,31 @@ use std::path::Path;
 use crate::error::AppError;
 use crate::models::AppConfig;
 
+const LOG_TARGET: &str = "config";
+
 pub fn load_config(path: &Path) -> Result<AppConfig, AppError> {
   if !path.exists() {
+    log::info!(target: LOG_TARGET, "Creating default config at {}", path.display());
     let config = AppConfig::default();
     save_config(path, &config)?;
     return Ok(config);
   }
 
   let content = fs::read_to_string(path)?;
-  let config = serde_json::from_str::<AppConfig>(&content)?;
+  let config = serde_json::from_str::<AppConfig>(&content).map_err(|err| {
+    log::error!(target: LOG_TARGET, "Invalid config at {}: {}", path.display(), err);
+    err
+  })?;
+  log::debug!(target: LOG_TARGET, "Loaded config from {}", path.display());
   Ok(config)
 }
 
 pub fn save_config(path: &Path, config: &AppConfig) -> Result<(), AppError> {
   let payload = serde_json::to_string_pretty(config)?;
-  fs::write(path, payload)?;
+  fs::write(path, payload).map_err(|err| {
+    log::error!(target: LOG_TARGET, "Failed to save {}: {}", path.display(), err);
+    err
+  })?;
+  log::debug!(target: LOG_TARGET, "Saved config to {}", path.display());
   Ok(())
 }
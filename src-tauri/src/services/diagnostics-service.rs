@@ -0,0 +1,145 @@
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::error::AppError;
+use crate::models::{AppConfig, LogLine, SoundPack, WayvibesLocation, WayvibesStatus};
+use crate::services::preflight_service;
+
+/// Everything gathered from the running app for a bundle
+pub struct Diagnostics {
+  pub config: AppConfig,
+  pub packs: Vec<SoundPack>,
+  pub status: WayvibesStatus,
+  pub location: WayvibesLocation,
+  pub wayvibes_logs: Vec<LogLine>,
+  pub errors: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EnvironmentFacts {
+  app_version: &'static str,
+  os: Option<String>,
+  kernel: Option<String>,
+  xdg_session_type: Option<String>,
+  wayland_display: Option<String>,
+  xdg_current_desktop: Option<String>,
+  groups: Vec<String>,
+  in_input_group: bool,
+  input_devices: Vec<InputDevice>,
+}
+
+#[derive(Debug, Serialize)]
+struct InputDevice {
+  path: String,
+  readable: bool,
+}
+
+/// Write a zip with config, packs, wayvibes probe, preflight, logs and environment facts.
+/// Paths under the home directory are shortened to `~` and env values are dropped.
+pub fn export(path: &Path, diagnostics: &Diagnostics, logs_dir: &Path) -> Result<(), AppError> {
+  if let Some(parent) = path.parent() {
+    fs::create_dir_all(parent)?;
+  }
+
+  let mut zip = ZipWriter::new(File::create(path)?);
+  let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+
+  let mut add = |name: &str, content: &str| -> Result<(), AppError> {
+    zip.start_file(name, options)?;
+    zip.write_all(redact_home(content).as_bytes())?;
+    Ok(())
+  };
+
+  add("config.json", &pretty(&redact_config(&diagnostics.config))?)?;
+  add("packs.json", &pretty(&diagnostics.packs)?)?;
+  add(
+    "wayvibes.json",
+    &pretty(&json!({
+      "status": diagnostics.status,
+      "location": diagnostics.location,
+    }))?,
+  )?;
+  add("environment.json", &pretty(&environment())?)?;
+  add(
+    "preflight.json",
+    &pretty(&preflight_service::run(&diagnostics.config.wayvibes))?,
+  )?;
+  add("errors.log", &diagnostics.errors.join("\n"))?;
+
+  let wayvibes_lines: Vec<String> = diagnostics
+    .wayvibes_logs
+    .iter()
+    .map(|line| format!("{} [{:?}] {}", line.timestamp, line.stream, line.text))
+    .collect();
+  add("logs/wayvibes-buffer.log", &wayvibes_lines.join("\n"))?;
+
+  for name in ["app.log", "app.log.1", "wayvibes.log"] {
+    if let Ok(content) = fs::read_to_string(logs_dir.join(name)) {
+      add(&format!("logs/{}", name), &content)?;
+    }
+  }
+
+  zip.finish()?;
+  Ok(())
+}
+
+fn environment() -> EnvironmentFacts {
+  let groups = preflight_service::groups();
+  EnvironmentFacts {
+    app_version: env!("CARGO_PKG_VERSION"),
+    os: os_name(),
+    kernel: fs::read_to_string("/proc/sys/kernel/osrelease")
+      .ok()
+      .map(|value| value.trim().to_string()),
+    xdg_session_type: std::env::var("XDG_SESSION_TYPE").ok(),
+    wayland_display: std::env::var("WAYLAND_DISPLAY").ok(),
+    xdg_current_desktop: std::env::var("XDG_CURRENT_DESKTOP").ok(),
+    in_input_group: groups.iter().any(|group| group == "input"),
+    groups,
+    input_devices: preflight_service::input_devices()
+      .into_iter()
+      .map(|(path, readable)| InputDevice { path, readable })
+      .collect(),
+  }
+}
+
+fn os_name() -> Option<String> {
+  let content = fs::read_to_string("/etc/os-release").ok()?;
+  content
+    .lines()
+    .find_map(|line| line.strip_prefix("PRETTY_NAME="))
+    .map(|value| value.trim_matches('"').to_string())
+}
+
+/// Config without the values of custom env vars, which may hold tokens
+fn redact_config(config: &AppConfig) -> Value {
+  let mut value = serde_json::to_value(config).unwrap_or(Value::Null);
+  if let Some(env) = value
+    .get_mut("wayvibes")
+    .and_then(|wayvibes| wayvibes.get_mut("env"))
+    .and_then(Value::as_object_mut)
+  {
+    for entry in env.values_mut() {
+      *entry = Value::String("<redacted>".into());
+    }
+  }
+  value
+}
+
+fn redact_home(content: &str) -> String {
+  match std::env::var("HOME") {
+    Ok(home) if home.len() > 1 => content.replace(&home, "~"),
+    _ => content.to_string(),
+  }
+}
+
+fn pretty<T: Serialize + ?Sized>(value: &T) -> Result<String, AppError> {
+  Ok(serde_json::to_string_pretty(value)?)
+}
@@ -0,0 +1,379 @@
+use std::collections::BTreeSet;
+
+use crate::models::KeyboardLayout;
+
+/// A physical key with the codes Mechvibes packs and the Linux kernel use for it
+#[derive(Debug, Clone, Copy)]
+pub struct Key {
+  /// iohook keycode used in a Mechvibes `defines` table
+  pub code: u32,
+  /// Linux input event code, as in `linux/input-event-codes.h`
+  pub evdev: u16,
+  /// Name of `evdev` without the `KEY_` prefix
+  pub evdev_name: &'static str,
+  pub label: &'static str,
+  /// Lowercase names a sound file may use for the key, e.g. `enter.wav`
+  pub aliases: &'static [&'static str],
+}
+
+const fn key(
+  code: u32,
+  evdev: u16,
+  evdev_name: &'static str,
+  label: &'static str,
+  aliases: &'static [&'static str],
+) -> Key {
+  Key {
+    code,
+    evdev,
+    evdev_name,
+    label,
+    aliases,
+  }
+}
+
+/// Every key of a full-size ISO/ANSI keyboard, in row order
+pub const KEYS: &[Key] = &[
+  key(1, 1, "ESC", "Esc", &["esc", "escape"]),
+  key(59, 59, "F1", "F1", &["f1"]),
+  key(60, 60, "F2", "F2", &["f2"]),
+  key(61, 61, "F3", "F3", &["f3"]),
+  key(62, 62, "F4", "F4", &["f4"]),
+  key(63, 63, "F5", "F5", &["f5"]),
+  key(64, 64, "F6", "F6", &["f6"]),
+  key(65, 65, "F7", "F7", &["f7"]),
+  key(66, 66, "F8", "F8", &["f8"]),
+  key(67, 67, "F9", "F9", &["f9"]),
+  key(68, 68, "F10", "F10", &["f10"]),
+  key(87, 87, "F11", "F11", &["f11"]),
+  key(88, 88, "F12", "F12", &["f12"]),
+  key(3639, 99, "SYSRQ", "Print Screen", &["printscreen", "print", "prtsc", "sysrq"]),
+  key(70, 70, "SCROLLLOCK", "Scroll Lock", &["scrolllock", "scroll"]),
+  key(3653, 119, "PAUSE", "Pause", &["pause", "break"]),
+  key(41, 41, "GRAVE", "`", &["grave", "backquote", "backtick", "tilde"]),
+  key(2, 2, "1", "1", &["1", "digit1"]),
+  key(3, 3, "2", "2", &["2", "digit2"]),
+  key(4, 4, "3", "3", &["3", "digit3"]),
+  key(5, 5, "4", "4", &["4", "digit4"]),
+  key(6, 6, "5", "5", &["5", "digit5"]),
+  key(7, 7, "6", "6", &["6", "digit6"]),
+  key(8, 8, "7", "7", &["7", "digit7"]),
+  key(9, 9, "8", "8", &["8", "digit8"]),
+  key(10, 10, "9", "9", &["9", "digit9"]),
+  key(11, 11, "0", "0", &["0", "digit0"]),
+  key(12, 12, "MINUS", "-", &["minus", "dash", "hyphen"]),
+  key(13, 13, "EQUAL", "=", &["equal", "equals"]),
+  key(14, 14, "BACKSPACE", "Backspace", &["backspace", "bksp", "back"]),
+  key(3666, 110, "INSERT", "Insert", &["insert", "ins"]),
+  key(3655, 102, "HOME", "Home", &["home"]),
+  key(3657, 104, "PAGEUP", "Page Up", &["pageup", "pgup"]),
+  key(69, 69, "NUMLOCK", "Num Lock", &["numlock"]),
+  key(3637, 98, "KPSLASH", "Numpad /", &["numpaddivide", "kpslash", "numpadslash"]),
+  key(55, 55, "KPASTERISK", "Numpad *", &["numpadmultiply", "kpasterisk", "numpadstar"]),
+  key(74, 74, "KPMINUS", "Numpad -", &["numpadsubtract", "kpminus", "numpadminus"]),
+  key(15, 15, "TAB", "Tab", &["tab"]),
+  key(16, 16, "Q", "Q", &["q"]),
+  key(17, 17, "W", "W", &["w"]),
+  key(18, 18, "E", "E", &["e"]),
+  key(19, 19, "R", "R", &["r"]),
+  key(20, 20, "T", "T", &["t"]),
+  key(21, 21, "Y", "Y", &["y"]),
+  key(22, 22, "U", "U", &["u"]),
+  key(23, 23, "I", "I", &["i"]),
+  key(24, 24, "O", "O", &["o"]),
+  key(25, 25, "P", "P", &["p"]),
+  key(26, 26, "LEFTBRACE", "[", &["leftbracket", "bracketleft", "lbracket", "leftbrace"]),
+  key(27, 27, "RIGHTBRACE", "]", &["rightbracket", "bracketright", "rbracket", "rightbrace"]),
+  key(43, 43, "BACKSLASH", "\\", &["backslash"]),
+  key(3667, 111, "DELETE", "Delete", &["delete", "del"]),
+  key(3663, 107, "END", "End", &["end"]),
+  key(3665, 109, "PAGEDOWN", "Page Down", &["pagedown", "pgdn"]),
+  key(71, 71, "KP7", "Numpad 7", &["numpad7", "kp7"]),
+  key(72, 72, "KP8", "Numpad 8", &["numpad8", "kp8"]),
+  key(73, 73, "KP9", "Numpad 9", &["numpad9", "kp9"]),
+  key(78, 78, "KPPLUS", "Numpad +", &["numpadadd", "kpplus", "numpadplus"]),
+  key(58, 58, "CAPSLOCK", "Caps Lock", &["capslock", "caps"]),
+  key(30, 30, "A", "A", &["a"]),
+  key(31, 31, "S", "S", &["s"]),
+  key(32, 32, "D", "D", &["d"]),
+  key(33, 33, "F", "F", &["f"]),
+  key(34, 34, "G", "G", &["g"]),
+  key(35, 35, "H", "H", &["h"]),
+  key(36, 36, "J", "J", &["j"]),
+  key(37, 37, "K", "K", &["k"]),
+  key(38, 38, "L", "L", &["l"]),
+  key(39, 39, "SEMICOLON", ";", &["semicolon"]),
+  key(40, 40, "APOSTROPHE", "'", &["apostrophe", "quote"]),
+  key(28, 28, "ENTER", "Enter", &["enter", "return"]),
+  key(75, 75, "KP4", "Numpad 4", &["numpad4", "kp4"]),
+  key(76, 76, "KP5", "Numpad 5", &["numpad5", "kp5"]),
+  key(77, 77, "KP6", "Numpad 6", &["numpad6", "kp6"]),
+  key(42, 42, "LEFTSHIFT", "Left Shift", &["shift", "leftshift", "lshift", "shiftleft"]),
+  key(86, 86, "102ND", "ISO \\", &["intlbackslash", "102nd", "iso"]),
+  key(44, 44, "Z", "Z", &["z"]),
+  key(45, 45, "X", "X", &["x"]),
+  key(46, 46, "C", "C", &["c"]),
+  key(47, 47, "V", "V", &["v"]),
+  key(48, 48, "B", "B", &["b"]),
+  key(49, 49, "N", "N", &["n"]),
+  key(50, 50, "M", "M", &["m"]),
+  key(51, 51, "COMMA", ",", &["comma"]),
+  key(52, 52, "DOT", ".", &["period", "dot"]),
+  key(53, 53, "SLASH", "/", &["slash"]),
+  key(54, 54, "RIGHTSHIFT", "Right Shift", &["rightshift", "rshift", "shiftright"]),
+  key(57416, 103, "UP", "Up", &["up", "arrowup", "uparrow"]),
+  key(79, 79, "KP1", "Numpad 1", &["numpad1", "kp1"]),
+  key(80, 80, "KP2", "Numpad 2", &["numpad2", "kp2"]),
+  key(81, 81, "KP3", "Numpad 3", &["numpad3", "kp3"]),
+  key(3612, 96, "KPENTER", "Numpad Enter", &["numpadenter", "kpenter"]),
+  key(29, 29, "LEFTCTRL", "Left Ctrl", &["ctrl", "control", "leftctrl", "lctrl", "controlleft"]),
+  key(3675, 125, "LEFTMETA", "Left Super", &["super", "meta", "win", "leftmeta", "lmeta"]),
+  key(56, 56, "LEFTALT", "Left Alt", &["alt", "leftalt", "lalt", "altleft"]),
+  key(57, 57, "SPACE", "Space", &["space", "spacebar"]),
+  key(3640, 100, "RIGHTALT", "Right Alt", &["rightalt", "ralt", "altgr", "altright"]),
+  key(3676, 126, "RIGHTMETA", "Right Super", &["rightmeta", "rmeta", "rightsuper"]),
+  key(3677, 127, "COMPOSE", "Menu", &["menu", "compose", "contextmenu"]),
+  key(3613, 97, "RIGHTCTRL", "Right Ctrl", &["rightctrl", "rctrl", "controlright"]),
+  key(57419, 105, "LEFT", "Left", &["left", "arrowleft", "leftarrow"]),
+  key(57424, 108, "DOWN", "Down", &["down", "arrowdown", "downarrow"]),
+  key(57421, 106, "RIGHT", "Right", &["right", "arrowright", "rightarrow"]),
+  key(82, 82, "KP0", "Numpad 0", &["numpad0", "kp0"]),
+  key(83, 83, "KPDOT", "Numpad .", &["numpaddecimal", "kpdot", "numpaddot"]),
+];
+
+/// A Linux key with no Mechvibes keycode, so no pack can give it a sound
+#[derive(Debug, Clone, Copy)]
+pub struct OtherKey {
+  pub evdev: u16,
+  pub evdev_name: &'static str,
+  pub label: &'static str,
+}
+
+const fn other(evdev: u16, evdev_name: &'static str, label: &'static str) -> OtherKey {
+  OtherKey {
+    evdev,
+    evdev_name,
+    label,
+  }
+}
+
+/// Keys a custom layout may list beyond `KEYS`: international, media and extra function keys
+pub const OTHER_KEYS: &[OtherKey] = &[
+  other(85, "ZENKAKUHANKAKU", "Zenkaku/Hankaku"),
+  other(89, "RO", "Ro"),
+  other(90, "KATAKANA", "Katakana"),
+  other(91, "HIRAGANA", "Hiragana"),
+  other(92, "HENKAN", "Henkan"),
+  other(93, "KATAKANAHIRAGANA", "Katakana/Hiragana"),
+  other(94, "MUHENKAN", "Muhenkan"),
+  other(95, "KPJPCOMMA", "Numpad , (JP)"),
+  other(113, "MUTE", "Mute"),
+  other(114, "VOLUMEDOWN", "Volume Down"),
+  other(115, "VOLUMEUP", "Volume Up"),
+  other(116, "POWER", "Power"),
+  other(117, "KPEQUAL", "Numpad ="),
+  other(118, "KPPLUSMINUS", "Numpad ±"),
+  other(121, "KPCOMMA", "Numpad ,"),
+  other(122, "HANGEUL", "Hangul"),
+  other(123, "HANJA", "Hanja"),
+  other(124, "YEN", "Yen"),
+  other(128, "STOP", "Stop"),
+  other(129, "AGAIN", "Again"),
+  other(130, "PROPS", "Props"),
+  other(131, "UNDO", "Undo"),
+  other(132, "FRONT", "Front"),
+  other(133, "COPY", "Copy"),
+  other(134, "OPEN", "Open"),
+  other(135, "PASTE", "Paste"),
+  other(136, "FIND", "Find"),
+  other(137, "CUT", "Cut"),
+  other(138, "HELP", "Help"),
+  other(139, "MENU", "Menu"),
+  other(140, "CALC", "Calculator"),
+  other(142, "SLEEP", "Sleep"),
+  other(143, "WAKEUP", "Wake Up"),
+  other(155, "MAIL", "Mail"),
+  other(156, "BOOKMARKS", "Bookmarks"),
+  other(157, "COMPUTER", "Computer"),
+  other(158, "BACK", "Back"),
+  other(159, "FORWARD", "Forward"),
+  other(161, "EJECTCD", "Eject"),
+  other(163, "NEXTSONG", "Next Track"),
+  other(164, "PLAYPAUSE", "Play/Pause"),
+  other(165, "PREVIOUSSONG", "Previous Track"),
+  other(166, "STOPCD", "Stop Media"),
+  other(172, "HOMEPAGE", "Home Page"),
+  other(173, "REFRESH", "Refresh"),
+  other(179, "KPLEFTPAREN", "Numpad ("),
+  other(180, "KPRIGHTPAREN", "Numpad )"),
+  other(183, "F13", "F13"),
+  other(184, "F14", "F14"),
+  other(185, "F15", "F15"),
+  other(186, "F16", "F16"),
+  other(187, "F17", "F17"),
+  other(188, "F18", "F18"),
+  other(189, "F19", "F19"),
+  other(190, "F20", "F20"),
+  other(191, "F21", "F21"),
+  other(192, "F22", "F22"),
+  other(193, "F23", "F23"),
+  other(194, "F24", "F24"),
+  other(210, "PRINT", "Print"),
+  other(217, "SEARCH", "Search"),
+  other(224, "BRIGHTNESSDOWN", "Brightness Down"),
+  other(225, "BRIGHTNESSUP", "Brightness Up"),
+  other(226, "MEDIA", "Media"),
+  other(248, "MICMUTE", "Mic Mute"),
+];
+
+pub fn by_code(code: u32) -> Option<&'static Key> {
+  KEYS.iter().find(|key| key.code == code)
+}
+
+pub fn by_evdev(evdev: u16) -> Option<&'static Key> {
+  KEYS.iter().find(|key| key.evdev == evdev)
+}
+
+/// Linux name of an evdev code, whether or not it has a Mechvibes keycode
+pub fn evdev_name(evdev: u16) -> Option<&'static str> {
+  match by_evdev(evdev) {
+    Some(key) => Some(key.evdev_name),
+    None => other_key(evdev).map(|key| key.evdev_name),
+  }
+}
+
+/// Label for an evdev code, falling back to the number for codes the app doesn't know
+pub fn evdev_label(evdev: u16) -> String {
+  match (by_evdev(evdev), other_key(evdev)) {
+    (Some(key), _) => key.label.to_string(),
+    (None, Some(key)) => key.label.to_string(),
+    (None, None) => format!("evdev {}", evdev),
+  }
+}
+
+fn other_key(evdev: u16) -> Option<&'static OtherKey> {
+  OTHER_KEYS.iter().find(|key| key.evdev == evdev)
+}
+
+/// Find the key a file or user-supplied name refers to. Case, spaces, dashes and
+/// underscores are ignored, so `Back_Space`, `page-up` and `KEY_ENTER` all match.
+pub fn by_name(name: &str) -> Option<&'static Key> {
+  let normalized: String = name
+    .chars()
+    .filter(|ch| ch.is_ascii_alphanumeric())
+    .collect::<String>()
+    .to_ascii_lowercase();
+  let normalized = normalized
+    .strip_prefix("key")
+    .filter(|rest| !rest.is_empty())
+    .unwrap_or(&normalized);
+  KEYS.iter().find(|key| {
+    key.aliases.contains(&normalized)
+      || key.evdev_name.replace('_', "").eq_ignore_ascii_case(normalized)
+  })
+}
+
+/// Key that types `ch` without modifiers, or its uppercase; `None` for shifted symbols
+pub fn by_char(ch: char) -> Option<&'static Key> {
+  match ch {
+    ' ' => by_name("space"),
+    '\n' => by_name("enter"),
+    '\t' => by_name("tab"),
+    _ => {
+      let ch = ch.to_ascii_uppercase().to_string();
+      KEYS.iter().find(|key| key.label == ch)
+    }
+  }
+}
+
+/// Label for a Mechvibes keycode, falling back to the number for keys not in `KEYS`
+pub fn label(code: u32) -> String {
+  by_code(code)
+    .map(|key| key.label.to_string())
+    .unwrap_or_else(|| format!("#{}", code))
+}
+
+pub fn is_numpad(key: &Key) -> bool {
+  key.evdev_name.starts_with("KP") || key.evdev_name == "NUMLOCK"
+}
+
+/// Keys a 60% board drops from a tenkeyless one
+const NOT_ON_SIXTY: &[&str] = &[
+  "F1", "F2", "F3", "F4", "F5", "F6", "F7", "F8", "F9", "F10", "F11", "F12", "SYSRQ",
+  "SCROLLLOCK", "PAUSE", "GRAVE", "INSERT", "HOME", "PAGEUP", "DELETE", "END", "PAGEDOWN", "UP",
+  "LEFT", "DOWN", "RIGHT",
+];
+
+/// Evdev codes of a layout's keys, in row order for the built-in layouts
+pub fn layout(layout: &KeyboardLayout) -> Vec<u16> {
+  let ansi = || KEYS.iter().filter(|key| key.evdev_name != "102ND");
+  match layout {
+    KeyboardLayout::Ansi104 => ansi().map(|key| key.evdev).collect(),
+    KeyboardLayout::Iso105 => KEYS.iter().map(|key| key.evdev).collect(),
+    KeyboardLayout::Tkl => ansi()
+      .filter(|key| !is_numpad(key))
+      .map(|key| key.evdev)
+      .collect(),
+    KeyboardLayout::Sixty => ansi()
+      .filter(|key| !is_numpad(key) && !NOT_ON_SIXTY.contains(&key.evdev_name))
+      .map(|key| key.evdev)
+      .collect(),
+    KeyboardLayout::Custom { evdev_codes } => {
+      let mut seen = BTreeSet::new();
+      evdev_codes
+        .iter()
+        .copied()
+        .filter(|code| seen.insert(*code))
+        .collect()
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn finds_keys_by_file_name() {
+    let code = |name: &str| by_name(name).map(|key| key.code);
+
+    assert_eq!(code("enter"), Some(28));
+    assert_eq!(code("Return"), Some(28));
+    assert_eq!(code("KEY_ENTER"), Some(28));
+    assert_eq!(code("Back_Space"), Some(14));
+    assert_eq!(code("page-up"), Some(3657));
+    assert_eq!(code("Numpad 5"), Some(76));
+    assert_eq!(code("kp_5"), Some(76));
+    assert_eq!(code("SPACEBAR"), Some(57));
+    assert_eq!(code("a"), Some(30));
+    assert_eq!(code("key"), None);
+    assert_eq!(code("click"), None);
+    assert_eq!(code(""), None);
+  }
+
+  #[test]
+  fn codes_and_names_are_unique() {
+    let codes: BTreeSet<u32> = KEYS.iter().map(|key| key.code).collect();
+    let evdevs: BTreeSet<u16> = KEYS.iter().map(|key| key.evdev).collect();
+    assert_eq!(codes.len(), KEYS.len());
+    assert_eq!(evdevs.len(), KEYS.len());
+
+    for key in KEYS {
+      assert_eq!(by_code(key.code).map(|found| found.evdev), Some(key.evdev));
+      assert_eq!(by_evdev(key.evdev).map(|found| found.code), Some(key.code));
+      assert_eq!(by_name(key.evdev_name).map(|found| found.code), Some(key.code));
+      for alias in key.aliases {
+        assert_eq!(by_name(alias).map(|found| found.code), Some(key.code), "{}", alias);
+      }
+    }
+  }
+
+  #[test]
+  fn finds_keys_by_the_character_they_type() {
+    assert_eq!(by_char('a').map(|key| key.code), Some(30));
+    assert_eq!(by_char('A').map(|key| key.code), Some(30));
+    assert_eq!(by_char(' ').map(|key| key.code), Some(57));
+    assert_eq!(by_char('\n').map(|key| key.code), Some(28));
+    assert!(by_char('é').is_none());
+  }
+}
@@ -0,0 +1,608 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde_json::{json, Map, Value};
+
+use crate::error::AppError;
+use crate::models::{ComposedPack, KeyDefineType, MappingStrategy, NewPack, SoundPack};
+use crate::services::audio_service;
+use crate::services::keymap::{self, Key};
+use crate::services::pack_manifest::{Define, PackManifest, MANIFEST_FILE};
+use crate::services::sound_pack_service::{self, StagedPack};
+
+/// Build a multi pack from loose audio files, staged for installing in `packs_dir` as
+/// with `sound_pack_service::import_pack`.
+pub fn create_pack(
+  packs_dir: &Path,
+  request: &NewPack,
+) -> Result<StagedPack<SoundPack>, AppError> {
+  let name = request.name.trim();
+  let pack_id = sound_pack_service::slugify(name);
+  if pack_id.is_empty() {
+    return Err(AppError::InvalidPack("Nome do pacote inválido".into()));
+  }
+  if packs_dir.join(&pack_id).exists() {
+    return Err(AppError::InvalidPack(format!("Pacote '{}' já existe", pack_id)));
+  }
+
+  let sources: Vec<PathBuf> = request.files.iter().map(PathBuf::from).collect();
+  if sources.is_empty() {
+    return Err(AppError::InvalidPack("Nenhum arquivo de áudio informado".into()));
+  }
+  if request.strategy == MappingStrategy::Single && sources.len() != 1 {
+    return Err(AppError::InvalidPack(
+      "Um único arquivo é necessário para usar o mesmo som em todas as teclas".into(),
+    ));
+  }
+  for source in &sources {
+    if !source.is_file() || !audio_service::is_audio_file(source) {
+      return Err(AppError::InvalidPack(format!(
+        "Arquivo de áudio inválido: {}",
+        source.display()
+      )));
+    }
+  }
+
+  log::info!("Creating pack '{}' from {} files", name, sources.len());
+  let staged = sound_pack_service::staging_dir(packs_dir)?;
+
+  let result = (|| {
+    let mut sounds = Vec::new();
+    for source in &sources {
+      let stem = source
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or_default()
+        .to_string();
+      sounds.push((stem, add_sound(&staged, source)?));
+    }
+
+    let mut manifest = PackManifest {
+      id: Some(pack_id.clone()),
+      name: Some(name.to_string()),
+      key_define_type: KeyDefineType::Multi,
+      includes_numpad: Some(true),
+      sound: sounds.first().map(|(_, file)| file.clone()),
+      defines: assign(request.strategy, &sounds),
+      defines_up: BTreeMap::new(),
+      extra: Map::new(),
+    };
+    manifest.set_text("version", Some("1.0.0".into()));
+    manifest.set_text("author", non_empty(&request.author));
+    manifest.set_text("description", non_empty(&request.description));
+    manifest.validate(&staged)?;
+    manifest.save(&staged)?;
+    sound_pack_service::read_staged(&staged, &pack_id)
+  })();
+
+  match result {
+    Ok(pack) => {
+      log::info!("Created pack '{}' as {}", pack.name, pack.id);
+      Ok(StagedPack::new(staged, packs_dir, &pack_id, pack))
+    }
+    Err(err) => {
+      log::warn!("Creating pack '{}' failed: {}", name, err);
+      let _ = fs::remove_dir_all(&staged);
+      Err(err)
+    }
+  }
+}
+
+/// Build a standalone multi pack from a base pack with some keys taken from other installed
+/// packs. Each source's sounds go to a folder named after it, single packs being split per
+/// key, and `composed_from` in `config.json` records where every key came from.
+pub fn compose_pack(
+  packs_dir: &Path,
+  request: &ComposedPack,
+) -> Result<StagedPack<SoundPack>, AppError> {
+  let name = request.name.trim();
+  let pack_id = sound_pack_service::slugify(name);
+  if pack_id.is_empty() {
+    return Err(AppError::InvalidPack("Nome do pacote inválido".into()));
+  }
+  if packs_dir.join(&pack_id).exists() {
+    return Err(AppError::InvalidPack(format!("Pacote '{}' já existe", pack_id)));
+  }
+  if request.overrides.is_empty() {
+    return Err(AppError::InvalidPack("Nenhuma tecla de outro pacote escolhida".into()));
+  }
+
+  log::info!(
+    "Composing pack '{}' from {} with {} overrides",
+    name,
+    request.base_pack_id,
+    request.overrides.len()
+  );
+  let staged = sound_pack_service::staging_dir(packs_dir)?;
+  let work_dir = sound_pack_service::staging_dir(packs_dir)?;
+
+  let result = (|| {
+    let mut sources = BTreeMap::new();
+    for source_id in std::iter::once(&request.base_pack_id)
+      .chain(request.overrides.iter().map(|entry| &entry.pack_id))
+    {
+      if !sources.contains_key(source_id) {
+        let source = Source::load(packs_dir, source_id, &work_dir)?;
+        sources.insert(source_id.clone(), source);
+      }
+    }
+
+    let base = &sources[&request.base_pack_id];
+    let mut origin: BTreeMap<u32, &str> = base
+      .manifest
+      .defines
+      .keys()
+      .map(|code| (*code, request.base_pack_id.as_str()))
+      .collect();
+    let mut defines = base.manifest.defines.clone();
+    let mut defines_up = base.manifest.defines_up.clone();
+
+    for entry in &request.overrides {
+      let source = &sources[&entry.pack_id];
+      for (code, define) in &source.manifest.defines {
+        if define.is_none() || !entry.keys.contains(*code) {
+          continue;
+        }
+        // The key-up sound goes with the press, or the key would mix two packs
+        defines.insert(*code, define.clone());
+        match source.manifest.defines_up.get(code) {
+          Some(up) => defines_up.insert(*code, up.clone()),
+          None => defines_up.remove(code),
+        };
+        origin.insert(*code, entry.pack_id.as_str());
+      }
+    }
+
+    // Copy the sounds each key now plays into the folder of the pack it came from
+    for (table, code, define) in defines
+      .iter_mut()
+      .map(|(code, define)| ("down", code, define))
+      .chain(defines_up.iter_mut().map(|(code, define)| ("up", code, define)))
+    {
+      let Some(define) = define else {
+        continue;
+      };
+      let source_id = origin.get(code).copied().unwrap_or(request.base_pack_id.as_str());
+      let source = sources.get(source_id).ok_or_else(|| {
+        AppError::InvalidPack(format!("Origem desconhecida da tecla {} ({})", code, table))
+      })?;
+      for sound in define.sounds_mut() {
+        if let Define::File(file) = sound {
+          *file = source.copy_into(source_id, file, &staged)?;
+        }
+      }
+    }
+    let sound = match &base.manifest.sound {
+      Some(file) => Some(base.copy_into(&request.base_pack_id, file, &staged)?),
+      None => None,
+    };
+
+    let mut manifest = PackManifest {
+      id: Some(pack_id.clone()),
+      name: Some(name.to_string()),
+      key_define_type: KeyDefineType::Multi,
+      includes_numpad: base.manifest.includes_numpad,
+      sound,
+      defines,
+      defines_up,
+      extra: Map::new(),
+    };
+    manifest.set_text("version", Some("1.0.0".into()));
+    manifest.set_text(
+      "description",
+      Some(format!(
+        "Composto de {}",
+        sources
+          .values()
+          .map(Source::label)
+          .collect::<Vec<_>>()
+          .join(", ")
+      )),
+    );
+    manifest
+      .extra
+      .insert("composed_from".into(), provenance(&request.base_pack_id, &sources, &origin));
+    manifest.validate(&staged)?;
+    manifest.save(&staged)?;
+    sound_pack_service::read_staged(&staged, &pack_id)
+  })();
+
+  let _ = fs::remove_dir_all(&work_dir);
+  match result {
+    Ok(pack) => {
+      log::info!("Composed pack '{}' as {}", pack.name, pack.id);
+      Ok(StagedPack::new(staged, packs_dir, &pack_id, pack))
+    }
+    Err(err) => {
+      log::warn!("Composing pack '{}' failed: {}", name, err);
+      let _ = fs::remove_dir_all(&staged);
+      Err(err)
+    }
+  }
+}
+
+/// An installed pack read as a multi pack, with the directory its files are in
+struct Source {
+  manifest: PackManifest,
+  files_dir: PathBuf,
+  version: String,
+}
+
+impl Source {
+  /// Single packs are split into per-key files under `work_dir`; multi packs are read
+  /// where they are installed
+  fn load(packs_dir: &Path, pack_id: &str, work_dir: &Path) -> Result<Self, AppError> {
+    let pack_dir = packs_dir.join(pack_id);
+    if !pack_dir.join(MANIFEST_FILE).exists() {
+      return Err(AppError::InvalidPack(format!("Pacote '{}' não encontrado", pack_id)));
+    }
+    let mut manifest = PackManifest::load(&pack_dir)?;
+    manifest.validate(&pack_dir)?;
+    let version = manifest.text("version").unwrap_or_else(|| "1.0.0".into());
+
+    let files_dir = match manifest.key_define_type {
+      KeyDefineType::Multi => pack_dir,
+      KeyDefineType::Single => {
+        let split_dir = work_dir.join(pack_id);
+        fs::create_dir_all(&split_dir)?;
+        sound_pack_service::split_sprite(&mut manifest, &pack_dir, &split_dir)?;
+        manifest.key_define_type = KeyDefineType::Multi;
+        split_dir
+      }
+    };
+    Ok(Self {
+      manifest,
+      files_dir,
+      version,
+    })
+  }
+
+  /// Copy one of the source's files into `<pack_id>/` of the new pack, once, and return
+  /// its name there
+  fn copy_into(&self, pack_id: &str, file: &str, target_dir: &Path) -> Result<String, AppError> {
+    let name = format!("{}/{}", pack_id, file);
+    let target = sound_pack_service::pack_file(target_dir, &name)?;
+    if !target.exists() {
+      if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+      }
+      fs::copy(sound_pack_service::pack_file(&self.files_dir, file)?, &target)?;
+    }
+    Ok(name)
+  }
+
+  fn label(&self) -> String {
+    format!(
+      "{} {}",
+      self.manifest.name.as_deref().unwrap_or("?"),
+      self.version
+    )
+  }
+}
+
+/// `composed_from` entry: the source packs with their versions, and the source of each key
+fn provenance(
+  base_pack_id: &str,
+  sources: &BTreeMap<String, Source>,
+  origin: &BTreeMap<u32, &str>,
+) -> Value {
+  let packs: Vec<Value> = sources
+    .iter()
+    .map(|(pack_id, source)| {
+      json!({
+        "id": pack_id,
+        "name": source.manifest.name,
+        "version": source.version,
+      })
+    })
+    .collect();
+  let keys: Map<String, Value> = origin
+    .iter()
+    .map(|(code, pack_id)| {
+      let version = sources.get(*pack_id).map(|source| source.version.as_str());
+      (code.to_string(), json!({ "pack": pack_id, "version": version }))
+    })
+    .collect();
+  json!({
+    "base": base_pack_id,
+    "packs": packs,
+    "keys": keys,
+  })
+}
+
+/// Copy an audio file into a pack, renaming it when the name is taken, and return its
+/// name inside the pack
+pub fn add_sound(pack_dir: &Path, source: &Path) -> Result<String, AppError> {
+  let stem = source
+    .file_stem()
+    .and_then(|stem| stem.to_str())
+    .map(sound_pack_service::slugify)
+    .filter(|stem| !stem.is_empty())
+    .unwrap_or_else(|| "sound".into());
+  let extension = source
+    .extension()
+    .and_then(|ext| ext.to_str())
+    .unwrap_or("wav")
+    .to_lowercase();
+
+  let mut file_name = format!("{}.{}", stem, extension);
+  let mut suffix = 2;
+  while pack_dir.join(&file_name).exists() {
+    file_name = format!("{}-{}.{}", stem, suffix, extension);
+    suffix += 1;
+  }
+
+  fs::copy(source, pack_dir.join(&file_name))?;
+  Ok(file_name)
+}
+
+/// Key-to-file table for the strategy; `sounds` pairs each file's original stem with its
+/// name inside the pack
+pub fn assign(
+  strategy: MappingStrategy,
+  sounds: &[(String, String)],
+) -> BTreeMap<u32, Option<Define>> {
+  let files: Vec<&String> = sounds.iter().map(|(_, file)| file).collect();
+  match strategy {
+    MappingStrategy::Single | MappingStrategy::RoundRobin => deal(keymap::KEYS.iter(), &files),
+    MappingStrategy::ByName => {
+      let mut defines = BTreeMap::new();
+      let mut unnamed = Vec::new();
+      for (stem, file) in sounds {
+        match keymap::by_name(stem) {
+          Some(key) => {
+            defines.insert(key.code, Some(Define::File(file.clone())));
+          }
+          None => unnamed.push(file),
+        }
+      }
+
+      // Keys without a file of their own share the unnamed files, or all of them
+      let pool = if unnamed.is_empty() { files } else { unnamed };
+      let rest = keymap::KEYS
+        .iter()
+        .filter(|key| !defines.contains_key(&key.code));
+      let dealt = deal(rest, &pool);
+      defines.extend(dealt);
+      defines
+    }
+  }
+}
+
+fn deal<'a>(
+  keys: impl Iterator<Item = &'a Key>,
+  files: &[&String],
+) -> BTreeMap<u32, Option<Define>> {
+  keys
+    .zip(files.iter().cycle())
+    .map(|(key, file)| (key.code, Some(Define::File((*file).clone()))))
+    .collect()
+}
+
+fn non_empty(value: &Option<String>) -> Option<String> {
+  value
+    .as_ref()
+    .map(|value| value.trim().to_string())
+    .filter(|value| !value.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::models::{KeyOverride, KeyRange};
+  use crate::services::audio_service::AudioBuffer;
+
+  const ESC: u32 = 1;
+  const F1: u32 = 59;
+  const F2: u32 = 60;
+  const ENTER: u32 = 28;
+  const KEY_A: u32 = 30;
+  const KEY_S: u32 = 31;
+  const SPACE: u32 = 57;
+
+  fn temp_dir(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("wayvibes-ui-{}-{}", test, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+  }
+
+  /// An installed multi pack whose files are short clicks
+  fn install(
+    packs_dir: &Path,
+    pack_id: &str,
+    version: &str,
+    defines: &[(u32, &str)],
+    defines_up: &[(u32, &str)],
+  ) {
+    let pack_dir = packs_dir.join(pack_id);
+    fs::create_dir_all(&pack_dir).unwrap();
+    let table = |entries: &[(u32, &str)]| -> BTreeMap<u32, Option<Define>> {
+      entries
+        .iter()
+        .map(|(code, file)| (*code, Some(Define::File(file.to_string()))))
+        .collect()
+    };
+    let mut manifest = PackManifest {
+      id: Some(pack_id.into()),
+      name: Some(pack_id.to_uppercase()),
+      key_define_type: KeyDefineType::Multi,
+      defines: table(defines),
+      defines_up: table(defines_up),
+      ..Default::default()
+    };
+    manifest.set_text("version", Some(version.into()));
+    let mut click = AudioBuffer::empty(8_000, 1);
+    click.samples = vec![0.5; 80];
+    for file in manifest.files() {
+      audio_service::write_wav(&pack_dir.join(file), &click).unwrap();
+    }
+    manifest.save(&pack_dir).unwrap();
+  }
+
+  fn sounds(stems: &[&str]) -> Vec<(String, String)> {
+    stems
+      .iter()
+      .map(|stem| (stem.to_string(), format!("{}.wav", stem)))
+      .collect()
+  }
+
+  fn file(defines: &BTreeMap<u32, Option<Define>>, code: u32) -> Option<&str> {
+    match defines.get(&code) {
+      Some(Some(Define::File(file))) => Some(file),
+      _ => None,
+    }
+  }
+
+  #[test]
+  fn single_file_plays_on_every_key() {
+    let defines = assign(MappingStrategy::Single, &sounds(&["click"]));
+
+    assert_eq!(defines.len(), keymap::KEYS.len());
+    assert!(keymap::KEYS
+      .iter()
+      .all(|key| file(&defines, key.code) == Some("click.wav")));
+  }
+
+  #[test]
+  fn round_robin_deals_files_in_keyboard_order() {
+    let defines = assign(MappingStrategy::RoundRobin, &sounds(&["one", "two"]));
+
+    assert_eq!(defines.len(), keymap::KEYS.len());
+    assert_eq!(file(&defines, ESC), Some("one.wav"));
+    assert_eq!(file(&defines, F1), Some("two.wav"));
+    assert_eq!(file(&defines, F2), Some("one.wav"));
+  }
+
+  #[test]
+  fn named_files_go_to_their_key_and_the_rest_are_shared() {
+    let defines = assign(MappingStrategy::ByName, &sounds(&["enter", "a", "click"]));
+
+    assert_eq!(defines.len(), keymap::KEYS.len());
+    assert_eq!(file(&defines, ENTER), Some("enter.wav"));
+    assert_eq!(file(&defines, KEY_A), Some("a.wav"));
+    assert!(keymap::KEYS
+      .iter()
+      .filter(|key| key.code != ENTER && key.code != KEY_A)
+      .all(|key| file(&defines, key.code) == Some("click.wav")));
+  }
+
+  #[test]
+  fn keys_left_over_share_all_files_when_every_file_is_named() {
+    let defines = assign(MappingStrategy::ByName, &sounds(&["enter", "space"]));
+
+    assert_eq!(defines.len(), keymap::KEYS.len());
+    assert_eq!(file(&defines, ENTER), Some("enter.wav"));
+    assert_eq!(file(&defines, ESC), Some("enter.wav"));
+    assert_eq!(file(&defines, F1), Some("space.wav"));
+  }
+
+  #[test]
+  fn creates_a_multi_pack_from_loose_files() {
+    let dir = temp_dir("create-pack");
+    let (sources, packs_dir) = (dir.join("sources"), dir.join("packs"));
+    fs::create_dir_all(&sources).unwrap();
+    fs::create_dir_all(&packs_dir).unwrap();
+    let mut click = AudioBuffer::empty(8_000, 1);
+    click.samples = vec![0.5; 80];
+    let files: Vec<String> = ["Enter.wav", "click.wav"]
+      .iter()
+      .map(|name| {
+        let path = sources.join(name);
+        audio_service::write_wav(&path, &click).unwrap();
+        path.to_string_lossy().to_string()
+      })
+      .collect();
+    let request = |strategy| NewPack {
+      name: "My Pack".into(),
+      author: Some("  ".into()),
+      description: None,
+      files: files.clone(),
+      strategy,
+    };
+
+    let staged = create_pack(&packs_dir, &request(MappingStrategy::ByName)).unwrap();
+    assert_eq!(staged.pack_id, "my-pack");
+    let manifest = PackManifest::load(&staged.dir).unwrap();
+    assert_eq!(manifest.key_define_type, KeyDefineType::Multi);
+    assert_eq!(manifest.sound.as_deref(), Some("enter.wav"));
+    assert_eq!(file(&manifest.defines, ENTER), Some("enter.wav"));
+    assert_eq!(file(&manifest.defines, KEY_A), Some("click.wav"));
+    assert_eq!(manifest.text("author"), None);
+    assert!(staged.dir.join("click.wav").is_file());
+
+    assert!(create_pack(&packs_dir, &request(MappingStrategy::Single)).is_err());
+    fs::create_dir_all(packs_dir.join("my-pack")).unwrap();
+    assert!(create_pack(&packs_dir, &request(MappingStrategy::ByName)).is_err());
+
+    fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn composed_pack_records_where_every_key_came_from() {
+    let packs_dir = temp_dir("compose-pack");
+    install(
+      &packs_dir,
+      "base",
+      "1.2.0",
+      &[(KEY_A, "soft.wav"), (KEY_S, "soft.wav")],
+      &[(KEY_A, "up.wav")],
+    );
+    install(&packs_dir, "other", "2.0.0", &[(KEY_A, "loud.wav"), (SPACE, "loud.wav")], &[]);
+    let request = ComposedPack {
+      name: "Mixed".into(),
+      base_pack_id: "base".into(),
+      overrides: vec![KeyOverride {
+        pack_id: "other".into(),
+        keys: KeyRange::Codes {
+          codes: vec![KEY_A, SPACE],
+        },
+      }],
+    };
+
+    let staged = compose_pack(&packs_dir, &request).unwrap();
+    let manifest = PackManifest::load(&staged.dir).unwrap();
+    assert_eq!(file(&manifest.defines, KEY_A), Some("other/loud.wav"));
+    assert_eq!(file(&manifest.defines, SPACE), Some("other/loud.wav"));
+    assert_eq!(file(&manifest.defines, KEY_S), Some("base/soft.wav"));
+    // The base key-up sound would mix two packs on the overridden key
+    assert!(manifest.defines_up.is_empty());
+    assert!(staged.dir.join("other").join("loud.wav").is_file());
+    assert!(!staged.dir.join("base").join("up.wav").exists());
+
+    let provenance = &manifest.extra["composed_from"];
+    assert_eq!(provenance["base"], "base");
+    assert_eq!(provenance["packs"].as_array().map(Vec::len), Some(2));
+    assert_eq!(provenance["packs"][0]["version"], "1.2.0");
+    assert_eq!(provenance["keys"]["30"], json!({ "pack": "other", "version": "2.0.0" }));
+    assert_eq!(provenance["keys"]["57"], json!({ "pack": "other", "version": "2.0.0" }));
+    assert_eq!(provenance["keys"]["31"], json!({ "pack": "base", "version": "1.2.0" }));
+
+    fs::remove_dir_all(&packs_dir).unwrap();
+  }
+
+  #[test]
+  fn composing_needs_an_override_and_installed_sources() {
+    let packs_dir = temp_dir("compose-pack-invalid");
+    install(&packs_dir, "base", "1.0.0", &[(KEY_A, "soft.wav")], &[]);
+    let request = |overrides: Vec<KeyOverride>| ComposedPack {
+      name: "Mixed".into(),
+      base_pack_id: "base".into(),
+      overrides,
+    };
+    let from = |pack_id: &str| KeyOverride {
+      pack_id: pack_id.into(),
+      keys: KeyRange::Range {
+        first: KEY_A,
+        last: KEY_S,
+      },
+    };
+
+    assert!(compose_pack(&packs_dir, &request(Vec::new())).is_err());
+    assert!(compose_pack(&packs_dir, &request(vec![from("missing")])).is_err());
+    assert!(compose_pack(&packs_dir, &request(vec![from("base")])).is_ok());
+
+    fs::remove_dir_all(&packs_dir).unwrap();
+  }
+}
@@ -0,0 +1,315 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::AppError;
+use crate::models::{
+  Coverage, KeyChange, KeyCoverage, KeyMapping, KeySound, KeyboardLayout, PackCoverage,
+  PackMapping,
+};
+use crate::services::pack_manifest::{Define, PackManifest};
+use crate::services::{audio_service, keymap, pack_builder_service};
+
+/// Every known key plus any extra code the pack defines, with the sound each one plays
+pub fn mapping(packs_dir: &Path, pack_id: &str) -> Result<PackMapping, AppError> {
+  let pack_dir = pack_dir(packs_dir, pack_id)?;
+  let manifest = PackManifest::load(&pack_dir)?;
+
+  let mut keys: Vec<KeyMapping> = keymap::KEYS
+    .iter()
+    .map(|key| {
+      let (coverage, sound) = covered(&manifest, manifest.defines.get(&key.code));
+      KeyMapping {
+        code: key.code,
+        label: key.label.to_string(),
+        evdev_name: Some(key.evdev_name.to_string()),
+        coverage,
+        sound,
+      }
+    })
+    .collect();
+  for (code, define) in &manifest.defines {
+    if keymap::by_code(*code).is_none() {
+      let (coverage, sound) = covered(&manifest, Some(define));
+      keys.push(KeyMapping {
+        code: *code,
+        label: keymap::label(*code),
+        evdev_name: None,
+        coverage,
+        sound,
+      });
+    }
+  }
+
+  Ok(PackMapping {
+    pack_id: pack_id.to_string(),
+    key_define_type: manifest.key_define_type,
+    sound: manifest.sound,
+    files: audio_files_in(&pack_dir)?,
+    keys,
+  })
+}
+
+/// How a pack covers each key of a layout. Keys without a define fall back to the pack's
+/// `sound` when it has one and are silent otherwise; keys without a Mechvibes keycode are
+/// unmapped whatever the pack holds.
+pub fn coverage(
+  packs_dir: &Path,
+  pack_id: &str,
+  layout: KeyboardLayout,
+) -> Result<PackCoverage, AppError> {
+  let pack_dir = pack_dir(packs_dir, pack_id)?;
+  let manifest = PackManifest::load(&pack_dir)?;
+
+  let keys: Vec<KeyCoverage> = keymap::layout(&layout)
+    .into_iter()
+    .map(|evdev| {
+      let key = keymap::by_evdev(evdev);
+      let (coverage, sound) = match key {
+        Some(key) => covered(&manifest, manifest.defines.get(&key.code)),
+        None => (Coverage::Unmapped, None),
+      };
+      KeyCoverage {
+        evdev,
+        evdev_name: keymap::evdev_name(evdev).map(str::to_string),
+        label: keymap::evdev_label(evdev),
+        code: key.map(|key| key.code),
+        coverage,
+        sound,
+      }
+    })
+    .collect();
+
+  let count = |coverage: Coverage| keys.iter().filter(|key| key.coverage == coverage).count();
+  Ok(PackCoverage {
+    pack_id: pack_id.to_string(),
+    mapped: count(Coverage::Mapped),
+    fallback: count(Coverage::Fallback),
+    silent: count(Coverage::Silent),
+    unmapped: count(Coverage::Unmapped),
+    layout,
+    keys,
+  })
+}
+
+/// How a key is covered given its entry in `defines`, with the sound it plays
+fn covered(
+  manifest: &PackManifest,
+  define: Option<&Option<Define>>,
+) -> (Coverage, Option<KeySound>) {
+  match define {
+    Some(Some(define)) => (Coverage::Mapped, Some(KeySound::from(define))),
+    Some(None) => (Coverage::Silent, None),
+    None => match manifest.fallback() {
+      Some(file) => (Coverage::Fallback, Some(KeySound::File { file: file.to_string() })),
+      None => (Coverage::Silent, None),
+    },
+  }
+}
+
+/// Apply all changes to `config.json` at once. Nothing is written unless the result validates.
+pub fn apply_changes(
+  packs_dir: &Path,
+  pack_id: &str,
+  changes: Vec<KeyChange>,
+) -> Result<PackMapping, AppError> {
+  let pack_dir = pack_dir(packs_dir, pack_id)?;
+  let mut manifest = PackManifest::load(&pack_dir)?;
+
+  // A cleared key is written as `null`; leaving it out would play the fallback instead
+  for change in changes {
+    manifest
+      .defines
+      .insert(change.code, change.sound.map(Define::from));
+  }
+
+  manifest.validate(&pack_dir)?;
+  manifest.save(&pack_dir)?;
+  log::info!("Saved key mapping of pack {}", pack_id);
+  mapping(packs_dir, pack_id)
+}
+
+/// Copy audio files into a pack without mapping them, returning their names inside it
+pub fn add_sounds(
+  packs_dir: &Path,
+  pack_id: &str,
+  files: &[String],
+) -> Result<Vec<String>, AppError> {
+  let pack_dir = pack_dir(packs_dir, pack_id)?;
+  let mut added = Vec::new();
+  for file in files {
+    let source = PathBuf::from(file);
+    if !source.is_file() || !audio_service::is_audio_file(&source) {
+      return Err(AppError::InvalidPack(format!("Arquivo de áudio inválido: {}", file)));
+    }
+    added.push(pack_builder_service::add_sound(&pack_dir, &source)?);
+  }
+  log::info!("Added {} sounds to pack {}", added.len(), pack_id);
+  Ok(added)
+}
+
+fn pack_dir(packs_dir: &Path, pack_id: &str) -> Result<PathBuf, AppError> {
+  let pack_dir = packs_dir.join(pack_id);
+  if !pack_dir.join("config.json").exists() {
+    return Err(AppError::InvalidPack("Pacote não encontrado".into()));
+  }
+  Ok(pack_dir)
+}
+
+fn audio_files_in(pack_dir: &Path) -> Result<Vec<String>, AppError> {
+  let mut files = Vec::new();
+  for entry in fs::read_dir(pack_dir)? {
+    let path = entry?.path();
+    if path.is_file() && audio_service::is_audio_file(&path) {
+      if let Some(name) = path.file_name().and_then(|name| name.to_str()) {
+        files.push(name.to_string());
+      }
+    }
+  }
+  files.sort();
+  Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::models::KeyDefineType;
+  use crate::services::audio_service::AudioBuffer;
+  use crate::services::pack_manifest::MANIFEST_FILE;
+
+  const ENTER: u32 = 28;
+  const KEY_A: u32 = 30;
+  const SPACE: u32 = 57;
+  const NUMPAD_5: u32 = 76;
+
+  /// A multi pack with two unmapped files, the first also being its fallback `sound`
+  fn pack(test: &str) -> PathBuf {
+    let packs_dir =
+      std::env::temp_dir().join(format!("wayvibes-ui-{}-{}", test, std::process::id()));
+    let pack_dir = packs_dir.join("edit");
+    let _ = fs::remove_dir_all(&packs_dir);
+    fs::create_dir_all(&pack_dir).unwrap();
+    let mut click = AudioBuffer::empty(8_000, 1);
+    click.samples = vec![0.5; 80];
+    audio_service::write_wav(&pack_dir.join("soft.wav"), &click).unwrap();
+    audio_service::write_wav(&pack_dir.join("heavy.wav"), &click).unwrap();
+
+    PackManifest {
+      id: Some("edit".into()),
+      key_define_type: KeyDefineType::Multi,
+      sound: Some("soft.wav".into()),
+      ..Default::default()
+    }
+    .save(&pack_dir)
+    .unwrap();
+    packs_dir
+  }
+
+  fn set(code: u32, file: Option<&str>) -> KeyChange {
+    KeyChange {
+      code,
+      sound: file.map(|file| KeySound::File { file: file.into() }),
+    }
+  }
+
+  fn key(mapping: &PackMapping, code: u32) -> &KeyMapping {
+    mapping.keys.iter().find(|key| key.code == code).unwrap()
+  }
+
+  #[test]
+  fn assigns_then_replaces_a_key_sound() {
+    let packs_dir = pack("editor-replace");
+    let mapping = apply_changes(&packs_dir, "edit", vec![set(ENTER, Some("soft.wav"))]).unwrap();
+    assert_eq!(key(&mapping, ENTER).coverage, Coverage::Mapped);
+
+    let mapping = apply_changes(&packs_dir, "edit", vec![set(ENTER, Some("heavy.wav"))]).unwrap();
+    let _ = fs::remove_dir_all(&packs_dir);
+    assert_eq!(
+      key(&mapping, ENTER).sound,
+      Some(KeySound::File {
+        file: "heavy.wav".into()
+      })
+    );
+  }
+
+  #[test]
+  fn cleared_key_is_saved_as_null_and_goes_silent() {
+    let packs_dir = pack("editor-clear");
+    apply_changes(&packs_dir, "edit", vec![set(ENTER, Some("heavy.wav"))]).unwrap();
+    let mapping = apply_changes(&packs_dir, "edit", vec![set(ENTER, None)]).unwrap();
+    let manifest = PackManifest::load(&packs_dir.join("edit")).unwrap();
+    let _ = fs::remove_dir_all(&packs_dir);
+
+    assert_eq!(manifest.defines.get(&ENTER), Some(&None));
+    assert_eq!(key(&mapping, ENTER).coverage, Coverage::Silent);
+    assert_eq!(key(&mapping, ENTER).sound, None);
+    // Keys never touched still play the fallback
+    assert_eq!(key(&mapping, KEY_A).coverage, Coverage::Fallback);
+  }
+
+  #[test]
+  fn nothing_is_written_when_a_change_is_invalid() {
+    let packs_dir = pack("editor-invalid");
+    let manifest_path = packs_dir.join("edit").join(MANIFEST_FILE);
+    let before = fs::read(&manifest_path).unwrap();
+
+    let changes = vec![set(ENTER, Some("heavy.wav")), set(KEY_A, Some("missing.wav"))];
+    let result = apply_changes(&packs_dir, "edit", changes);
+    let after = fs::read(&manifest_path).unwrap();
+    let _ = fs::remove_dir_all(&packs_dir);
+
+    assert!(result.is_err());
+    assert_eq!(before, after);
+  }
+
+  /// Enter and numpad 5 mapped, Space silenced, every other key falling back to `soft.wav`
+  fn covered_pack(test: &str) -> PathBuf {
+    let packs_dir = pack(test);
+    let changes = vec![
+      set(ENTER, Some("heavy.wav")),
+      set(NUMPAD_5, Some("heavy.wav")),
+      set(SPACE, None),
+    ];
+    apply_changes(&packs_dir, "edit", changes).unwrap();
+    packs_dir
+  }
+
+  #[test]
+  fn counts_coverage_of_every_built_in_layout() {
+    let packs_dir = covered_pack("editor-layouts");
+    let counts = |layout: KeyboardLayout| {
+      let report = coverage(&packs_dir, "edit", layout).unwrap();
+      let total = report.keys.len();
+      (total, report.mapped, report.fallback, report.silent, report.unmapped)
+    };
+    let ansi = counts(KeyboardLayout::Ansi104);
+    let iso = counts(KeyboardLayout::Iso105);
+    let tkl = counts(KeyboardLayout::Tkl);
+    let sixty = counts(KeyboardLayout::Sixty);
+    let _ = fs::remove_dir_all(&packs_dir);
+
+    assert_eq!(ansi, (104, 2, 101, 1, 0));
+    assert_eq!(iso, (105, 2, 102, 1, 0));
+    // Without a numpad only Enter is left mapped
+    assert_eq!(tkl, (87, 1, 85, 1, 0));
+    assert_eq!(sixty, (61, 1, 59, 1, 0));
+  }
+
+  #[test]
+  fn custom_keys_without_a_keycode_are_unmapped_but_named() {
+    let packs_dir = covered_pack("editor-custom");
+    let layout = KeyboardLayout::Custom {
+      evdev_codes: vec![28, 183, 57, 700],
+    };
+    let report = coverage(&packs_dir, "edit", layout).unwrap();
+    let _ = fs::remove_dir_all(&packs_dir);
+
+    let counts = (report.mapped, report.fallback, report.silent, report.unmapped);
+    assert_eq!(counts, (1, 0, 1, 2));
+    let f13 = &report.keys[1];
+    assert_eq!(f13.evdev_name.as_deref(), Some("F13"));
+    assert_eq!((f13.code, f13.coverage), (None, Coverage::Unmapped));
+    let unknown = &report.keys[3];
+    assert_eq!(unknown.evdev_name, None);
+    assert_eq!(unknown.label, "evdev 700");
+  }
+}
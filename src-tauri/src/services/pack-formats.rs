@@ -0,0 +1,328 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde_json::{Map, Value};
+
+use crate::error::AppError;
+use crate::models::{KeyDefineType, MappingStrategy};
+use crate::services::pack_manifest::{Define, PackManifest};
+use crate::services::{audio_service, keymap, pack_builder_service, sound_pack_service};
+
+/// A pack layout without a Mechvibes `config.json` that import can generate one for
+pub trait PackFormat: Sync {
+  /// Short name for logs and the generated manifest, e.g. `bucklespring`
+  fn name(&self) -> &'static str;
+  fn detect(&self, dir: &Path) -> Result<bool, AppError>;
+  /// Manifest for the pack in `dir`; `name` is used when the format has none of its own
+  fn manifest(&self, dir: &Path, name: &str) -> Result<PackManifest, AppError>;
+}
+
+/// Tried in order, so stricter formats come before the plain folder
+pub static FORMATS: &[&dyn PackFormat] = &[&Bucklespring, &WavFolder];
+
+/// Files that carry a pack's credits or license and travel with it
+const ATTRIBUTION_PREFIXES: &[&str] =
+  &["license", "licence", "copying", "readme", "authors", "credits"];
+
+/// The first format of `FORMATS` found in `root` or any directory below it, with the
+/// directory that holds the pack
+pub fn find(root: &Path) -> Result<Option<(&'static dyn PackFormat, PathBuf)>, AppError> {
+  for format in FORMATS {
+    if let Some(dir) = find_dir(root, *format)? {
+      return Ok(Some((*format, dir)));
+    }
+  }
+  Ok(None)
+}
+
+fn find_dir(dir: &Path, format: &dyn PackFormat) -> Result<Option<PathBuf>, AppError> {
+  if format.detect(dir)? {
+    return Ok(Some(dir.to_path_buf()));
+  }
+  for entry in fs::read_dir(dir)? {
+    let path = entry?.path();
+    if path.is_dir() {
+      if let Some(found) = find_dir(&path, format)? {
+        return Ok(Some(found));
+      }
+    }
+  }
+  Ok(None)
+}
+
+/// Copy license and readme files from the directories between `root` and `pack_dir` into
+/// `pack_dir`, so flattening the pack doesn't drop its credits
+pub fn keep_attribution(root: &Path, pack_dir: &Path) -> Result<(), AppError> {
+  let mut dir = pack_dir.parent();
+  while let Some(current) = dir.filter(|dir| dir.starts_with(root)) {
+    for name in attribution_files(current)? {
+      let target = pack_dir.join(&name);
+      if !target.exists() {
+        fs::copy(current.join(&name), &target)?;
+      }
+    }
+    dir = current.parent();
+  }
+  Ok(())
+}
+
+fn attribution_files(dir: &Path) -> Result<Vec<String>, AppError> {
+  let mut files = Vec::new();
+  for entry in fs::read_dir(dir)? {
+    let entry = entry?;
+    let name = entry.file_name().to_string_lossy().to_string();
+    let lower = name.to_lowercase();
+    if entry.file_type()?.is_file()
+      && ATTRIBUTION_PREFIXES
+        .iter()
+        .any(|prefix| lower.starts_with(prefix))
+    {
+      files.push(name);
+    }
+  }
+  files.sort();
+  Ok(files)
+}
+
+/// Manifest shared by the adapters: a multi pack tagged with the format it came from and
+/// pointing at its credits
+fn generated_manifest(
+  format: &str,
+  dir: &Path,
+  name: &str,
+  defines: BTreeMap<u32, Option<Define>>,
+  defines_up: BTreeMap<u32, Option<Define>>,
+) -> Result<PackManifest, AppError> {
+  let mut manifest = PackManifest {
+    id: Some(sound_pack_service::slugify(name)),
+    name: Some(name.to_string()),
+    key_define_type: KeyDefineType::Multi,
+    includes_numpad: Some(true),
+    sound: None,
+    defines,
+    defines_up,
+    extra: Map::new(),
+  };
+  manifest.set_text("version", Some("1.0.0".into()));
+  let credits = attribution_files(dir)?;
+  let description = if credits.is_empty() {
+    format!("Convertido de um pacote {}", format)
+  } else {
+    format!("Convertido de um pacote {}; créditos em {}", format, credits.join(", "))
+  };
+  manifest.set_text("description", Some(description));
+  manifest
+    .extra
+    .insert("source_format".into(), Value::String(format.to_string()));
+  Ok(manifest)
+}
+
+fn audio_file_names(dir: &Path) -> Result<Vec<String>, AppError> {
+  let mut files = Vec::new();
+  for entry in fs::read_dir(dir)? {
+    let path = entry?.path();
+    if path.is_file() && audio_service::is_audio_file(&path) {
+      if let Some(name) = path.file_name().and_then(|name| name.to_str()) {
+        files.push(name.to_string());
+      }
+    }
+  }
+  files.sort();
+  Ok(files)
+}
+
+/// bucklespring: `XX-1.wav` is the press and `XX-0.wav` the release of the key with
+/// Linux keycode `0xXX`; releases become the key-up table
+pub struct Bucklespring;
+
+impl Bucklespring {
+  /// Keycode and whether the file is the press sound
+  fn parse(file: &str) -> Option<(u16, bool)> {
+    let stem = file.to_lowercase().strip_suffix(".wav")?.to_string();
+    let (code, state) = stem.split_once('-')?;
+    if code.len() != 2 {
+      return None;
+    }
+    let code = u16::from_str_radix(code, 16).ok()?;
+    match state {
+      "1" => Some((code, true)),
+      "0" => Some((code, false)),
+      _ => None,
+    }
+  }
+}
+
+impl PackFormat for Bucklespring {
+  fn name(&self) -> &'static str {
+    "bucklespring"
+  }
+
+  fn detect(&self, dir: &Path) -> Result<bool, AppError> {
+    let presses = audio_file_names(dir)?
+      .iter()
+      .filter(|file| matches!(Self::parse(file), Some((_, true))))
+      .count();
+    Ok(presses >= 2)
+  }
+
+  fn manifest(&self, dir: &Path, name: &str) -> Result<PackManifest, AppError> {
+    let (mut defines, mut defines_up) = (BTreeMap::new(), BTreeMap::new());
+    for file in audio_file_names(dir)? {
+      let Some((evdev, press)) = Self::parse(&file) else {
+        continue;
+      };
+      let Some(key) = keymap::by_evdev(evdev) else {
+        log::debug!("Skipping {}: no Mechvibes code for evdev {}", file, evdev);
+        continue;
+      };
+      let table = if press { &mut defines } else { &mut defines_up };
+      table.insert(key.code, Some(Define::File(file)));
+    }
+    generated_manifest(self.name(), dir, name, defines, defines_up)
+  }
+}
+
+/// Loose audio files; files named after a key play on it and the rest are shared by
+/// the other keys
+pub struct WavFolder;
+
+impl PackFormat for WavFolder {
+  fn name(&self) -> &'static str {
+    "wav"
+  }
+
+  fn detect(&self, dir: &Path) -> Result<bool, AppError> {
+    Ok(!audio_file_names(dir)?.is_empty())
+  }
+
+  fn manifest(&self, dir: &Path, name: &str) -> Result<PackManifest, AppError> {
+    let sounds: Vec<(String, String)> = audio_file_names(dir)?
+      .into_iter()
+      .map(|file| {
+        let stem = Path::new(&file)
+          .file_stem()
+          .and_then(|stem| stem.to_str())
+          .unwrap_or_default()
+          .to_string();
+        (stem, file)
+      })
+      .collect();
+    let defines = pack_builder_service::assign(MappingStrategy::ByName, &sounds);
+    generated_manifest(self.name(), dir, name, defines, BTreeMap::new())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const KEY_A: u32 = 30;
+  const KEY_S: u32 = 31;
+  const ENTER: u32 = 28;
+
+  /// A directory holding empty files with the given names; detection and manifests
+  /// only look at names
+  fn pack_dir(test: &str, files: &[&str]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("wayvibes-ui-{}-{}", test, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    for file in files {
+      fs::write(dir.join(file), b"").unwrap();
+    }
+    dir
+  }
+
+  fn file(table: &BTreeMap<u32, Option<Define>>, code: u32) -> Option<&str> {
+    match table.get(&code) {
+      Some(Some(Define::File(file))) => Some(file),
+      _ => None,
+    }
+  }
+
+  #[test]
+  fn parses_bucklespring_file_names() {
+    assert_eq!(Bucklespring::parse("1e-1.wav"), Some((0x1e, true)));
+    assert_eq!(Bucklespring::parse("1E-0.WAV"), Some((0x1e, false)));
+    assert_eq!(Bucklespring::parse("1c-1.wav"), Some((28, true)));
+    assert_eq!(Bucklespring::parse("1e-2.wav"), None);
+    assert_eq!(Bucklespring::parse("1e1-1.wav"), None);
+    assert_eq!(Bucklespring::parse("zz-1.wav"), None);
+    assert_eq!(Bucklespring::parse("1e-1.ogg"), None);
+  }
+
+  #[test]
+  fn bucklespring_releases_become_the_key_up_table() {
+    let dir = pack_dir(
+      "bucklespring",
+      &["1e-1.wav", "1e-0.wav", "1f-1.wav", "1c-1.wav", "ff-1.wav", "readme.md"],
+    );
+
+    assert!(Bucklespring.detect(&dir).unwrap());
+    let manifest = Bucklespring.manifest(&dir, "Buckling Spring").unwrap();
+    assert_eq!(file(&manifest.defines, KEY_A), Some("1e-1.wav"));
+    assert_eq!(file(&manifest.defines, KEY_S), Some("1f-1.wav"));
+    assert_eq!(file(&manifest.defines, ENTER), Some("1c-1.wav"));
+    assert_eq!(manifest.defines.len(), 3);
+    assert_eq!(file(&manifest.defines_up, KEY_A), Some("1e-0.wav"));
+    assert_eq!(manifest.defines_up.len(), 1);
+    assert_eq!(manifest.id.as_deref(), Some("buckling-spring"));
+    assert_eq!(
+      manifest.extra.get("source_format"),
+      Some(&Value::String("bucklespring".into()))
+    );
+    assert!(manifest.text("description").unwrap().contains("readme.md"));
+
+    fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn plain_folders_are_not_bucklespring() {
+    let dir = pack_dir("bucklespring-plain", &["1e-1.wav", "click.wav", "enter.wav"]);
+
+    assert!(!Bucklespring.detect(&dir).unwrap());
+    assert!(WavFolder.detect(&dir).unwrap());
+    let (format, found) = find(&dir).unwrap().unwrap();
+    assert_eq!(format.name(), "wav");
+    assert_eq!(found, dir);
+
+    fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn wav_folder_files_named_after_keys_play_on_them() {
+    let dir = pack_dir("wav-folder", &["enter.wav", "click.wav", "clack.ogg", "notes.txt"]);
+
+    let manifest = WavFolder.manifest(&dir, "Loose").unwrap();
+    assert_eq!(file(&manifest.defines, ENTER), Some("enter.wav"));
+    let shared: Vec<&str> = manifest
+      .defines
+      .keys()
+      .filter(|code| **code != ENTER)
+      .filter_map(|code| file(&manifest.defines, *code))
+      .collect();
+    assert_eq!(shared.len(), keymap::KEYS.len() - 1);
+    assert!(shared.iter().all(|file| *file == "clack.ogg" || *file == "click.wav"));
+    assert!(manifest.defines_up.is_empty());
+
+    fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn finds_the_pack_below_the_archive_root() {
+    let root = pack_dir("formats-nested", &["LICENSE"]);
+    let inner = root.join("pack").join("sounds");
+    fs::create_dir_all(&inner).unwrap();
+    for file in ["1e-1.wav", "1f-1.wav"] {
+      fs::write(inner.join(file), b"").unwrap();
+    }
+
+    let (format, found) = find(&root).unwrap().unwrap();
+    assert_eq!(format.name(), "bucklespring");
+    assert_eq!(found, inner);
+    keep_attribution(&root, &inner).unwrap();
+    assert!(inner.join("LICENSE").exists());
+
+    fs::remove_dir_all(&root).unwrap();
+  }
+}
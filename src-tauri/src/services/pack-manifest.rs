@@ -0,0 +1,373 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::error::AppError;
+use crate::models::{KeyDefineType, KeySound};
+use crate::services::{audio_service, sound_pack_service};
+
+pub const MANIFEST_FILE: &str = "config.json";
+
+/// Mechvibes `config.json`, including the Mechvibes 2 `config_version` and the
+/// Mechvibes++ key-up table and per-key variations. Fields the app doesn't use are kept
+/// in `extra` and written back unchanged.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PackManifest {
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub id: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub name: Option<String>,
+  #[serde(default)]
+  pub key_define_type: KeyDefineType,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub includes_numpad: Option<bool>,
+  /// Sprite file of a single pack; some multi packs set it too
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub sound: Option<String>,
+  /// Keycode to sound; `null` leaves the key silent
+  #[serde(default)]
+  pub defines: BTreeMap<u32, Option<Define>>,
+  /// Mechvibes++ sounds played on key release, shaped like `defines`
+  #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+  pub defines_up: BTreeMap<u32, Option<Define>>,
+  #[serde(flatten)]
+  pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Define {
+  File(String),
+  /// `[offset_ms, duration_ms]` into the sprite
+  Slice([f64; 2]),
+  /// Mechvibes++: one of these files or slices, picked at random on every press
+  Variants(Vec<Define>),
+}
+
+impl Define {
+  /// The files and slices the key can play
+  pub fn sounds(&self) -> Vec<&Define> {
+    match self {
+      Define::Variants(variants) => variants.iter().flat_map(Define::sounds).collect(),
+      sound => vec![sound],
+    }
+  }
+
+  pub fn sounds_mut(&mut self) -> Vec<&mut Define> {
+    match self {
+      Define::Variants(variants) => variants.iter_mut().flat_map(Define::sounds_mut).collect(),
+      sound => vec![sound],
+    }
+  }
+}
+
+impl From<&Define> for KeySound {
+  fn from(define: &Define) -> Self {
+    match define {
+      Define::File(file) => KeySound::File { file: file.clone() },
+      Define::Slice([offset_ms, duration_ms]) => KeySound::Slice {
+        offset_ms: *offset_ms,
+        duration_ms: *duration_ms,
+      },
+      Define::Variants(variants) => KeySound::Variants {
+        sounds: variants.iter().map(KeySound::from).collect(),
+      },
+    }
+  }
+}
+
+impl From<KeySound> for Define {
+  fn from(sound: KeySound) -> Self {
+    match sound {
+      KeySound::File { file } => Define::File(file),
+      KeySound::Slice {
+        offset_ms,
+        duration_ms,
+      } => Define::Slice([offset_ms, duration_ms]),
+      KeySound::Variants { sounds } => {
+        Define::Variants(sounds.into_iter().map(Define::from).collect())
+      }
+    }
+  }
+}
+
+impl PackManifest {
+  pub fn load(pack_dir: &Path) -> Result<Self, AppError> {
+    let content = fs::read_to_string(pack_dir.join(MANIFEST_FILE))?;
+    Ok(serde_json::from_str(&content)?)
+  }
+
+  /// Write `config.json` through a temporary file so a crash never leaves it half written
+  pub fn save(&self, pack_dir: &Path) -> Result<(), AppError> {
+    let payload = serde_json::to_string_pretty(self)?;
+    let temp_path = pack_dir.join(format!("{}.tmp", MANIFEST_FILE));
+    fs::write(&temp_path, payload)?;
+    fs::rename(&temp_path, pack_dir.join(MANIFEST_FILE))?;
+    Ok(())
+  }
+
+  /// Mechvibes `config_version`, written as `"2"` or `2` depending on the pack
+  pub fn config_version(&self) -> Option<String> {
+    match self.extra.get("config_version")? {
+      Value::String(version) => Some(version.clone()),
+      Value::Number(version) => Some(version.to_string()),
+      _ => None,
+    }
+  }
+
+  /// Every file and slice of both tables, variants included
+  pub fn sounds(&self) -> impl Iterator<Item = &Define> {
+    self
+      .defines
+      .values()
+      .chain(self.defines_up.values())
+      .flatten()
+      .flat_map(Define::sounds)
+  }
+
+  pub fn sounds_mut(&mut self) -> impl Iterator<Item = &mut Define> {
+    self
+      .defines
+      .values_mut()
+      .chain(self.defines_up.values_mut())
+      .flatten()
+      .flat_map(Define::sounds_mut)
+  }
+
+  /// File a key missing from `defines` plays: the `sound` of a multi pack. A single pack's
+  /// `sound` is its sprite, so wayvibes leaves such keys silent.
+  pub fn fallback(&self) -> Option<&str> {
+    match self.key_define_type {
+      KeyDefineType::Multi => self.sound.as_deref(),
+      KeyDefineType::Single => None,
+    }
+  }
+
+  /// Keys with key-up sounds
+  pub fn keyup_keys(&self) -> usize {
+    self.defines_up.values().flatten().count()
+  }
+
+  /// Keys that pick from several sounds, and the most sounds any of them has
+  pub fn variant_counts(&self) -> (usize, usize) {
+    self
+      .defines
+      .values()
+      .chain(self.defines_up.values())
+      .flatten()
+      .filter_map(|define| match define {
+        Define::Variants(variants) if variants.len() > 1 => Some(variants.len()),
+        _ => None,
+      })
+      .fold((0, 0), |(keys, most), count| (keys + 1, most.max(count)))
+  }
+
+  /// String metadata such as `version` or `author` from the fields kept in `extra`
+  pub fn text(&self, field: &str) -> Option<String> {
+    self
+      .extra
+      .get(field)
+      .and_then(|value| value.as_str())
+      .map(|value| value.to_string())
+  }
+
+  pub fn set_text(&mut self, field: &str, value: Option<String>) {
+    match value {
+      Some(value) => {
+        self.extra.insert(field.to_string(), Value::String(value));
+      }
+      None => {
+        self.extra.remove(field);
+      }
+    }
+  }
+
+  /// Check that every define fits the pack type and names an audio file present in `pack_dir`
+  pub fn validate(&self, pack_dir: &Path) -> Result<(), AppError> {
+    let check_file = |file: &str| -> Result<(), AppError> {
+      let path = sound_pack_service::pack_file(pack_dir, file)?;
+      if !path.is_file() || !audio_service::is_audio_file(&path) {
+        return Err(AppError::InvalidPack(format!("Arquivo de áudio ausente: {}", file)));
+      }
+      Ok(())
+    };
+
+    if self.key_define_type == KeyDefineType::Single {
+      match &self.sound {
+        Some(sound) => check_file(sound)?,
+        None => return Err(AppError::InvalidPack("Pacote single sem 'sound'".into())),
+      }
+    }
+
+    let tables = self.defines.iter().chain(self.defines_up.iter());
+    for (code, define) in tables {
+      let Some(define) = define else {
+        continue;
+      };
+      if let Define::Variants(variants) = define {
+        let nested = variants
+          .iter()
+          .any(|variant| matches!(variant, Define::Variants(_)));
+        if variants.is_empty() || nested {
+          return Err(AppError::InvalidPack(format!("Variações inválidas na tecla {}", code)));
+        }
+      }
+      for sound in define.sounds() {
+        match (sound, self.key_define_type) {
+          (Define::File(file), KeyDefineType::Multi) => check_file(file)?,
+          (Define::Slice([offset, duration]), KeyDefineType::Single) => {
+            if *offset < 0.0 || *duration <= 0.0 {
+              return Err(AppError::InvalidPack(format!("Trecho inválido na tecla {}", code)));
+            }
+          }
+          _ => {
+            return Err(AppError::InvalidPack(format!(
+              "A tecla {} não corresponde ao tipo do pacote",
+              code
+            )))
+          }
+        }
+      }
+    }
+    Ok(())
+  }
+
+  /// Point `sound` and the defines at new file names, e.g. after a conversion to WAV
+  pub fn rename_files(&mut self, renamed: &BTreeMap<String, String>) {
+    if let Some(sound) = self.sound.as_mut() {
+      if let Some(name) = renamed.get(sound.as_str()) {
+        *sound = name.clone();
+      }
+    }
+    for define in self.sounds_mut() {
+      if let Define::File(file) = define {
+        if let Some(name) = renamed.get(file.as_str()) {
+          *file = name.clone();
+        }
+      }
+    }
+  }
+
+  /// File names the pack plays, without duplicates
+  pub fn files(&self) -> Vec<String> {
+    let mut files: Vec<String> = self.sound.iter().cloned().collect();
+    for define in self.sounds() {
+      if let Define::File(file) = define {
+        files.push(file.clone());
+      }
+    }
+    files.sort_unstable();
+    files.dedup();
+    files
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn temp_dir(test: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("wayvibes-ui-{}-{}", test, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+  }
+
+  /// Write `json` as the pack's manifest, then load, save and load it again
+  fn round_trip(dir: &Path, json: &str) -> (PackManifest, PackManifest) {
+    fs::write(dir.join(MANIFEST_FILE), json).unwrap();
+    let first = PackManifest::load(dir).unwrap();
+    first.save(dir).unwrap();
+    (first, PackManifest::load(dir).unwrap())
+  }
+
+  #[test]
+  fn reads_config_version_as_string_or_number() {
+    let dir = temp_dir("manifest-version");
+
+    let (first, second) = round_trip(&dir, r#"{"config_version": "2", "defines": {}}"#);
+    assert_eq!(first.config_version().as_deref(), Some("2"));
+    assert_eq!(second.config_version().as_deref(), Some("2"));
+    assert_eq!(second.extra.get("config_version"), Some(&Value::String("2".into())));
+
+    let (first, second) = round_trip(&dir, r#"{"config_version": 2, "defines": {}}"#);
+    assert_eq!(first.config_version().as_deref(), Some("2"));
+    assert_eq!(second.extra.get("config_version"), Some(&Value::from(2)));
+
+    let (first, _) = round_trip(&dir, r#"{"defines": {}}"#);
+    assert_eq!(first.config_version(), None);
+
+    fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn keeps_key_up_sounds_and_unknown_fields() {
+    let dir = temp_dir("manifest-round-trip");
+    let json = r#"{
+      "id": "round",
+      "key_define_type": "multi",
+      "sound": "a.wav",
+      "author": "Someone",
+      "tags": ["thock", "linear"],
+      "defines": {"30": "a.wav", "31": null, "57": ["a.wav", "b.wav"]},
+      "defines_up": {"30": "b.wav", "57": null}
+    }"#;
+
+    let (first, second) = round_trip(&dir, json);
+    for manifest in [&first, &second] {
+      assert_eq!(manifest.defines.get(&30), Some(&Some(Define::File("a.wav".into()))));
+      assert_eq!(manifest.defines.get(&31), Some(&None));
+      assert_eq!(
+        manifest.defines.get(&57),
+        Some(&Some(Define::Variants(vec![
+          Define::File("a.wav".into()),
+          Define::File("b.wav".into()),
+        ])))
+      );
+      assert_eq!(manifest.defines_up.get(&30), Some(&Some(Define::File("b.wav".into()))));
+      assert_eq!(manifest.defines_up.get(&57), Some(&None));
+      assert_eq!(manifest.keyup_keys(), 1);
+      assert_eq!(manifest.text("author").as_deref(), Some("Someone"));
+      assert_eq!(manifest.extra.get("tags"), Some(&serde_json::json!(["thock", "linear"])));
+    }
+
+    fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn omits_an_empty_key_up_table() {
+    let dir = temp_dir("manifest-no-key-up");
+
+    round_trip(&dir, r#"{"defines": {"30": "a.wav"}}"#);
+    let saved = fs::read_to_string(dir.join(MANIFEST_FILE)).unwrap();
+    assert!(!saved.contains("defines_up"));
+
+    fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn rejects_nested_and_empty_variants() {
+    let dir = temp_dir("manifest-variants");
+    fs::write(dir.join("a.wav"), b"").unwrap();
+    fs::write(dir.join("b.wav"), b"").unwrap();
+    let manifest = |define: Define| PackManifest {
+      key_define_type: KeyDefineType::Multi,
+      defines: BTreeMap::from([(30, Some(define))]),
+      ..Default::default()
+    };
+    let file = |name: &str| Define::File(name.into());
+
+    assert!(manifest(Define::Variants(vec![file("a.wav"), file("b.wav")]))
+      .validate(&dir)
+      .is_ok());
+    let nested = Define::Variants(vec![file("a.wav"), Define::Variants(vec![file("b.wav")])]);
+    assert!(manifest(nested).validate(&dir).is_err());
+    assert!(manifest(Define::Variants(Vec::new())).validate(&dir).is_err());
+    assert!(manifest(file("missing.wav")).validate(&dir).is_err());
+
+    fs::remove_dir_all(&dir).unwrap();
+  }
+}
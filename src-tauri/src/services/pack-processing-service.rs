@@ -0,0 +1,373 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+
+use crate::error::AppError;
+use crate::models::{
+  KeyDefineType, KeySound, NormalizeTarget, ProcessOptions, ProcessReport, SoundChange,
+};
+use crate::services::audio_service::{self, AudioBuffer};
+use crate::services::pack_manifest::{Define, PackManifest};
+use crate::services::sound_pack_service::{self, StagedPack};
+use crate::services::transcode_service;
+
+const MAX_PADDING_MS: f64 = 1000.0;
+const MIN_TARGET_DB: f32 = -60.0;
+
+/// Trim and normalize every sound of a pack into a new pack, leaving the source untouched.
+/// Single packs are rebuilt slice by slice, so each key's offsets follow its trimmed sound.
+pub fn process_pack(
+  packs_dir: &Path,
+  pack_id: &str,
+  options: &ProcessOptions,
+) -> Result<StagedPack<ProcessReport>, AppError> {
+  validate_options(options)?;
+  let source_dir = packs_dir.join(pack_id);
+  if !source_dir.join("config.json").exists() {
+    return Err(AppError::InvalidPack("Pacote não encontrado".into()));
+  }
+  let source = PackManifest::load(&source_dir)?;
+  source.validate(&source_dir)?;
+
+  let name = format!(
+    "{} (processado)",
+    source.name.clone().unwrap_or_else(|| pack_id.to_string())
+  );
+  let new_id = sound_pack_service::unique_pack_id(packs_dir, &sound_pack_service::slugify(&name));
+  log::info!("Processing pack {} into {}", pack_id, new_id);
+
+  let staged = sound_pack_service::staging_dir(packs_dir)?;
+  let result = (|| {
+    sound_pack_service::copy_dir(&source_dir, &staged)?;
+    let mut manifest = source.clone();
+    manifest.id = Some(new_id.clone());
+    manifest.name = Some(name.clone());
+
+    let changes = match manifest.key_define_type {
+      KeyDefineType::Multi => process_files(&mut manifest, &staged, options)?,
+      KeyDefineType::Single => process_sprite(&mut manifest, &staged, options)?,
+    };
+    manifest.validate(&staged)?;
+    manifest.save(&staged)?;
+    Ok(ProcessReport {
+      source_pack_id: pack_id.to_string(),
+      pack: sound_pack_service::read_staged(&staged, &new_id)?,
+      changes,
+    })
+  })();
+
+  match result {
+    Ok(report) => Ok(StagedPack::new(staged, packs_dir, &new_id, report)),
+    Err(err) => {
+      log::warn!("Processing pack {} failed: {}", pack_id, err);
+      let _ = fs::remove_dir_all(&staged);
+      Err(err)
+    }
+  }
+}
+
+fn validate_options(options: &ProcessOptions) -> Result<(), AppError> {
+  if options.trim.is_none() && options.normalize.is_none() {
+    return Err(AppError::InvalidConfig("Nenhuma etapa de processamento escolhida".into()));
+  }
+  if let Some(trim) = &options.trim {
+    if !(audio_service::SILENCE_DB..=0.0).contains(&trim.threshold_db) {
+      return Err(AppError::InvalidConfig("Limiar de silêncio fora do intervalo".into()));
+    }
+    if !(0.0..=MAX_PADDING_MS).contains(&trim.padding_ms) {
+      return Err(AppError::InvalidConfig(format!(
+        "Margem fora do intervalo 0-{} ms",
+        MAX_PADDING_MS
+      )));
+    }
+  }
+  if let Some(NormalizeTarget::Peak { db } | NormalizeTarget::Rms { db }) = options.normalize {
+    if !(MIN_TARGET_DB..=0.0).contains(&db) {
+      return Err(AppError::InvalidConfig(format!(
+        "Nível alvo fora do intervalo {}-0 dBFS",
+        MIN_TARGET_DB
+      )));
+    }
+  }
+  Ok(())
+}
+
+/// What `process_sound` did to a sound
+struct Edit {
+  trimmed_start_ms: f64,
+  trimmed_end_ms: f64,
+  gain_db: f32,
+}
+
+impl Edit {
+  fn into_change(self, source: KeySound, keys: usize) -> SoundChange {
+    SoundChange {
+      source,
+      keys,
+      trimmed_start_ms: self.trimmed_start_ms,
+      trimmed_end_ms: self.trimmed_end_ms,
+      gain_db: self.gain_db,
+    }
+  }
+}
+
+/// Process each file of a multi pack in place, renaming non-WAV files
+fn process_files(
+  manifest: &mut PackManifest,
+  pack_dir: &Path,
+  options: &ProcessOptions,
+) -> Result<Vec<SoundChange>, AppError> {
+  let files = manifest.files();
+  let mut taken: BTreeSet<String> = files.iter().map(|file| file.to_lowercase()).collect();
+  let mut renamed = BTreeMap::new();
+  let mut changes = Vec::new();
+
+  for file in &files {
+    let buffer = audio_service::decode(&sound_pack_service::pack_file(pack_dir, file)?)?;
+    let (buffer, edit) = process_sound(buffer, options);
+    let keys = manifest
+      .sounds()
+      .filter(|sound| matches!(sound, Define::File(name) if name == file))
+      .count();
+
+    let target_name = transcode_service::replace_with_wav(pack_dir, file, &buffer, &mut taken)?;
+    if target_name != *file {
+      renamed.insert(file.clone(), target_name);
+    }
+    changes.push(edit.into_change(KeySound::File { file: file.clone() }, keys));
+  }
+
+  manifest.rename_files(&renamed);
+  Ok(changes)
+}
+
+/// Rebuild a single pack's sprite from its processed slices, back to back, and point each
+/// key at the new position of its slice
+fn process_sprite(
+  manifest: &mut PackManifest,
+  pack_dir: &Path,
+  options: &ProcessOptions,
+) -> Result<Vec<SoundChange>, AppError> {
+  let sprite_name = manifest
+    .sound
+    .clone()
+    .ok_or_else(|| AppError::InvalidPack("Pacote single sem 'sound'".into()))?;
+  let sprite = audio_service::decode(&sound_pack_service::pack_file(pack_dir, &sprite_name)?)?;
+  let frames = |[offset_ms, duration_ms]: [f64; 2]| {
+    (sprite.ms_to_frame(offset_ms), sprite.ms_to_frame(duration_ms))
+  };
+
+  // Keys often share a slice; process each distinct one once
+  let mut slices: BTreeMap<(usize, usize), ([f64; 2], usize)> = BTreeMap::new();
+  for sound in manifest.sounds() {
+    if let Define::Slice(slice) = sound {
+      slices.entry(frames(*slice)).or_insert((*slice, 0)).1 += 1;
+    }
+  }
+
+  let mut rebuilt = AudioBuffer::empty(sprite.sample_rate, sprite.channels);
+  rebuilt.bits_per_sample = sprite.bits_per_sample;
+  let mut moved = BTreeMap::new();
+  let mut changes = Vec::new();
+  for (&(start, len), &([offset_ms, duration_ms], keys)) in &slices {
+    let (segment, edit) = process_sound(sprite.slice(start, len), options);
+    let new_start = rebuilt.frames();
+    rebuilt.append(&segment)?;
+    let new_offset_ms = rebuilt.frame_to_ms(new_start);
+    let new_duration_ms = rebuilt.frame_to_ms(rebuilt.frames()) - new_offset_ms;
+    moved.insert((start, len), [new_offset_ms, new_duration_ms]);
+
+    let source = KeySound::Slice {
+      offset_ms,
+      duration_ms,
+    };
+    changes.push(edit.into_change(source, keys));
+  }
+
+  for sound in manifest.sounds_mut() {
+    if let Define::Slice(slice) = sound {
+      if let Some(new_slice) = moved.get(&frames(*slice)) {
+        *slice = *new_slice;
+      }
+    }
+  }
+
+  let mut taken = BTreeSet::from([sprite_name.to_lowercase()]);
+  manifest.sound = Some(transcode_service::replace_with_wav(
+    pack_dir,
+    &sprite_name,
+    &rebuilt,
+    &mut taken,
+  )?);
+  Ok(changes)
+}
+
+/// Trim and normalize one sound
+fn process_sound(mut buffer: AudioBuffer, options: &ProcessOptions) -> (AudioBuffer, Edit) {
+  let mut edit = Edit {
+    trimmed_start_ms: 0.0,
+    trimmed_end_ms: 0.0,
+    gain_db: 0.0,
+  };
+
+  if let Some(trim) = &options.trim {
+    // A sound that is silent throughout is left alone rather than cut to nothing
+    if let Some((start, end)) = buffer.audible_range(trim.threshold_db) {
+      let padding = buffer.ms_to_frame(trim.padding_ms);
+      let start = start.saturating_sub(padding);
+      let end = (end + padding).min(buffer.frames());
+      edit.trimmed_start_ms = buffer.frame_to_ms(start);
+      edit.trimmed_end_ms = buffer.frame_to_ms(buffer.frames() - end);
+      buffer = buffer.slice(start, end - start);
+    }
+  }
+
+  if let Some(target) = options.normalize {
+    let peak_db = buffer.peak_db();
+    if peak_db > audio_service::SILENCE_DB {
+      let gain_db = match target {
+        NormalizeTarget::Peak { db } => db - peak_db,
+        NormalizeTarget::Rms { db } => {
+          let rms_db = buffer.gated_rms_db();
+          if rms_db > audio_service::SILENCE_DB {
+            (db - rms_db).min(-peak_db)
+          } else {
+            0.0
+          }
+        }
+      };
+      buffer.apply_gain(gain_db);
+      edit.gain_db = (gain_db * 10.0).round() / 10.0;
+    }
+  }
+
+  (buffer, edit)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::models::TrimOptions;
+
+  /// Mono at 1 kHz, so frames and milliseconds match
+  fn buffer(samples: Vec<f32>) -> AudioBuffer {
+    let mut buffer = AudioBuffer::empty(1_000, 1);
+    buffer.samples = samples;
+    buffer
+  }
+
+  /// `lead` frames of `floor`, `loud` frames at half scale, then `tail` frames of `floor`
+  fn click(lead: usize, loud: usize, tail: usize, floor: f32) -> Vec<f32> {
+    let mut samples = vec![floor; lead];
+    samples.extend(vec![0.5; loud]);
+    samples.extend(vec![floor; tail]);
+    samples
+  }
+
+  fn trim(threshold_db: f32, padding_ms: f64) -> ProcessOptions {
+    ProcessOptions {
+      trim: Some(TrimOptions {
+        threshold_db,
+        padding_ms,
+      }),
+      normalize: None,
+    }
+  }
+
+  #[test]
+  fn trims_what_is_below_the_threshold() {
+    // The floor at -60 dBFS is silence for a -40 dB threshold but not for -80 dB
+    let floor = 0.001;
+
+    let (trimmed, edit) = process_sound(buffer(click(10, 5, 12, floor)), &trim(-40.0, 0.0));
+    assert_eq!(trimmed.samples, vec![0.5; 5]);
+    assert_eq!((edit.trimmed_start_ms, edit.trimmed_end_ms), (10.0, 12.0));
+
+    let (kept, edit) = process_sound(buffer(click(10, 5, 12, floor)), &trim(-80.0, 0.0));
+    assert_eq!(kept.frames(), 27);
+    assert_eq!((edit.trimmed_start_ms, edit.trimmed_end_ms), (0.0, 0.0));
+  }
+
+  #[test]
+  fn padding_keeps_some_silence_around_the_sound() {
+    let (trimmed, edit) = process_sound(buffer(click(10, 5, 1, 0.0)), &trim(-40.0, 3.0));
+
+    assert_eq!(trimmed.frames(), 9);
+    assert_eq!((edit.trimmed_start_ms, edit.trimmed_end_ms), (7.0, 0.0));
+  }
+
+  #[test]
+  fn leaves_a_silent_sound_alone() {
+    let (kept, edit) = process_sound(buffer(vec![0.0; 20]), &trim(-40.0, 0.0));
+
+    assert_eq!(kept.frames(), 20);
+    assert_eq!((edit.trimmed_start_ms, edit.trimmed_end_ms), (0.0, 0.0));
+  }
+
+  #[test]
+  fn normalizes_the_peak_to_the_target() {
+    let options = ProcessOptions {
+      trim: None,
+      normalize: Some(NormalizeTarget::Peak { db: -1.0 }),
+    };
+
+    let (normalized, edit) = process_sound(buffer(click(10, 5, 10, 0.0)), &options);
+    assert!((normalized.peak_db() + 1.0).abs() < 0.01);
+    assert_eq!(edit.gain_db, 5.0);
+  }
+
+  #[test]
+  fn rms_gain_stops_before_clipping() {
+    let mut samples = vec![0.1; 800];
+    samples[400] = 0.5;
+    let mut quiet = AudioBuffer::empty(8_000, 1);
+    quiet.samples = samples;
+    let options = ProcessOptions {
+      trim: None,
+      normalize: Some(NormalizeTarget::Rms { db: 0.0 }),
+    };
+
+    let (normalized, edit) = process_sound(quiet, &options);
+    assert!(normalized.peak_db().abs() < 0.01);
+    assert_eq!(edit.gain_db, 6.0);
+  }
+
+  #[test]
+  fn rebuilt_sprite_moves_each_slice_to_its_trimmed_position() {
+    let packs_dir =
+      std::env::temp_dir().join(format!("wayvibes-ui-process-sprite-{}", std::process::id()));
+    let pack_dir = packs_dir.join("sprite");
+    let _ = fs::remove_dir_all(&packs_dir);
+    fs::create_dir_all(&pack_dir).unwrap();
+    let mut sprite = click(10, 5, 5, 0.0);
+    sprite.extend(click(5, 5, 10, 0.0));
+    audio_service::write_wav(&pack_dir.join("sprite.wav"), &buffer(sprite)).unwrap();
+    PackManifest {
+      id: Some("sprite".into()),
+      key_define_type: KeyDefineType::Single,
+      sound: Some("sprite.wav".into()),
+      defines: BTreeMap::from([
+        (30, Some(Define::Slice([0.0, 20.0]))),
+        (31, Some(Define::Slice([20.0, 20.0]))),
+        (32, Some(Define::Slice([20.0, 20.0]))),
+      ]),
+      ..Default::default()
+    }
+    .save(&pack_dir)
+    .unwrap();
+
+    let staged = process_pack(&packs_dir, "sprite", &trim(-40.0, 0.0)).unwrap();
+    assert_eq!(staged.pack_id, "sprite-processado");
+    let manifest = PackManifest::load(&staged.dir).unwrap();
+    assert_eq!(manifest.defines[&30], Some(Define::Slice([0.0, 5.0])));
+    assert_eq!(manifest.defines[&31], Some(Define::Slice([5.0, 5.0])));
+    assert_eq!(manifest.defines[&32], Some(Define::Slice([5.0, 5.0])));
+    let rebuilt = audio_service::decode(&staged.dir.join("sprite.wav")).unwrap();
+    assert_eq!(rebuilt.samples, vec![0.5; 10]);
+    // The source pack is untouched
+    let source = PackManifest::load(&pack_dir).unwrap();
+    assert_eq!(source.defines[&30], Some(Define::Slice([0.0, 20.0])));
+
+    fs::remove_dir_all(&packs_dir).unwrap();
+  }
+}
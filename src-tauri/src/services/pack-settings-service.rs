@@ -0,0 +1,142 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::AppError;
+use crate::models::{PackLoudness, PackSettings};
+use crate::services::{audio_service, sound_pack_service};
+
+/// Per-pack settings live next to the packs so they move with the library
+const SETTINGS_FILE: &str = "pack-settings.json";
+/// Gated RMS the suggested gain aims for
+const TARGET_RMS_DB: f32 = -20.0;
+/// Headroom kept below full scale when suggesting a boost
+const PEAK_CEILING_DB: f32 = -1.0;
+pub const MAX_GAIN_DB: f32 = 12.0;
+
+pub fn load(packs_dir: &Path) -> Result<BTreeMap<String, PackSettings>, AppError> {
+  let path = settings_path(packs_dir);
+  if !path.exists() {
+    return Ok(BTreeMap::new());
+  }
+  let content = fs::read_to_string(&path)?;
+  Ok(serde_json::from_str(&content)?)
+}
+
+/// Settings of one pack, or the defaults when none were saved or the file is unreadable
+pub fn get(packs_dir: &Path, pack_id: &str) -> PackSettings {
+  match load(packs_dir) {
+    Ok(mut all) => all.remove(pack_id).unwrap_or_default(),
+    Err(err) => {
+      log::warn!("Ignoring unreadable {}: {}", SETTINGS_FILE, err);
+      PackSettings::default()
+    }
+  }
+}
+
+pub fn save(packs_dir: &Path, pack_id: &str, settings: &PackSettings) -> Result<(), AppError> {
+  validate(settings)?;
+  let mut all = load(packs_dir)?;
+  if *settings == PackSettings::default() {
+    all.remove(pack_id);
+  } else {
+    all.insert(pack_id.to_string(), settings.clone());
+  }
+  write(packs_dir, &all)?;
+  log::debug!("Saved settings of pack {}", pack_id);
+  Ok(())
+}
+
+pub fn remove(packs_dir: &Path, pack_id: &str) -> Result<(), AppError> {
+  let mut all = load(packs_dir)?;
+  if all.remove(pack_id).is_some() {
+    write(packs_dir, &all)?;
+  }
+  Ok(())
+}
+
+/// Measure peak and gated RMS over every sound of a pack and suggest a gain towards
+/// `TARGET_RMS_DB` that keeps the peak under `PEAK_CEILING_DB`
+pub fn analyze(packs_dir: &Path, pack_id: &str) -> Result<PackLoudness, AppError> {
+  let pack_dir = packs_dir.join(pack_id);
+  if !pack_dir.exists() {
+    return Err(AppError::InvalidPack("Pacote não encontrado".into()));
+  }
+
+  let mut peak_db = audio_service::SILENCE_DB;
+  let mut rms_values = Vec::new();
+  let mut files = 0;
+  for path in sound_pack_service::audio_files(&pack_dir)? {
+    let buffer = match audio_service::decode(&path) {
+      Ok(buffer) => buffer,
+      Err(err) => {
+        log::warn!("Skipping {} in loudness analysis: {}", path.display(), err);
+        continue;
+      }
+    };
+    files += 1;
+    peak_db = peak_db.max(buffer.peak_db());
+    let rms_db = buffer.gated_rms_db();
+    if rms_db > audio_service::SILENCE_DB {
+      rms_values.push(audio_service::db_to_linear(rms_db));
+    }
+  }
+
+  if files == 0 {
+    return Err(AppError::InvalidPack("Nenhum áudio legível no pacote".into()));
+  }
+
+  let rms_db = if rms_values.is_empty() {
+    audio_service::SILENCE_DB
+  } else {
+    audio_service::linear_to_db(rms_values.iter().sum::<f32>() / rms_values.len() as f32)
+  };
+  let suggested_gain_db = if rms_db <= audio_service::SILENCE_DB {
+    0.0
+  } else {
+    (TARGET_RMS_DB - rms_db)
+      .min(PEAK_CEILING_DB - peak_db)
+      .clamp(-MAX_GAIN_DB, MAX_GAIN_DB)
+  };
+
+  log::info!(
+    "Pack {}: peak {:.1} dBFS, rms {:.1} dBFS, suggested gain {:+.1} dB",
+    pack_id,
+    peak_db,
+    rms_db,
+    suggested_gain_db
+  );
+  Ok(PackLoudness {
+    pack_id: pack_id.to_string(),
+    files,
+    peak_db,
+    rms_db,
+    suggested_gain_db: (suggested_gain_db * 10.0).round() / 10.0,
+  })
+}
+
+fn validate(settings: &PackSettings) -> Result<(), AppError> {
+  if !(-MAX_GAIN_DB..=MAX_GAIN_DB).contains(&settings.gain_db) {
+    return Err(AppError::InvalidConfig(format!(
+      "Ganho fora do intervalo ±{} dB",
+      MAX_GAIN_DB
+    )));
+  }
+  if let Some(volume) = settings.default_volume {
+    if !(0.0..=1.0).contains(&volume) {
+      return Err(AppError::InvalidConfig("Volume padrão fora do intervalo 0-1".into()));
+    }
+  }
+  Ok(())
+}
+
+fn write(packs_dir: &Path, all: &BTreeMap<String, PackSettings>) -> Result<(), AppError> {
+  let path = settings_path(packs_dir);
+  let payload = serde_json::to_string_pretty(all)?;
+  fs::write(&path, payload)?;
+  Ok(())
+}
+
+fn settings_path(packs_dir: &Path) -> PathBuf {
+  packs_dir.join(SETTINGS_FILE)
+}
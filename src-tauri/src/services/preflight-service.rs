@@ -0,0 +1,303 @@
+use std::fs::{self, File};
+use std::path::PathBuf;
+use std::process::Command;
+
+use crate::models::{
+  CheckStatus, PreflightCheck, PreflightReport, PreflightSummary, WayvibesSettings,
+};
+use crate::services::{wayvibes_capabilities, wayvibes_locator};
+
+const INPUT_GROUP: &str = "input";
+
+/// Check everything wayvibes needs before it is started
+pub fn run(settings: &WayvibesSettings) -> PreflightReport {
+  let devices = input_devices();
+  let checks = vec![
+    check_binary(settings),
+    check_input_devices(&devices),
+    check_input_group(&devices),
+    check_audio_server(),
+    check_session(),
+  ];
+  PreflightReport {
+    summary: summarize(&checks),
+    checks,
+  }
+}
+
+pub fn summarize(checks: &[PreflightCheck]) -> PreflightSummary {
+  let count = |status: CheckStatus| checks.iter().filter(|check| check.status == status).count();
+  PreflightSummary {
+    status: checks
+      .iter()
+      .map(|check| check.status)
+      .max()
+      .unwrap_or(CheckStatus::Pass),
+    passed: count(CheckStatus::Pass),
+    warnings: count(CheckStatus::Warn),
+    failures: count(CheckStatus::Fail),
+  }
+}
+
+/// Groups of this process (`id -Gn`), which only pick up new memberships after a re-login
+pub fn groups() -> Vec<String> {
+  Command::new("id")
+    .arg("-Gn")
+    .output()
+    .ok()
+    .filter(|output| output.status.success())
+    .map(|output| {
+      String::from_utf8_lossy(&output.stdout)
+        .split_whitespace()
+        .map(|group| group.to_string())
+        .collect()
+    })
+    .unwrap_or_default()
+}
+
+/// `/dev/input/event*` devices and whether this process can open them for reading
+pub fn input_devices() -> Vec<(String, bool)> {
+  let mut devices: Vec<(String, bool)> = fs::read_dir("/dev/input")
+    .map(|entries| {
+      entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+          path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.starts_with("event"))
+        })
+        .map(|path| {
+          let readable = File::open(&path).is_ok();
+          (path.to_string_lossy().to_string(), readable)
+        })
+        .collect()
+    })
+    .unwrap_or_default();
+  devices.sort();
+  devices
+}
+
+fn check_binary(settings: &WayvibesSettings) -> PreflightCheck {
+  let id = "binary";
+  let label = "Binário do wayvibes";
+  let binary = match wayvibes_locator::resolve(settings) {
+    Ok(binary) => binary,
+    Err(err) => {
+      return check(
+        id,
+        label,
+        CheckStatus::Fail,
+        err.to_string(),
+        Some("Instale o wayvibes ou informe o caminho do binário nas configurações."),
+      )
+    }
+  };
+
+  let caps = wayvibes_capabilities::probe(&binary);
+  if !caps.probed {
+    return check(
+      id,
+      label,
+      CheckStatus::Warn,
+      format!("{} não respondeu a --help", binary.path.display()),
+      Some("Verifique se o arquivo é o wayvibes e se ele executa no terminal."),
+    );
+  }
+
+  let version = caps.version.unwrap_or_else(|| "versão desconhecida".into());
+  check(
+    id,
+    label,
+    CheckStatus::Pass,
+    format!("{} ({})", binary.path.display(), version),
+    None,
+  )
+}
+
+fn check_input_devices(devices: &[(String, bool)]) -> PreflightCheck {
+  let id = "input-devices";
+  let label = "Acesso a /dev/input";
+  let readable = devices.iter().filter(|(_, readable)| *readable).count();
+  let remediation = Some(
+    "Adicione seu usuário ao grupo input (sudo usermod -aG input $USER) e faça logout e login.",
+  );
+
+  if devices.is_empty() {
+    return check(
+      id,
+      label,
+      CheckStatus::Fail,
+      "Nenhum dispositivo /dev/input/event* encontrado".into(),
+      Some("Verifique se o sistema expõe os dispositivos de entrada (evdev)."),
+    );
+  }
+  if readable == 0 {
+    return check(
+      id,
+      label,
+      CheckStatus::Fail,
+      format!("Nenhum dos {} dispositivos pode ser lido", devices.len()),
+      remediation,
+    );
+  }
+  if readable < devices.len() {
+    return check(
+      id,
+      label,
+      CheckStatus::Warn,
+      format!("{} de {} dispositivos podem ser lidos", readable, devices.len()),
+      remediation,
+    );
+  }
+  check(
+    id,
+    label,
+    CheckStatus::Pass,
+    format!("{} dispositivos podem ser lidos", readable),
+    None,
+  )
+}
+
+fn check_input_group(devices: &[(String, bool)]) -> PreflightCheck {
+  let id = "input-group";
+  let label = "Grupo input";
+
+  if groups().iter().any(|group| group == INPUT_GROUP) {
+    return check(
+      id,
+      label,
+      CheckStatus::Pass,
+      "A sessão atual pertence ao grupo input".into(),
+      None,
+    );
+  }
+
+  if listed_in_input_group() {
+    return check(
+      id,
+      label,
+      CheckStatus::Warn,
+      "Usuário adicionado ao grupo input, mas a sessão atual ainda não reflete isso".into(),
+      Some("Faça logout e login (ou reinicie) para aplicar o novo grupo."),
+    );
+  }
+
+  // Some distros grant access through logind ACLs instead of the group
+  if !devices.is_empty() && devices.iter().all(|(_, readable)| *readable) {
+    return check(
+      id,
+      label,
+      CheckStatus::Pass,
+      "Fora do grupo input, mas os dispositivos já podem ser lidos".into(),
+      None,
+    );
+  }
+
+  check(
+    id,
+    label,
+    CheckStatus::Fail,
+    "O usuário não pertence ao grupo input".into(),
+    Some("Execute sudo usermod -aG input $USER e faça logout e login."),
+  )
+}
+
+fn check_audio_server() -> PreflightCheck {
+  let id = "audio-server";
+  let label = "Servidor de áudio";
+
+  if let Ok(server) = std::env::var("PULSE_SERVER") {
+    return check(
+      id,
+      label,
+      CheckStatus::Pass,
+      format!("PULSE_SERVER={}", server),
+      None,
+    );
+  }
+
+  let runtime_dir = std::env::var("XDG_RUNTIME_DIR").map(PathBuf::from).ok();
+  let candidates = [("pipewire-0", "PipeWire"), ("pulse/native", "PulseAudio")];
+  for (socket, name) in candidates {
+    if let Some(path) = runtime_dir.as_ref().map(|dir| dir.join(socket)) {
+      if path.exists() {
+        return check(
+          id,
+          label,
+          CheckStatus::Pass,
+          format!("{} ({})", name, path.display()),
+          None,
+        );
+      }
+    }
+  }
+
+  check(
+    id,
+    label,
+    CheckStatus::Fail,
+    "Nenhum socket do PipeWire ou PulseAudio encontrado".into(),
+    Some("Inicie o servidor de áudio, por exemplo: systemctl --user start pipewire pipewire-pulse"),
+  )
+}
+
+fn check_session() -> PreflightCheck {
+  let id = "session";
+  let label = "Tipo de sessão";
+  let session = std::env::var("XDG_SESSION_TYPE").unwrap_or_default();
+  let wayland_display = std::env::var("WAYLAND_DISPLAY").ok();
+
+  match (session.as_str(), wayland_display) {
+    ("wayland", _) | (_, Some(_)) => check(id, label, CheckStatus::Pass, "Wayland".into(), None),
+    ("x11", None) => check(
+      id,
+      label,
+      CheckStatus::Warn,
+      "Sessão X11".into(),
+      Some("O wayvibes é feito para Wayland; em X11 ele pode funcionar, mas não é suportado."),
+    ),
+    _ => check(
+      id,
+      label,
+      CheckStatus::Warn,
+      "Tipo de sessão não identificado (XDG_SESSION_TYPE vazio)".into(),
+      Some("Inicie o app a partir da sessão gráfica para herdar as variáveis do ambiente."),
+    ),
+  }
+}
+
+/// Whether `/etc/group` lists the user in `input`, even if this session predates it
+fn listed_in_input_group() -> bool {
+  let Ok(user) = std::env::var("USER") else {
+    return false;
+  };
+  fs::read_to_string("/etc/group")
+    .map(|content| {
+      content.lines().any(|line| {
+        let mut fields = line.split(':');
+        fields.next() == Some(INPUT_GROUP)
+          && fields
+            .nth(2)
+            .is_some_and(|members| members.split(',').any(|member| member == user))
+      })
+    })
+    .unwrap_or(false)
+}
+
+fn check(
+  id: &str,
+  label: &str,
+  status: CheckStatus,
+  detail: String,
+  remediation: Option<&str>,
+) -> PreflightCheck {
+  PreflightCheck {
+    id: id.into(),
+    label: label.into(),
+    status,
+    detail,
+    remediation: remediation.map(|text| text.to_string()),
+  }
+}
@@ -0,0 +1,298 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::error::AppError;
+use crate::models::{KeyStroke, PreviewInput, PreviewRequest};
+use crate::services::audio_service::{self, AudioBuffer};
+use crate::services::pack_manifest::{Define, PackManifest, MANIFEST_FILE};
+use crate::services::{keymap, pack_settings_service, sound_pack_service};
+
+/// Pace of a text preview when the request sets none, about 100 words per minute
+const DEFAULT_INTERVAL_MS: f64 = 120.0;
+/// How long each key of a text preview stays down before its key-up sound
+const DEFAULT_HOLD_MS: f64 = 60.0;
+const MAX_PREVIEW_MS: f64 = 60_000.0;
+
+/// A sound to start at `at_ms`: a file or a slice of the sprite, never a variants list
+struct Hit {
+  at_ms: f64,
+  sound: Define,
+}
+
+/// Mix the sounds a pack plays for a sequence of keystrokes, with the pack's gain applied.
+/// Variations are taken in turn rather than at random, so a request always renders the
+/// same audio.
+pub fn render(packs_dir: &Path, request: &PreviewRequest) -> Result<AudioBuffer, AppError> {
+  let pack_dir = packs_dir.join(&request.pack_id);
+  if !pack_dir.join(MANIFEST_FILE).exists() {
+    return Err(AppError::InvalidPack("Pacote não encontrado".into()));
+  }
+  let manifest = PackManifest::load(&pack_dir)?;
+  manifest.validate(&pack_dir)?;
+
+  let strokes = strokes(&request.input)?;
+  let hits = hits(&manifest, &strokes);
+  if hits.is_empty() {
+    return Err(AppError::InvalidPack("O pacote não tem som para essas teclas".into()));
+  }
+
+  // Decode each file once; the output takes the highest rate and channel count among them
+  let mut files = BTreeMap::new();
+  for hit in &hits {
+    let file = match &hit.sound {
+      Define::File(file) => file,
+      _ => manifest
+        .sound
+        .as_ref()
+        .ok_or_else(|| AppError::InvalidPack("Pacote single sem 'sound'".into()))?,
+    };
+    if !files.contains_key(file) {
+      let buffer = audio_service::decode(&sound_pack_service::pack_file(&pack_dir, file)?)?;
+      files.insert(file.clone(), buffer);
+    }
+  }
+  let sample_rate = files.values().map(|buffer| buffer.sample_rate).max().unwrap_or(44_100);
+  let channels = files.values().map(|buffer| buffer.channels).max().unwrap_or(1).max(1);
+
+  let mut output = AudioBuffer::empty(sample_rate, channels);
+  for hit in &hits {
+    let clip = match &hit.sound {
+      Define::File(file) => files[file].clone(),
+      Define::Slice([offset_ms, duration_ms]) => {
+        let sprite = manifest.sound.as_ref().map(|file| &files[file]);
+        let Some(sprite) = sprite else {
+          continue;
+        };
+        sprite.slice(sprite.ms_to_frame(*offset_ms), sprite.ms_to_frame(*duration_ms))
+      }
+      Define::Variants(_) => continue,
+    };
+    let start = output.ms_to_frame(hit.at_ms);
+    mix(&mut output, start, &clip.resample(sample_rate));
+  }
+
+  // Keep the pause after the last keystroke, as a recording of the typing would
+  let end = strokes
+    .iter()
+    .map(|stroke| stroke.at_ms + stroke.hold_ms.unwrap_or(0.0))
+    .fold(0.0, f64::max);
+  let frames = output.ms_to_frame(end).max(output.frames());
+  output.samples.resize(frames * channels, 0.0);
+
+  output.apply_gain(pack_settings_service::get(packs_dir, &request.pack_id).gain_db);
+  log::info!(
+    "Rendered preview of pack {}: {} keystrokes, {} sounds, {:.0} ms",
+    request.pack_id,
+    strokes.len(),
+    hits.len(),
+    output.frame_to_ms(output.frames())
+  );
+  Ok(output)
+}
+
+/// `render` as the bytes of a WAV file
+pub fn render_wav(packs_dir: &Path, request: &PreviewRequest) -> Result<Vec<u8>, AppError> {
+  audio_service::wav_bytes(&render(packs_dir, request)?)
+}
+
+fn strokes(input: &PreviewInput) -> Result<Vec<KeyStroke>, AppError> {
+  let strokes = match input {
+    PreviewInput::Text { text, interval_ms } => {
+      let interval_ms = interval_ms.unwrap_or(DEFAULT_INTERVAL_MS);
+      if !interval_ms.is_finite() || interval_ms <= 0.0 || interval_ms > MAX_PREVIEW_MS {
+        return Err(AppError::InvalidConfig("Intervalo entre teclas inválido".into()));
+      }
+      // Unknown characters still take their time, so the rhythm of the text is kept
+      text
+        .chars()
+        .enumerate()
+        .filter_map(|(index, ch)| {
+          let key = keymap::by_char(ch);
+          if key.is_none() {
+            log::debug!("No key types {:?}; leaving a pause", ch);
+          }
+          key.map(|key| KeyStroke {
+            code: key.code,
+            at_ms: index as f64 * interval_ms,
+            hold_ms: Some(DEFAULT_HOLD_MS.min(interval_ms)),
+          })
+        })
+        .collect()
+    }
+    PreviewInput::Keys { strokes } => strokes.clone(),
+  };
+
+  if strokes.is_empty() {
+    return Err(AppError::InvalidConfig("Nenhuma tecla para tocar".into()));
+  }
+  for stroke in &strokes {
+    let end_ms = stroke.at_ms + stroke.hold_ms.unwrap_or(0.0);
+    if stroke.at_ms < 0.0 || stroke.hold_ms.is_some_and(|hold| hold < 0.0) {
+      return Err(AppError::InvalidConfig(format!("Tempo inválido na tecla {}", stroke.code)));
+    }
+    if !end_ms.is_finite() || end_ms > MAX_PREVIEW_MS {
+      return Err(AppError::InvalidConfig(format!(
+        "Prévia longa demais (máximo {} s)",
+        MAX_PREVIEW_MS / 1000.0
+      )));
+    }
+  }
+  Ok(strokes)
+}
+
+/// The sounds the strokes trigger, with `PackManifest::fallback` for keys the pack leaves out
+fn hits(manifest: &PackManifest, strokes: &[KeyStroke]) -> Vec<Hit> {
+  let fallback = manifest.fallback().map(|file| Define::File(file.to_string()));
+  let mut presses: BTreeMap<(bool, u32), usize> = BTreeMap::new();
+  let mut pick = |up: bool, code: u32, define: &Define| {
+    let count = presses.entry((up, code)).or_default();
+    let sound = match define {
+      Define::Variants(variants) => variants[*count % variants.len()].clone(),
+      sound => sound.clone(),
+    };
+    *count += 1;
+    sound
+  };
+
+  let mut hits = Vec::new();
+  for stroke in strokes {
+    let press = match manifest.defines.get(&stroke.code) {
+      Some(define) => define.as_ref(),
+      None => fallback.as_ref(),
+    };
+    if let Some(define) = press {
+      hits.push(Hit {
+        at_ms: stroke.at_ms,
+        sound: pick(false, stroke.code, define),
+      });
+    }
+
+    let release = manifest.defines_up.get(&stroke.code).and_then(Option::as_ref);
+    if let (Some(hold_ms), Some(define)) = (stroke.hold_ms, release) {
+      hits.push(Hit {
+        at_ms: stroke.at_ms + hold_ms,
+        sound: pick(true, stroke.code, define),
+      });
+    }
+  }
+  hits
+}
+
+/// Add `clip` to `output` from frame `start`, growing `output` as needed. Mono clips are
+/// copied to every channel.
+fn mix(output: &mut AudioBuffer, start: usize, clip: &AudioBuffer) {
+  let channels = output.channels.max(1);
+  let clip_channels = clip.channels.max(1);
+  let end = (start + clip.frames()) * channels;
+  if output.samples.len() < end {
+    output.samples.resize(end, 0.0);
+  }
+  for (frame, samples) in clip.samples.chunks_exact(clip_channels).enumerate() {
+    let base = (start + frame) * channels;
+    for channel in 0..channels {
+      output.samples[base + channel] += samples[channel.min(samples.len() - 1)];
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::fs;
+  use std::path::PathBuf;
+
+  use super::*;
+  use crate::models::KeyDefineType;
+
+  const RATE: u32 = 8_000;
+  const KEY_A: u32 = 30;
+
+  /// 100 frames of silence with `marker` on the first one, so each hit shows up as one sample
+  fn write_click(path: &Path, marker: f32) {
+    let mut buffer = AudioBuffer::empty(RATE, 1);
+    buffer.samples = vec![0.0; 100];
+    buffer.samples[0] = marker;
+    audio_service::write_wav(path, &buffer).unwrap();
+  }
+
+  /// A multi pack whose A key alternates between two clicks and has a key-up sound
+  fn tiny_pack(test: &str) -> PathBuf {
+    let packs_dir =
+      std::env::temp_dir().join(format!("wayvibes-ui-{}-{}", test, std::process::id()));
+    let pack_dir = packs_dir.join("tiny");
+    let _ = fs::remove_dir_all(&packs_dir);
+    fs::create_dir_all(&pack_dir).unwrap();
+    write_click(&pack_dir.join("click-1.wav"), 0.5);
+    write_click(&pack_dir.join("click-2.wav"), 0.75);
+    write_click(&pack_dir.join("up.wav"), -0.25);
+
+    let mut manifest = PackManifest {
+      id: Some("tiny".into()),
+      name: Some("Tiny".into()),
+      key_define_type: KeyDefineType::Multi,
+      ..Default::default()
+    };
+    manifest.defines.insert(
+      KEY_A,
+      Some(Define::Variants(vec![
+        Define::File("click-1.wav".into()),
+        Define::File("click-2.wav".into()),
+      ])),
+    );
+    manifest.defines_up.insert(KEY_A, Some(Define::File("up.wav".into())));
+    manifest.save(&pack_dir).unwrap();
+    packs_dir
+  }
+
+  fn request() -> PreviewRequest {
+    let strokes = [0.0, 125.0, 250.0]
+      .into_iter()
+      .map(|at_ms| KeyStroke {
+        code: KEY_A,
+        at_ms,
+        hold_ms: Some(40.0),
+      })
+      .collect();
+    PreviewRequest {
+      pack_id: "tiny".into(),
+      input: PreviewInput::Keys { strokes },
+    }
+  }
+
+  #[test]
+  fn renders_the_same_request_identically() {
+    let packs_dir = tiny_pack("preview-identical");
+    let first = render_wav(&packs_dir, &request()).unwrap();
+    let second = render_wav(&packs_dir, &request()).unwrap();
+    assert_eq!(first, second);
+    let _ = fs::remove_dir_all(&packs_dir);
+  }
+
+  #[test]
+  fn places_each_hit_at_its_time() {
+    let packs_dir = tiny_pack("preview-offsets");
+    let output = render(&packs_dir, &request()).unwrap();
+    let _ = fs::remove_dir_all(&packs_dir);
+
+    assert_eq!((output.sample_rate, output.channels), (RATE, 1));
+    let hits: Vec<(usize, f32)> = output
+      .samples
+      .iter()
+      .enumerate()
+      .filter(|(_, sample)| **sample != 0.0)
+      .map(|(frame, sample)| (frame, *sample))
+      .collect();
+    let frame = |at_ms: f64| (at_ms * RATE as f64 / 1000.0) as usize;
+    // Presses take the variations in turn; releases follow 40 ms later
+    assert_eq!(
+      hits,
+      vec![
+        (frame(0.0), 0.5),
+        (frame(40.0), -0.25),
+        (frame(125.0), 0.75),
+        (frame(165.0), -0.25),
+        (frame(250.0), 0.5),
+        (frame(290.0), -0.25),
+      ]
+    );
+  }
+}
@@ -0,0 +1,34 @@
+use std::collections::HashSet;
+
+use crate::error::AppError;
+use crate::models::Profile;
+use crate::services::pack_settings_service::MAX_GAIN_DB;
+
+pub fn validate_profiles(profiles: &[Profile]) -> Result<(), AppError> {
+  let mut ids = HashSet::new();
+  for profile in profiles {
+    if profile.id.trim().is_empty() || !ids.insert(profile.id.as_str()) {
+      return Err(AppError::InvalidConfig(format!(
+        "Identificador de perfil inválido: '{}'",
+        profile.id
+      )));
+    }
+    if profile.name.trim().is_empty() {
+      return Err(AppError::InvalidConfig(format!(
+        "Perfil '{}' sem nome",
+        profile.id
+      )));
+    }
+    if !(-MAX_GAIN_DB..=MAX_GAIN_DB).contains(&profile.gain_db) {
+      return Err(AppError::InvalidConfig(format!(
+        "Ganho do perfil '{}' fora do intervalo ±{} dB",
+        profile.name, MAX_GAIN_DB
+      )));
+    }
+  }
+  Ok(())
+}
+
+pub fn find<'a>(profiles: &'a [Profile], profile_id: &str) -> Option<&'a Profile> {
+  profiles.iter().find(|profile| profile.id == profile_id)
+}
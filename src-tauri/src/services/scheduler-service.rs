@@ -0,0 +1,261 @@
+use std::collections::HashSet;
+
+use chrono::{Datelike, NaiveDateTime, NaiveTime};
+
+use crate::error::AppError;
+use crate::models::{AppConfig, Profile, ScheduleAction, ScheduleRule, Weekday};
+use crate::services::profile_service;
+
+/// What wayvibes plays: the manual state with the action of the schedule in effect on top.
+/// It's never saved, so the config only holds what the user chose and a restart in the
+/// middle of a window can't mistake a scheduled value for a manual one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Playback {
+  pub paused: bool,
+  pub pack_id: Option<String>,
+  pub profile_id: Option<String>,
+  /// Scheduled volume, in place of the slider
+  pub volume: Option<f32>,
+}
+
+/// Parse a local "HH:MM" time
+pub fn parse_time(value: &str) -> Result<NaiveTime, AppError> {
+  NaiveTime::parse_from_str(value.trim(), "%H:%M")
+    .map_err(|_| AppError::InvalidConfig(format!("Horário inválido: {}", value)))
+}
+
+/// Check rules before saving them; a rule can only switch to one of `profiles`
+pub fn validate_rules(rules: &[ScheduleRule], profiles: &[Profile]) -> Result<(), AppError> {
+  let mut ids = HashSet::new();
+  for rule in rules {
+    if rule.id.trim().is_empty() || !ids.insert(rule.id.as_str()) {
+      return Err(AppError::InvalidConfig(format!(
+        "Identificador de agenda inválido: '{}'",
+        rule.id
+      )));
+    }
+    if rule.days.is_empty() {
+      return Err(AppError::InvalidConfig(format!(
+        "Agenda '{}' sem dias da semana",
+        rule.name
+      )));
+    }
+    parse_time(&rule.start)?;
+    parse_time(&rule.end)?;
+    if let ScheduleAction::SetVolume { volume } = rule.action {
+      if !(0.0..=1.0).contains(&volume) {
+        return Err(AppError::InvalidConfig(format!(
+          "Volume da agenda '{}' fora do intervalo 0-1",
+          rule.name
+        )));
+      }
+    }
+    if let ScheduleAction::SwitchProfile { profile_id } = &rule.action {
+      if profile_service::find(profiles, profile_id).is_none() {
+        return Err(AppError::InvalidConfig(format!(
+          "Perfil da agenda '{}' não encontrado",
+          rule.name
+        )));
+      }
+    }
+  }
+  Ok(())
+}
+
+/// First enabled rule whose window contains `now`; earlier rules take priority
+pub fn active_rule(rules: &[ScheduleRule], now: NaiveDateTime) -> Option<&ScheduleRule> {
+  rules
+    .iter()
+    .filter(|rule| rule.enabled)
+    .find(|rule| rule_matches(rule, now))
+}
+
+pub fn playback(config: &AppConfig, action: Option<&ScheduleAction>) -> Playback {
+  let mut playback = Playback {
+    paused: config.paused,
+    pack_id: config.active_pack_id.clone(),
+    profile_id: config.active_profile_id.clone(),
+    volume: None,
+  };
+  match action {
+    Some(ScheduleAction::Pause) => playback.paused = true,
+    Some(ScheduleAction::SetVolume { volume }) => playback.volume = Some(volume.clamp(0.0, 1.0)),
+    Some(ScheduleAction::SetPack { pack_id }) => playback.pack_id = Some(pack_id.clone()),
+    // A profile removed since the rule was saved leaves the manual state alone
+    Some(ScheduleAction::SwitchProfile { profile_id }) => {
+      if let Some(profile) = profile_service::find(&config.profiles, profile_id) {
+        playback.profile_id = Some(profile.id.clone());
+        if let Some(pack_id) = &profile.pack_id {
+          playback.pack_id = Some(pack_id.clone());
+        }
+      }
+    }
+    None => {}
+  }
+  playback
+}
+
+fn rule_matches(rule: &ScheduleRule, now: NaiveDateTime) -> bool {
+  let (Ok(start), Ok(end)) = (parse_time(&rule.start), parse_time(&rule.end)) else {
+    return false;
+  };
+  let time = now.time();
+  let today = to_weekday(now.weekday());
+  let yesterday = to_weekday(now.weekday().pred());
+
+  if start < end {
+    rule.days.contains(&today) && time >= start && time < end
+  } else {
+    // Crosses midnight (or spans the whole day when start == end): the part
+    // after midnight belongs to the day the window started on
+    (rule.days.contains(&today) && time >= start)
+      || (rule.days.contains(&yesterday) && time < end)
+  }
+}
+
+fn to_weekday(day: chrono::Weekday) -> Weekday {
+  match day {
+    chrono::Weekday::Mon => Weekday::Mon,
+    chrono::Weekday::Tue => Weekday::Tue,
+    chrono::Weekday::Wed => Weekday::Wed,
+    chrono::Weekday::Thu => Weekday::Thu,
+    chrono::Weekday::Fri => Weekday::Fri,
+    chrono::Weekday::Sat => Weekday::Sat,
+    chrono::Weekday::Sun => Weekday::Sun,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use chrono::NaiveDate;
+
+  use super::*;
+
+  fn rule(start: &str, end: &str, days: Vec<Weekday>) -> ScheduleRule {
+    with_action(start, end, days, ScheduleAction::Pause)
+  }
+
+  fn with_action(
+    start: &str,
+    end: &str,
+    days: Vec<Weekday>,
+    action: ScheduleAction,
+  ) -> ScheduleRule {
+    ScheduleRule {
+      id: "test".into(),
+      name: "Test".into(),
+      enabled: true,
+      days,
+      start: start.into(),
+      end: end.into(),
+      action,
+    }
+  }
+
+  /// 2024-01-01 is a Monday
+  fn at(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2024, 1, day)
+      .and_then(|date| date.and_hms_opt(hour, minute, 0))
+      .unwrap()
+  }
+
+  #[test]
+  fn overnight_window_matches_before_midnight_on_its_day() {
+    let night = rule("22:00", "06:00", vec![Weekday::Mon]);
+    assert!(rule_matches(&night, at(1, 23, 0)));
+    assert!(!rule_matches(&night, at(1, 21, 59)));
+  }
+
+  #[test]
+  fn overnight_window_after_midnight_belongs_to_the_start_day() {
+    let night = rule("22:00", "06:00", vec![Weekday::Mon]);
+    assert!(rule_matches(&night, at(2, 5, 0)));
+    // Monday early morning is the tail of Sunday's window, which isn't selected
+    assert!(!rule_matches(&night, at(1, 5, 0)));
+  }
+
+  #[test]
+  fn overnight_window_ends_exactly_at_end() {
+    let night = rule("22:00", "06:00", vec![Weekday::Mon]);
+    assert!(rule_matches(&night, at(2, 5, 59)));
+    assert!(!rule_matches(&night, at(2, 6, 0)));
+  }
+
+  #[test]
+  fn equal_start_and_end_spans_a_whole_day() {
+    let all_day = rule("08:00", "08:00", vec![Weekday::Mon]);
+    assert!(rule_matches(&all_day, at(1, 8, 0)));
+    assert!(rule_matches(&all_day, at(2, 7, 59)));
+    assert!(!rule_matches(&all_day, at(2, 8, 0)));
+    assert!(!rule_matches(&all_day, at(1, 7, 59)));
+  }
+
+  /// Playback at `now` for the config as saved to disk and read back, as after a restart
+  fn after_restart(config: &AppConfig, now: NaiveDateTime) -> Playback {
+    let saved = serde_json::to_string(config).unwrap();
+    let config: AppConfig = serde_json::from_str(&saved).unwrap();
+    let action = active_rule(&config.schedules, now).map(|rule| &rule.action);
+    playback(&config, action)
+  }
+
+  #[test]
+  fn pause_ends_after_a_restart_mid_window() {
+    let config = AppConfig {
+      schedules: vec![rule("22:00", "06:00", vec![Weekday::Mon])],
+      ..Default::default()
+    };
+    assert!(after_restart(&config, at(2, 1, 0)).paused);
+    assert!(!after_restart(&config, at(2, 6, 0)).paused);
+  }
+
+  #[test]
+  fn manual_volume_returns_after_a_restart_mid_window() {
+    let mut config = AppConfig {
+      volume: 0.6,
+      schedules: vec![with_action(
+        "22:00",
+        "06:00",
+        vec![Weekday::Mon],
+        ScheduleAction::SetVolume { volume: 0.2 },
+      )],
+      ..Default::default()
+    };
+    assert_eq!(after_restart(&config, at(2, 1, 0)).volume, Some(0.2));
+
+    // The user moves the slider while the window is in effect
+    config.volume = 0.4;
+    assert_eq!(after_restart(&config, at(2, 2, 0)).volume, Some(0.2));
+    let ended = after_restart(&config, at(2, 6, 0));
+    assert_eq!(ended, playback(&config, None));
+    assert_eq!(ended.volume, None);
+  }
+
+  #[test]
+  fn switched_profile_brings_its_pack_until_the_window_ends() {
+    let config = AppConfig {
+      active_pack_id: Some("cherry".into()),
+      profiles: vec![Profile {
+        id: "quiet".into(),
+        name: "Silencioso".into(),
+        pack_id: Some("topre".into()),
+        gain_db: -6.0,
+      }],
+      schedules: vec![with_action(
+        "09:00",
+        "17:00",
+        vec![Weekday::Mon],
+        ScheduleAction::SwitchProfile {
+          profile_id: "quiet".into(),
+        },
+      )],
+      ..Default::default()
+    };
+    let during = after_restart(&config, at(1, 10, 0));
+    assert_eq!(during.profile_id.as_deref(), Some("quiet"));
+    assert_eq!(during.pack_id.as_deref(), Some("topre"));
+
+    let ended = after_restart(&config, at(1, 17, 0));
+    assert_eq!(ended.profile_id, None);
+    assert_eq!(ended.pack_id.as_deref(), Some("cherry"));
+  }
+}
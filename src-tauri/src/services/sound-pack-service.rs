@@ -1,7 +1,9 @@
+use std::collections::BTreeMap;
 use std::fs::{self, File};
 use std::io::{Read, Write};
 use std::path::{Component, Path, PathBuf};
 use std::process::Command;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use flate2::read::GzDecoder;
@@ -11,7 +13,16 @@ use unrar::Archive as UnrarArchive;
 use zip::ZipArchive;
 
 use crate::error::AppError;
-use crate::models::SoundPack;
+use crate::models::{
+  KeyDefineType, PackSettings, SoundPack, TranscodeOptions, TranscodeReport,
+  WayvibesCapabilities,
+};
+use crate::services::audio_service::{self, AudioBuffer};
+use crate::services::pack_manifest::{Define, PackManifest};
+use crate::services::{
+  keymap, pack_formats, pack_settings_service, transcode_service, wayvibes_capabilities,
+};
+use crate::services::wayvibes_locator::WayvibesBinary;
 
 #[derive(Clone, Copy)]
 enum ArchiveType {
@@ -21,6 +32,8 @@ enum ArchiveType {
   Gz,
   Rar,
   SevenZ,
+  /// An unpacked pack folder
+  Directory,
 }
 
 pub fn list_packs(packs_dir: &Path) -> Result<Vec<SoundPack>, AppError> {
@@ -28,6 +41,7 @@ pub fn list_packs(packs_dir: &Path) -> Result<Vec<SoundPack>, AppError> {
     return Ok(Vec::new());
   }
 
+  let mut settings = pack_settings_service::load(packs_dir).unwrap_or_default();
   let mut packs = Vec::new();
   for entry in fs::read_dir(packs_dir)? {
     let entry = entry?;
@@ -42,58 +56,48 @@ pub fn list_packs(packs_dir: &Path) -> Result<Vec<SoundPack>, AppError> {
       .unwrap_or_default()
       .to_string();
 
-    let config_path = path.join("config.json");
-    let config: serde_json::Value = if config_path.exists() {
-      let content = fs::read_to_string(config_path)?;
-      serde_json::from_str(&content)?
-    } else {
+    if !path.join("config.json").exists() {
       continue;
+    }
+    let manifest = match PackManifest::load(&path) {
+      Ok(manifest) => manifest,
+      Err(err) => {
+        log::warn!("Skipping pack {}: {}", pack_id, err);
+        continue;
+      }
     };
 
-    let name = config
-      .get("name")
-      .and_then(|v| v.as_str())
-      .unwrap_or(&pack_id)
-      .to_string();
-    let version = config
-      .get("version")
-      .and_then(|v| v.as_str())
-      .unwrap_or("1.0.0")
-      .to_string();
-    let author = config
-      .get("author")
-      .and_then(|v| v.as_str())
-      .map(|s| s.to_string());
-    let description = config
-      .get("description")
-      .and_then(|v| v.as_str())
-      .map(|s| s.to_string());
-
-    packs.push(SoundPack {
-      id: pack_id,
-      name,
-      version,
-      author,
-      description,
-    });
+    let mut pack = pack_from_manifest(&pack_id, &manifest);
+    pack.settings = settings.remove(&pack_id).unwrap_or_default();
+    packs.push(pack);
   }
   packs.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
   Ok(packs)
 }
 
-pub fn import_pack(archive_path: &Path, packs_dir: &Path) -> Result<SoundPack, AppError> {
-  if !is_wayvibes_installed() {
-    return Err(AppError::WayvibesMissing);
-  }
+/// Extract a pack from an archive or folder into a staging directory. Packs without a
+/// `config.json` get one from the first matching `pack_formats::FORMATS` adapter. With
+/// `transcode` set, the sounds are converted to WAV. The caller installs the result once
+/// wayvibes can load it (see `validate_pack_with_wayvibes`).
+pub fn import_pack(
+  archive_path: &Path,
+  packs_dir: &Path,
+  transcode: Option<&TranscodeOptions>,
+) -> Result<StagedPack<SoundPack>, AppError> {
+  log::info!("Importing {}", archive_path.display());
 
   let archive_type = detect_archive_type(archive_path).ok_or_else(|| {
     AppError::InvalidPack("Formato não suportado".into())
   })?;
 
-  let temp_root = packs_dir.join(".importing");
-  fs::create_dir_all(&temp_root)?;
-  let temp_dir = temp_root.join(temp_dir_name());
-  fs::create_dir_all(&temp_dir)?;
+  let temp_dir = staging_dir(packs_dir)?;
+  let fallback_name = match archive_type {
+    ArchiveType::Directory => archive_path.file_name(),
+    _ => archive_path.file_stem(),
+  }
+  .and_then(|s| s.to_str())
+  .unwrap_or("unknown-pack")
+  .to_string();
 
   let result = (|| {
     extract_archive(archive_path, &temp_dir, archive_type)?;
@@ -102,8 +106,14 @@ pub fn import_pack(archive_path: &Path, packs_dir: &Path) -> Result<SoundPack, A
     let root_config = temp_dir.join("config.json");
     if !root_config.exists() {
       if let Some(config_path) = find_config_in_subdirs(&temp_dir)? {
-        flatten_pack_dir(&temp_dir, &config_path)?;
+        log::debug!("Flattening pack rooted at {}", config_path.display());
+        flatten_pack_dir(&temp_dir, config_dir(&config_path)?)?;
         did_flatten = true;
+      } else if let Some((format, pack_dir)) = pack_formats::find(&temp_dir)? {
+        log::info!("No config.json, importing as a {} pack", format.name());
+        pack_formats::keep_attribution(&temp_dir, &pack_dir)?;
+        flatten_pack_dir(&temp_dir, &pack_dir)?;
+        format.manifest(&temp_dir, &fallback_name)?.save(&temp_dir)?;
       } else {
         return Err(AppError::InvalidPack("config.json não encontrado".into()));
       }
@@ -114,84 +124,432 @@ pub fn import_pack(archive_path: &Path, packs_dir: &Path) -> Result<SoundPack, A
       return Err(AppError::InvalidPack("config.json não encontrado".into()));
     }
 
-    if let Err(err) = validate_pack_with_wayvibes(&temp_dir) {
-      if did_flatten {
-        return Err(AppError::InvalidPack(
+    let manifest = PackManifest::load(&temp_dir)?;
+    let pack_name = manifest.name.clone().unwrap_or(fallback_name);
+
+    let pack_id = slugify(&pack_name);
+
+    let report = match transcode {
+      Some(options) => Some(transcode_service::transcode_dir(&temp_dir, &pack_id, options)?),
+      None => None,
+    };
+
+    let mut pack = pack_from_manifest(&pack_id, &manifest);
+    pack.name = pack_name;
+    pack.transcode = report;
+    Ok((pack, did_flatten))
+  })();
+
+  match result {
+    Ok((pack, did_flatten)) => {
+      log::info!("Staged pack '{}' as {}", pack.name, pack.id);
+      let pack_id = pack.id.clone();
+      let staged = StagedPack::new(temp_dir, packs_dir, &pack_id, pack);
+      Ok(if did_flatten {
+        staged.with_rejection(
           "Pacote inválido. Tentamos corrigir a estrutura, mas o wayvibes não conseguiu ler. Ajuste manual necessário.".into(),
-        ));
-      }
-      return Err(err);
+        )
+      } else {
+        staged
+      })
     }
+    Err(err) => {
+      log::warn!("Import of {} failed: {}", archive_path.display(), err);
+      let _ = fs::remove_dir_all(&temp_dir);
+      Err(err)
+    }
+  }
+}
+
+pub fn delete_pack(pack_id: &str, packs_dir: &Path) -> Result<(), AppError> {
+  let target_dir = packs_dir.join(pack_id);
+  if !target_dir.exists() {
+    return Err(AppError::InvalidPack("Pacote não encontrado".into()));
+  }
+  fs::remove_dir_all(target_dir)?;
+  if let Err(err) = pack_settings_service::remove(packs_dir, pack_id) {
+    log::warn!("Failed to drop settings of pack {}: {}", pack_id, err);
+  }
+  log::info!("Deleted pack {}", pack_id);
+  Ok(())
+}
 
-    let config: serde_json::Value = serde_json::from_str(&fs::read_to_string(&config_path)?)?;
-
-    let pack_name = config
-      .get("name")
-      .and_then(|v| v.as_str())
-      .map(|s| s.to_string())
-      .unwrap_or_else(|| {
-        archive_path
-          .file_stem()
-          .and_then(|s| s.to_str())
-          .unwrap_or("unknown-pack")
-          .to_string()
+/// Copy a pack into a new one with the other key define type: a single pack's sprite is
+/// cut into one WAV per key, or a multi pack's files are joined into one sprite with a
+/// generated `defines` table. The decoded samples are written out unchanged, so WAV and
+/// FLAC sounds come out exact. OGG and MP3 sounds can't be cut without decoding them:
+/// they are stored as their decoded samples in 32-bit float WAV, which sounds the same but
+/// is larger, and the returned pack's `transcode` reports how many there were.
+pub fn convert_pack(
+  packs_dir: &Path,
+  pack_id: &str,
+) -> Result<StagedPack<SoundPack>, AppError> {
+  let source_dir = packs_dir.join(pack_id);
+  if !source_dir.join("config.json").exists() {
+    return Err(AppError::InvalidPack("Pacote não encontrado".into()));
+  }
+  let source = PackManifest::load(&source_dir)?;
+  source.validate(&source_dir)?;
+
+  let base_name = source.name.clone().unwrap_or_else(|| pack_id.to_string());
+  let (target_type, suffix) = match source.key_define_type {
+    KeyDefineType::Single => (KeyDefineType::Multi, "multi"),
+    KeyDefineType::Multi => (KeyDefineType::Single, "single"),
+  };
+  let name = format!("{} ({})", base_name, suffix);
+  let new_id = unique_pack_id(packs_dir, &slugify(&name));
+  log::info!("Converting pack {} into {} pack {}", pack_id, suffix, new_id);
+
+  let staged = staging_dir(packs_dir)?;
+  let result = (|| {
+    let mut manifest = source.clone();
+    manifest.id = Some(new_id.clone());
+    manifest.name = Some(name.clone());
+    manifest.key_define_type = target_type;
+    let decoded = match target_type {
+      KeyDefineType::Multi => split_sprite(&mut manifest, &source_dir, &staged)?,
+      KeyDefineType::Single => join_sprite(&mut manifest, &source_dir, &staged)?,
+    };
+    manifest.validate(&staged)?;
+    manifest.save(&staged)?;
+
+    let mut pack = read_staged(&staged, &new_id)?;
+    if decoded > 0 {
+      log::warn!(
+        "Pack {} has {} compressed sounds; pack {} stores them decoded",
+        pack_id,
+        decoded,
+        new_id
+      );
+      pack.transcode = Some(TranscodeReport {
+        pack_id: new_id.clone(),
+        files: source.files().len(),
+        converted: decoded,
+        bytes_before: audio_bytes(&source_dir, &source)?,
+        bytes_after: audio_bytes(&staged, &manifest)?,
       });
+    }
+    Ok(pack)
+  })();
 
-    let pack_id = slugify(&pack_name);
-    if pack_id.is_empty() {
-      return Err(AppError::InvalidPack("Nome do pacote inválido".into()));
+  match result {
+    Ok(mut pack) => {
+      // The audio sounds the same, so the gain and default volume still fit
+      let settings = pack_settings_service::get(packs_dir, pack_id);
+      pack.settings = settings.clone();
+      let converted = StagedPack::new(staged, packs_dir, &new_id, pack);
+      Ok(if settings == PackSettings::default() {
+        converted
+      } else {
+        converted.with_settings(settings)
+      })
+    }
+    Err(err) => {
+      log::warn!("Conversion of pack {} failed: {}", pack_id, err);
+      let _ = fs::remove_dir_all(&staged);
+      Err(err)
     }
+  }
+}
 
-    let target_dir = packs_dir.join(&pack_id);
-    if target_dir.exists() {
-      return Err(AppError::InvalidPack(format!(
-        "Pacote '{}' já existe",
-        pack_id
-      )));
+/// Write each slice of a single pack's sprite to its own WAV in `target_dir` and point the
+/// keys at the files. Keys sharing a slice share the file. Returns 1 when the sprite was
+/// compressed, so its slices hold decoded samples, and 0 otherwise.
+pub fn split_sprite(
+  manifest: &mut PackManifest,
+  source_dir: &Path,
+  target_dir: &Path,
+) -> Result<usize, AppError> {
+  let sprite_name = manifest
+    .sound
+    .take()
+    .ok_or_else(|| AppError::InvalidPack("Pacote single sem 'sound'".into()))?;
+  let sprite = audio_service::decode(&pack_file(source_dir, &sprite_name)?)?;
+
+  let mut files: BTreeMap<(usize, usize), String> = BTreeMap::new();
+  for (table, suffix) in [(&mut manifest.defines, ""), (&mut manifest.defines_up, "-up")] {
+    for (code, define) in table.iter_mut() {
+      let Some(define) = define else {
+        continue;
+      };
+      for (index, sound) in define.sounds_mut().into_iter().enumerate() {
+        let Define::Slice([offset_ms, duration_ms]) = *sound else {
+          continue;
+        };
+        let (start, len) = (sprite.ms_to_frame(offset_ms), sprite.ms_to_frame(duration_ms));
+        let file = match files.get(&(start, len)) {
+          Some(file) => file.clone(),
+          None => {
+            let variant = if index == 0 {
+              String::new()
+            } else {
+              format!("-{}", index + 1)
+            };
+            let file = format!("{}{}{}.wav", key_file_stem(*code), suffix, variant);
+            audio_service::write_wav(&target_dir.join(&file), &sprite.slice(start, len))?;
+            files.insert((start, len), file.clone());
+            file
+          }
+        };
+        *sound = Define::File(file);
+      }
     }
+  }
+  Ok(usize::from(sprite.bits_per_sample.is_none()))
+}
 
-    let version = config
-      .get("version")
-      .and_then(|v| v.as_str())
-      .unwrap_or("1.0.0")
-      .to_string();
-    let author = config
-      .get("author")
-      .and_then(|v| v.as_str())
-      .map(|s| s.to_string());
-    let description = config
-      .get("description")
-      .and_then(|v| v.as_str())
-      .map(|s| s.to_string());
-
-    fs::rename(&temp_dir, &target_dir)?;
-
-    Ok(SoundPack {
-      id: pack_id,
-      name: pack_name,
-      version,
-      author,
-      description,
-    })
-  })();
+/// Concatenate every file the keys of a multi pack play into `sound.wav` and point each
+/// key at its slice. Returns how many of the files were compressed.
+fn join_sprite(
+  manifest: &mut PackManifest,
+  source_dir: &Path,
+  target_dir: &Path,
+) -> Result<usize, AppError> {
+  // Multi packs often keep a leftover `sound` that no key plays, so only the keys count
+  let mut decoded = BTreeMap::new();
+  for sound in manifest.sounds() {
+    if let Define::File(file) = sound {
+      if !decoded.contains_key(file) {
+        decoded.insert(file.clone(), audio_service::decode(&pack_file(source_dir, file)?)?);
+      }
+    }
+  }
 
-  if result.is_err() {
-    let _ = fs::remove_dir_all(&temp_dir);
+  let first = decoded
+    .values()
+    .next()
+    .ok_or_else(|| AppError::InvalidPack("Pacote sem sons".into()))?;
+  let channels = decoded.values().map(|buffer| buffer.channels).max().unwrap_or(1);
+  let mut sprite = AudioBuffer::empty(first.sample_rate, channels);
+
+  let mut slices = BTreeMap::new();
+  for (file, buffer) in &decoded {
+    let start = sprite.frames();
+    sprite
+      .append(buffer)
+      .map_err(|err| AppError::InvalidPack(format!("{}: {}", file, err)))?;
+    let offset_ms = sprite.frame_to_ms(start);
+    let duration_ms = sprite.frame_to_ms(sprite.frames()) - offset_ms;
+    slices.insert(file.clone(), [offset_ms, duration_ms]);
   }
 
-  result
+  let sound = "sound.wav".to_string();
+  audio_service::write_wav(&target_dir.join(&sound), &sprite)?;
+  manifest.sound = Some(sound);
+
+  for sound in manifest.sounds_mut() {
+    let slice = match sound {
+      Define::File(file) => slices.get(file.as_str()).copied(),
+      _ => None,
+    };
+    if let Some(slice) = slice {
+      *sound = Define::Slice(slice);
+    }
+  }
+  Ok(decoded.values().filter(|buffer| buffer.bits_per_sample.is_none()).count())
 }
 
-pub fn delete_pack(pack_id: &str, packs_dir: &Path) -> Result<(), AppError> {
-  let target_dir = packs_dir.join(pack_id);
-  if !target_dir.exists() {
+/// Size of the audio files a pack plays
+fn audio_bytes(pack_dir: &Path, manifest: &PackManifest) -> Result<u64, AppError> {
+  let mut bytes = 0;
+  for file in manifest.files() {
+    bytes += fs::metadata(pack_file(pack_dir, &file)?)?.len();
+  }
+  Ok(bytes)
+}
+
+/// File name for a key's sound, e.g. `enter` or `key-3612` for codes the app doesn't know
+fn key_file_stem(code: u32) -> String {
+  keymap::by_code(code)
+    .map(|key| slugify(key.evdev_name))
+    .unwrap_or_else(|| format!("key-{}", code))
+}
+
+/// `base`, or `base-2`, `base-3`... when a pack with that id already exists
+pub fn unique_pack_id(packs_dir: &Path, base: &str) -> String {
+  let mut pack_id = base.to_string();
+  let mut suffix = 2;
+  while packs_dir.join(&pack_id).exists() {
+    pack_id = format!("{}-{}", base, suffix);
+    suffix += 1;
+  }
+  pack_id
+}
+
+/// Metadata of an installed pack
+pub fn read_pack(packs_dir: &Path, pack_id: &str) -> Result<SoundPack, AppError> {
+  let pack_dir = packs_dir.join(pack_id);
+  if !pack_dir.join("config.json").exists() {
     return Err(AppError::InvalidPack("Pacote não encontrado".into()));
   }
-  fs::remove_dir_all(target_dir)?;
+  let mut pack = pack_from_manifest(pack_id, &PackManifest::load(&pack_dir)?);
+  pack.settings = pack_settings_service::get(packs_dir, pack_id);
+  Ok(pack)
+}
+
+/// `read_pack` for a pack still in its staging directory, before it has any settings
+pub fn read_staged(staged: &Path, pack_id: &str) -> Result<SoundPack, AppError> {
+  Ok(pack_from_manifest(pack_id, &PackManifest::load(staged)?))
+}
+
+/// Audio files a pack's config.json refers to, through `sound` or the `defines` table
+pub fn audio_files(pack_dir: &Path) -> Result<Vec<PathBuf>, AppError> {
+  Ok(
+    PackManifest::load(pack_dir)?
+      .files()
+      .into_iter()
+      .filter_map(|name| pack_file(pack_dir, &name).ok())
+      .filter(|path| path.is_file() && audio_service::is_audio_file(path))
+      .collect(),
+  )
+}
+
+/// Path of a file named in a pack's config, refusing names that escape the pack directory
+pub fn pack_file(pack_dir: &Path, name: &str) -> Result<PathBuf, AppError> {
+  Ok(pack_dir.join(sanitize_archive_path(Path::new(name))?))
+}
+
+/// Create an empty directory under `packs_dir/.importing` to assemble a pack in
+pub fn staging_dir(packs_dir: &Path) -> Result<PathBuf, AppError> {
+  let temp_dir = packs_dir.join(".importing").join(temp_dir_name());
+  fs::create_dir_all(&temp_dir)?;
+  Ok(temp_dir)
+}
+
+/// Move an assembled pack into the library under `pack_id`
+pub fn install_staged(staged: &Path, packs_dir: &Path, pack_id: &str) -> Result<(), AppError> {
+  if pack_id.is_empty() {
+    return Err(AppError::InvalidPack("Nome do pacote inválido".into()));
+  }
+
+  let target_dir = packs_dir.join(pack_id);
+  if target_dir.exists() {
+    return Err(AppError::InvalidPack(format!(
+      "Pacote '{}' já existe",
+      pack_id
+    )));
+  }
+
+  fs::rename(staged, &target_dir)?;
+  Ok(())
+}
+
+/// Swap an installed pack for an assembled one, putting the old pack back if the move fails
+pub fn replace_pack(staged: &Path, packs_dir: &Path, pack_id: &str) -> Result<(), AppError> {
+  let target_dir = packs_dir.join(pack_id);
+  let backup = packs_dir.join(".importing").join(format!("{}-old", temp_dir_name()));
+  fs::rename(&target_dir, &backup)?;
+  if let Err(err) = fs::rename(staged, &target_dir) {
+    fs::rename(&backup, &target_dir)?;
+    return Err(err.into());
+  }
+  if let Err(err) = fs::remove_dir_all(&backup) {
+    log::warn!("Could not remove {}: {}", backup.display(), err);
+  }
+  log::info!("Replaced pack {}", pack_id);
   Ok(())
 }
 
+/// A pack assembled under `.importing` that has yet to pass its checks. Nothing reaches
+/// the library until `install`; `reject` throws the directory away.
+pub struct StagedPack<T> {
+  pub dir: PathBuf,
+  pub pack_id: String,
+  packs_dir: PathBuf,
+  /// Swap out the installed pack with the same id instead of adding a new one
+  replace: bool,
+  /// Saved for the pack once it is installed
+  settings: Option<PackSettings>,
+  /// Reported instead of the validation error when it explains the failure better
+  rejection: Option<String>,
+  value: T,
+}
+
+impl<T> StagedPack<T> {
+  pub fn new(dir: PathBuf, packs_dir: &Path, pack_id: &str, value: T) -> Self {
+    Self {
+      dir,
+      pack_id: pack_id.to_string(),
+      packs_dir: packs_dir.to_path_buf(),
+      replace: false,
+      settings: None,
+      rejection: None,
+      value,
+    }
+  }
+
+  pub fn replacing(mut self) -> Self {
+    self.replace = true;
+    self
+  }
+
+  pub fn with_settings(mut self, settings: PackSettings) -> Self {
+    self.settings = Some(settings);
+    self
+  }
+
+  pub fn with_rejection(mut self, message: String) -> Self {
+    self.rejection = Some(message);
+    self
+  }
+
+  /// Move the pack into the library and return what building it produced
+  pub fn install(self) -> Result<T, AppError> {
+    let result = if self.replace {
+      replace_pack(&self.dir, &self.packs_dir, &self.pack_id)
+    } else {
+      install_staged(&self.dir, &self.packs_dir, &self.pack_id)
+    };
+    if let Err(err) = result {
+      log::warn!("Installing pack {} failed: {}", self.pack_id, err);
+      let _ = fs::remove_dir_all(&self.dir);
+      return Err(err);
+    }
+
+    if let Some(settings) = &self.settings {
+      if let Err(err) = pack_settings_service::save(&self.packs_dir, &self.pack_id, settings) {
+        log::warn!("Failed to save settings of pack {}: {}", self.pack_id, err);
+      }
+    }
+    log::info!("Installed pack {}", self.pack_id);
+    Ok(self.value)
+  }
+
+  /// Drop the pack after validation turned it down, returning the error to report
+  pub fn reject(self, err: AppError) -> AppError {
+    log::warn!("Pack {} rejected: {}", self.pack_id, err);
+    let _ = fs::remove_dir_all(&self.dir);
+    match self.rejection {
+      Some(message) => AppError::InvalidPack(message),
+      None => err,
+    }
+  }
+}
+
+/// Copy the contents of `source` into the existing directory `target`
+pub fn copy_dir(source: &Path, target: &Path) -> Result<(), AppError> {
+  for entry in fs::read_dir(source)? {
+    let entry = entry?;
+    let path = entry.path();
+    let dest = target.join(entry.file_name());
+    if entry.file_type()?.is_dir() {
+      fs::create_dir_all(&dest)?;
+      copy_dir(&path, &dest)?;
+    } else {
+      fs::copy(&path, &dest)?;
+    }
+  }
+  Ok(())
+}
+
+pub fn is_supported_archive(path: &Path) -> bool {
+  detect_archive_type(path).is_some()
+}
+
 fn detect_archive_type(path: &Path) -> Option<ArchiveType> {
+  if path.is_dir() {
+    return Some(ArchiveType::Directory);
+  }
   let name = path.file_name()?.to_string_lossy().to_lowercase();
   if name.ends_with(".zip") {
     Some(ArchiveType::Zip)
@@ -222,6 +580,7 @@ fn extract_archive(
     ArchiveType::Gz => extract_gz(archive_path, dest),
     ArchiveType::Rar => extract_rar(archive_path, dest),
     ArchiveType::SevenZ => extract_7z(archive_path, dest),
+    ArchiveType::Directory => copy_dir(archive_path, dest),
   }
 }
 
@@ -346,11 +705,15 @@ fn find_config_recursive(dir: &Path) -> Result<Option<PathBuf>, AppError> {
   Ok(None)
 }
 
-fn flatten_pack_dir(root_dir: &Path, config_path: &Path) -> Result<(), AppError> {
-  let config_dir = config_path
+fn config_dir(config_path: &Path) -> Result<&Path, AppError> {
+  config_path
     .parent()
-    .ok_or_else(|| AppError::InvalidPack("config.json inválido".into()))?;
-  if config_dir == root_dir {
+    .ok_or_else(|| AppError::InvalidPack("config.json inválido".into()))
+}
+
+/// Make `pack_dir`, somewhere below `root_dir`, the root of the extracted pack
+fn flatten_pack_dir(root_dir: &Path, pack_dir: &Path) -> Result<(), AppError> {
+  if pack_dir == root_dir {
     return Ok(());
   }
 
@@ -364,7 +727,7 @@ fn flatten_pack_dir(root_dir: &Path, config_path: &Path) -> Result<(), AppError>
     .join(format!("{}-flat", root_name));
 
   fs::create_dir_all(&flat_dir)?;
-  for entry in fs::read_dir(config_dir)? {
+  for entry in fs::read_dir(pack_dir)? {
     let entry = entry?;
     let from = entry.path();
     let name = entry.file_name();
@@ -378,53 +741,57 @@ fn flatten_pack_dir(root_dir: &Path, config_path: &Path) -> Result<(), AppError>
   Ok(())
 }
 
-fn validate_pack_with_wayvibes(pack_path: &Path) -> Result<(), AppError> {
+/// Briefly run wayvibes on a pack in the background and stop the instances it started.
+/// `owned` is the app's own wayvibes, which is left running whatever pgrep reports.
+pub fn validate_pack_with_wayvibes(
+  binary: &WayvibesBinary,
+  pack_path: &Path,
+  owned: Option<u32>,
+) -> Result<(), AppError> {
   let path = pack_path
     .to_str()
     .ok_or_else(|| AppError::InvalidPack("Caminho inválido".into()))?;
 
-  let before_pid = get_running_pid().unwrap_or(None);
-  let status = Command::new("wayvibes")
+  let caps = wayvibes_capabilities::probe(binary);
+  wayvibes_capabilities::require(&caps, caps.background, "execução em segundo plano (--background)")?;
+
+  let before = running_pids(binary);
+  let status = binary
+    .command()
     .arg(path)
     .arg("-v")
     .arg("1.0")
     .arg("--background")
+    .args(&binary.extra_args)
     .status()
     .map_err(AppError::Io)?;
 
   if !status.success() {
+    log::debug!("Validation run of {} exited with {}", path, status);
     return Err(AppError::InvalidPack(
       "Pacote inválido (wayvibes não conseguiu iniciar)".into(),
     ));
   }
 
-  if let Ok(after_pid) = get_running_pid() {
-    if after_pid.is_some() && after_pid != before_pid {
-      let _ = stop_pid(after_pid.unwrap());
+  for pid in running_pids(binary) {
+    if !before.contains(&pid) && Some(pid) != owned {
+      let _ = stop_pid(pid);
     }
   }
 
   Ok(())
 }
 
-fn get_running_pid() -> Result<Option<u32>, AppError> {
-  let output = Command::new("pgrep")
-    .arg("-nx")
-    .arg("wayvibes")
-    .output()
-    .map_err(AppError::Io)?;
-
-  if !output.status.success() {
-    return Ok(None);
-  }
-
-  let raw = String::from_utf8_lossy(&output.stdout).trim().to_string();
-  if raw.is_empty() {
-    return Ok(None);
-  }
-  raw.parse::<u32>()
-    .map(Some)
-    .map_err(|_| AppError::InvalidPack("PID inválido".into()))
+/// Every running process with the binary's name; empty when pgrep finds none or fails
+fn running_pids(binary: &WayvibesBinary) -> Vec<u32> {
+  let output = match Command::new("pgrep").arg("-x").arg(binary.process_name()).output() {
+    Ok(output) if output.status.success() => output,
+    _ => return Vec::new(),
+  };
+  String::from_utf8_lossy(&output.stdout)
+    .lines()
+    .filter_map(|line| line.trim().parse().ok())
+    .collect()
 }
 
 fn stop_pid(pid: u32) -> Result<(), AppError> {
@@ -435,14 +802,6 @@ fn stop_pid(pid: u32) -> Result<(), AppError> {
   Ok(())
 }
 
-fn is_wayvibes_installed() -> bool {
-  Command::new("which")
-    .arg("wayvibes")
-    .output()
-    .map(|output| output.status.success())
-    .unwrap_or(false)
-}
-
 fn sanitize_archive_path(path: &Path) -> Result<PathBuf, AppError> {
   let mut result = PathBuf::new();
   for component in path.components() {
@@ -460,14 +819,59 @@ fn sanitize_archive_path(path: &Path) -> Result<PathBuf, AppError> {
 }
 
 fn temp_dir_name() -> String {
+  static COUNTER: AtomicU64 = AtomicU64::new(0);
   let timestamp = SystemTime::now()
     .duration_since(UNIX_EPOCH)
     .map(|d| d.as_millis())
     .unwrap_or(0);
-  format!("tmp-{}", timestamp)
+  format!("tmp-{}-{}", timestamp, COUNTER.fetch_add(1, Ordering::Relaxed))
 }
 
-fn slugify(name: &str) -> String {
+fn pack_from_manifest(pack_id: &str, manifest: &PackManifest) -> SoundPack {
+  let (variant_keys, max_variants) = manifest.variant_counts();
+  SoundPack {
+    id: pack_id.to_string(),
+    name: manifest.name.clone().unwrap_or_else(|| pack_id.to_string()),
+    version: manifest.text("version").unwrap_or_else(|| "1.0.0".into()),
+    author: manifest.text("author"),
+    description: manifest.text("description"),
+    config_version: manifest.config_version(),
+    supports_keyup: manifest.keyup_keys() > 0,
+    variant_keys,
+    max_variants,
+    warnings: Vec::new(),
+    settings: PackSettings::default(),
+    transcode: None,
+  }
+}
+
+/// Features `pack` uses that the probed wayvibes can't play, as messages for the UI
+pub fn unsupported_features(
+  pack: &SoundPack,
+  capabilities: &WayvibesCapabilities,
+) -> Vec<String> {
+  let mut warnings = Vec::new();
+  if let Some(version) = &pack.config_version {
+    if version != "1" && version != "2" {
+      warnings.push(format!("config_version {} desconhecida", version));
+    }
+  }
+  if !capabilities.probed {
+    return warnings;
+  }
+  if pack.supports_keyup && !capabilities.keyup {
+    warnings.push("Sons de soltar tecla não são suportados por esta versão do wayvibes".into());
+  }
+  if pack.variant_keys > 0 && !capabilities.variations {
+    warnings.push(format!(
+      "Variações por tecla ({} teclas) não são suportadas por esta versão do wayvibes",
+      pack.variant_keys
+    ));
+  }
+  warnings
+}
+
+pub fn slugify(name: &str) -> String {
   let mut result = String::new();
   let mut prev_dash = false;
   for ch in name.chars() {
@@ -481,3 +885,63 @@ fn slugify(name: &str) -> String {
   }
   result.trim_matches('-').to_string()
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn split_then_join_keeps_every_sample() {
+    let root =
+      std::env::temp_dir().join(format!("wayvibes-ui-sprite-roundtrip-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    let (single_dir, multi_dir, joined_dir) =
+      (root.join("single"), root.join("multi"), root.join("joined"));
+    for dir in [&single_dir, &multi_dir, &joined_dir] {
+      fs::create_dir_all(dir).unwrap();
+    }
+
+    // 30 ms of distinct 16-bit values at 8 kHz
+    let mut sprite = AudioBuffer::empty(8_000, 1);
+    sprite.samples = (0..240)
+      .map(|index| (index as f32 * 100.0 - 12_000.0) / 32768.0)
+      .collect();
+    audio_service::write_wav(&single_dir.join("sprite.wav"), &sprite).unwrap();
+
+    let mut manifest = PackManifest {
+      key_define_type: KeyDefineType::Single,
+      sound: Some("sprite.wav".into()),
+      ..Default::default()
+    };
+    manifest.defines.insert(30, Some(Define::Slice([0.0, 10.0])));
+    manifest.defines.insert(31, Some(Define::Slice([10.0, 10.0])));
+    manifest.defines.insert(28, Some(Define::Slice([20.0, 10.0])));
+    let original = manifest.clone();
+
+    assert_eq!(split_sprite(&mut manifest, &single_dir, &multi_dir).unwrap(), 0);
+    manifest.key_define_type = KeyDefineType::Multi;
+    assert_eq!(join_sprite(&mut manifest, &multi_dir, &joined_dir).unwrap(), 0);
+
+    let before = audio_service::decode(&single_dir.join("sprite.wav")).unwrap();
+    let after = audio_service::decode(&joined_dir.join("sound.wav")).unwrap();
+    let _ = fs::remove_dir_all(&root);
+
+    let samples = |buffer: &AudioBuffer, define: &Option<Define>| match define {
+      Some(Define::Slice([offset_ms, duration_ms])) => {
+        buffer
+          .slice(buffer.ms_to_frame(*offset_ms), buffer.ms_to_frame(*duration_ms))
+          .samples
+      }
+      other => panic!("not a slice: {:?}", other),
+    };
+    assert_eq!(after.samples.len(), before.samples.len());
+    for (code, define) in &original.defines {
+      assert_eq!(
+        samples(&before, define),
+        samples(&after, &manifest.defines[code]),
+        "key {}",
+        code
+      );
+    }
+  }
+}
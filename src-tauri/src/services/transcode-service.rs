@@ -0,0 +1,275 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+
+use crate::error::AppError;
+use crate::models::{TranscodeOptions, TranscodeReport};
+use crate::services::audio_service::{self, AudioBuffer};
+use crate::services::pack_manifest::PackManifest;
+use crate::services::sound_pack_service::{self, StagedPack};
+
+const MIN_SAMPLE_RATE: u32 = 8_000;
+const MAX_SAMPLE_RATE: u32 = 192_000;
+const CANONICAL_BITS: u32 = 16;
+
+pub fn validate_options(options: &TranscodeOptions) -> Result<(), AppError> {
+  if let Some(rate) = options.sample_rate {
+    if !(MIN_SAMPLE_RATE..=MAX_SAMPLE_RATE).contains(&rate) {
+      return Err(AppError::InvalidConfig(format!(
+        "Taxa de amostragem fora do intervalo {}-{} Hz",
+        MIN_SAMPLE_RATE, MAX_SAMPLE_RATE
+      )));
+    }
+  }
+  Ok(())
+}
+
+/// Rewrite every sound a pack refers to as 16-bit PCM WAV and point `config.json` at the
+/// new files. Works in place, so callers hand it a staged copy of the pack.
+pub fn transcode_dir(
+  pack_dir: &Path,
+  pack_id: &str,
+  options: &TranscodeOptions,
+) -> Result<TranscodeReport, AppError> {
+  validate_options(options)?;
+  let mut manifest = PackManifest::load(pack_dir)?;
+  let files = manifest.files();
+
+  let mut taken: BTreeSet<String> = files.iter().map(|file| file.to_lowercase()).collect();
+  let mut renamed = BTreeMap::new();
+  let (mut bytes_before, mut bytes_after, mut converted) = (0, 0, 0);
+
+  for file in &files {
+    let source = sound_pack_service::pack_file(pack_dir, file)?;
+    bytes_before += fs::metadata(&source)?.len();
+
+    let buffer = audio_service::decode(&source)?;
+    let rate_matches = options
+      .sample_rate
+      .map(|rate| rate == buffer.sample_rate)
+      .unwrap_or(true);
+    if is_wav(&source) && rate_matches && buffer.bits_per_sample == Some(CANONICAL_BITS) {
+      bytes_after += fs::metadata(&source)?.len();
+      continue;
+    }
+
+    let mut buffer = match options.sample_rate {
+      Some(rate) => buffer.resample(rate),
+      None => buffer,
+    };
+    buffer.bits_per_sample = Some(CANONICAL_BITS);
+
+    let target_name = replace_with_wav(pack_dir, file, &buffer, &mut taken)?;
+    bytes_after += fs::metadata(sound_pack_service::pack_file(pack_dir, &target_name)?)?.len();
+    converted += 1;
+    if target_name != *file {
+      renamed.insert(file.clone(), target_name);
+    }
+  }
+
+  if !renamed.is_empty() {
+    manifest.rename_files(&renamed);
+    manifest.save(pack_dir)?;
+  }
+
+  log::info!(
+    "Transcoded {} of {} files of pack {}: {} -> {} bytes",
+    converted,
+    files.len(),
+    pack_id,
+    bytes_before,
+    bytes_after
+  );
+  Ok(TranscodeReport {
+    pack_id: pack_id.to_string(),
+    files: files.len(),
+    converted,
+    bytes_before,
+    bytes_after,
+  })
+}
+
+/// Convert an installed pack. The work happens on a staged copy that replaces the pack
+/// once it is installed.
+pub fn normalize_pack(
+  packs_dir: &Path,
+  pack_id: &str,
+  options: &TranscodeOptions,
+) -> Result<StagedPack<TranscodeReport>, AppError> {
+  let pack_dir = packs_dir.join(pack_id);
+  if !pack_dir.join("config.json").exists() {
+    return Err(AppError::InvalidPack("Pacote não encontrado".into()));
+  }
+  validate_options(options)?;
+
+  let staged = sound_pack_service::staging_dir(packs_dir)?;
+  let result = (|| {
+    sound_pack_service::copy_dir(&pack_dir, &staged)?;
+    let report = transcode_dir(&staged, pack_id, options)?;
+    PackManifest::load(&staged)?.validate(&staged)?;
+    Ok(report)
+  })();
+
+  match result {
+    Ok(report) => Ok(StagedPack::new(staged, packs_dir, pack_id, report).replacing()),
+    Err(err) => {
+      log::warn!("Normalizing pack {} failed: {}", pack_id, err);
+      let _ = fs::remove_dir_all(&staged);
+      Err(err)
+    }
+  }
+}
+
+/// Overwrite a pack's sound with `buffer` as WAV and return the file's new name. A WAV
+/// keeps its name; other formats get a `.wav` name that no file of the pack uses yet.
+/// `taken` holds the lowercased names already in use.
+pub fn replace_with_wav(
+  pack_dir: &Path,
+  file: &str,
+  buffer: &AudioBuffer,
+  taken: &mut BTreeSet<String>,
+) -> Result<String, AppError> {
+  let source = sound_pack_service::pack_file(pack_dir, file)?;
+  let target_name = if is_wav(&source) {
+    file.to_string()
+  } else {
+    wav_name(pack_dir, file, taken)
+  };
+
+  let target = sound_pack_service::pack_file(pack_dir, &target_name)?;
+  let temp = target.with_extension("wav.tmp");
+  audio_service::write_wav(&temp, buffer)?;
+  if target != source {
+    fs::remove_file(&source)?;
+  }
+  fs::rename(&temp, &target)?;
+  Ok(target_name)
+}
+
+fn is_wav(path: &Path) -> bool {
+  path
+    .extension()
+    .and_then(|ext| ext.to_str())
+    .map(|ext| ext.eq_ignore_ascii_case("wav"))
+    .unwrap_or(false)
+}
+
+/// `sounds/click.ogg` becomes `sounds/click.wav`, or `sounds/click-2.wav` when another
+/// file of the pack already has that name
+fn wav_name(pack_dir: &Path, file: &str, taken: &mut BTreeSet<String>) -> String {
+  let stem = Path::new(file).with_extension("");
+  let stem = stem.to_string_lossy();
+  let mut name = format!("{}.wav", stem);
+  let mut suffix = 2;
+  while taken.contains(&name.to_lowercase()) || pack_dir.join(&name).exists() {
+    name = format!("{}-{}.wav", stem, suffix);
+    suffix += 1;
+  }
+  taken.insert(name.to_lowercase());
+  name
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::models::KeyDefineType;
+  use crate::services::pack_manifest::Define;
+
+  /// A multi pack with a 16-bit WAV on A and a 32-bit float WAV on S, 10 ms at 8 kHz each
+  fn pack(test: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("wayvibes-ui-{}-{}", test, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let mut click = AudioBuffer::empty(8_000, 1);
+    click.samples = vec![0.5; 80];
+    audio_service::write_wav(&dir.join("int.wav"), &click).unwrap();
+    click.bits_per_sample = None;
+    audio_service::write_wav(&dir.join("float.wav"), &click).unwrap();
+
+    PackManifest {
+      key_define_type: KeyDefineType::Multi,
+      defines: BTreeMap::from([
+        (30, Some(Define::File("int.wav".into()))),
+        (31, Some(Define::File("float.wav".into()))),
+      ]),
+      ..Default::default()
+    }
+    .save(&dir)
+    .unwrap();
+    dir
+  }
+
+  #[test]
+  fn resample_interpolates_between_frames() {
+    let mut buffer = AudioBuffer::empty(8_000, 1);
+    buffer.samples = vec![0.0, 0.5, 1.0, 0.5];
+
+    let up = buffer.resample(16_000);
+    assert_eq!(up.sample_rate, 16_000);
+    assert_eq!(up.samples, vec![0.0, 0.25, 0.5, 0.75, 1.0, 0.75, 0.5, 0.5]);
+    assert_eq!(up.bits_per_sample, None);
+
+    let down = buffer.resample(4_000);
+    assert_eq!(down.samples, vec![0.0, 1.0]);
+
+    let same = buffer.resample(8_000);
+    assert_eq!(same.samples, buffer.samples);
+    assert_eq!(same.bits_per_sample, Some(16));
+  }
+
+  #[test]
+  fn resample_keeps_channels_apart() {
+    let mut buffer = AudioBuffer::empty(8_000, 2);
+    buffer.samples = vec![0.0, 1.0, 0.5, -1.0];
+
+    let up = buffer.resample(16_000);
+    assert_eq!(up.channels, 2);
+    assert_eq!(up.samples, vec![0.0, 1.0, 0.25, 0.0, 0.5, -1.0, 0.5, -1.0]);
+  }
+
+  #[test]
+  fn converts_only_files_that_are_not_16_bit_wav() {
+    let dir = pack("transcode-bits");
+
+    let report = transcode_dir(&dir, "bits", &TranscodeOptions { sample_rate: None }).unwrap();
+    assert_eq!((report.files, report.converted), (2, 1));
+    assert!(report.bytes_after < report.bytes_before);
+    let converted = audio_service::decode(&dir.join("float.wav")).unwrap();
+    assert_eq!(converted.bits_per_sample, Some(16));
+    assert_eq!(converted.sample_rate, 8_000);
+    assert_eq!(converted.samples, vec![0.5; 80]);
+    assert_eq!(PackManifest::load(&dir).unwrap().files(), ["float.wav", "int.wav"]);
+
+    fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn resamples_every_file_to_the_chosen_rate() {
+    let dir = pack("transcode-rate");
+    let options = TranscodeOptions {
+      sample_rate: Some(16_000),
+    };
+
+    let report = transcode_dir(&dir, "rate", &options).unwrap();
+    assert_eq!((report.files, report.converted), (2, 2));
+    for file in ["int.wav", "float.wav"] {
+      let buffer = audio_service::decode(&dir.join(file)).unwrap();
+      assert_eq!(buffer.sample_rate, 16_000);
+      assert_eq!(buffer.frames(), 160);
+      assert_eq!(buffer.bits_per_sample, Some(16));
+    }
+
+    fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn rejects_rates_out_of_range() {
+    let options = |rate| TranscodeOptions {
+      sample_rate: Some(rate),
+    };
+    assert!(validate_options(&options(4_000)).is_err());
+    assert!(validate_options(&options(384_000)).is_err());
+    assert!(validate_options(&options(44_100)).is_ok());
+    assert!(validate_options(&TranscodeOptions { sample_rate: None }).is_ok());
+  }
+}
@@ -0,0 +1,85 @@
+use crate::error::AppError;
+use crate::models::{AppConfig, PackSettings, Profile, VolumeCurve};
+use crate::services::audio_service;
+
+const MIN_EXPONENT: f32 = 0.1;
+const MAX_EXPONENT: f32 = 4.0;
+
+/// Map the 0-1 slider value through the curve; 0 and 1 are fixed points of every curve
+pub fn apply_curve(volume: f32, curve: VolumeCurve) -> f32 {
+  let volume = volume.clamp(0.0, 1.0);
+  match curve {
+    VolumeCurve::Linear => volume,
+    VolumeCurve::Logarithmic => (1.0 + 9.0 * volume).log10(),
+    VolumeCurve::Exponent { exponent } => volume.powf(exponent),
+  }
+}
+
+/// Slider value a pack plays at: its own volume when it has one, the global one otherwise
+pub fn pack_volume(config: &AppConfig, settings: &PackSettings) -> f32 {
+  settings.default_volume.unwrap_or(config.volume)
+}
+
+/// Volume handed to wayvibes: `volume`, from `pack_volume` or a schedule, through the curve,
+/// scaled by the pack and profile gains and capped at 1.0. Zero while muted.
+pub fn effective_volume(
+  config: &AppConfig,
+  volume: f32,
+  settings: &PackSettings,
+  profile: Option<&Profile>,
+) -> f32 {
+  if config.muted {
+    return 0.0;
+  }
+  let gain_db = settings.gain_db + profile.map_or(0.0, |profile| profile.gain_db);
+  let volume = apply_curve(volume, config.volume_curve);
+  (volume * audio_service::db_to_linear(gain_db)).clamp(0.0, 1.0)
+}
+
+pub fn validate_curve(curve: VolumeCurve) -> Result<(), AppError> {
+  if let VolumeCurve::Exponent { exponent } = curve {
+    if !(MIN_EXPONENT..=MAX_EXPONENT).contains(&exponent) {
+      return Err(AppError::InvalidConfig(format!(
+        "Expoente da curva de volume fora do intervalo {}-{}",
+        MIN_EXPONENT, MAX_EXPONENT
+      )));
+    }
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn pack_volume_stands_in_for_the_global_volume() {
+    let config = AppConfig {
+      volume: 0.8,
+      ..Default::default()
+    };
+    let own = PackSettings {
+      default_volume: Some(0.3),
+      ..Default::default()
+    };
+    assert_eq!(pack_volume(&config, &own), 0.3);
+    assert_eq!(pack_volume(&config, &PackSettings::default()), 0.8);
+  }
+
+  #[test]
+  fn profile_gain_adds_to_the_pack_gain() {
+    let config = AppConfig::default();
+    let settings = PackSettings {
+      gain_db: 6.0,
+      ..Default::default()
+    };
+    let profile = Profile {
+      id: "quiet".into(),
+      name: "Silencioso".into(),
+      pack_id: None,
+      gain_db: -6.0,
+    };
+    assert_eq!(effective_volume(&config, 0.5, &settings, Some(&profile)), 0.5);
+    assert!(effective_volume(&config, 0.5, &settings, None) > 0.9);
+  }
+}
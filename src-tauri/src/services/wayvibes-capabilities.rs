@@ -0,0 +1,175 @@
+use std::collections::{BTreeSet, HashMap};
+use std::io::Read;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::error::AppError;
+use crate::models::WayvibesCapabilities;
+use crate::services::wayvibes_locator::WayvibesBinary;
+
+/// Older releases may treat an unknown flag as a pack path and keep running
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+const DEVICE_FLAGS: &[&str] = &["--device", "--prompt", "-p", "--input"];
+
+type ProbeCache = Mutex<HashMap<PathBuf, (SystemTime, WayvibesCapabilities)>>;
+
+fn cache() -> &'static ProbeCache {
+  static CACHE: OnceLock<ProbeCache> = OnceLock::new();
+  CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Capabilities of the binary, probed once per binary mtime
+pub fn probe(binary: &WayvibesBinary) -> WayvibesCapabilities {
+  let mtime = binary
+    .path
+    .metadata()
+    .and_then(|meta| meta.modified())
+    .unwrap_or(SystemTime::UNIX_EPOCH);
+
+  if let Ok(cache) = cache().lock() {
+    if let Some((cached_mtime, capabilities)) = cache.get(&binary.path) {
+      if *cached_mtime == mtime {
+        return capabilities.clone();
+      }
+    }
+  }
+
+  let version_output = run_with_timeout(binary, "--version").unwrap_or_default();
+  let help_output = run_with_timeout(binary, "--help").unwrap_or_default();
+  let capabilities = parse_capabilities(&version_output, &help_output);
+
+  if let Ok(mut cache) = cache().lock() {
+    cache.insert(binary.path.clone(), (mtime, capabilities.clone()));
+  }
+  capabilities
+}
+
+/// Fail with a clear message when a probed binary lacks a feature the app needs
+pub fn require(
+  capabilities: &WayvibesCapabilities,
+  supported: bool,
+  feature: &str,
+) -> Result<(), AppError> {
+  if capabilities.probed && !supported {
+    let installed = capabilities.version.as_deref().unwrap_or("desconhecida");
+    return Err(AppError::WayvibesUnsupported(format!(
+      "{} (versão instalada: {})",
+      feature, installed
+    )));
+  }
+  Ok(())
+}
+
+fn parse_capabilities(version_output: &str, help_output: &str) -> WayvibesCapabilities {
+  let version = parse_version(version_output).or_else(|| parse_version(help_output));
+  let flags = parse_flags(help_output);
+  let has = |flag: &str| flags.iter().any(|value| value == flag);
+
+  let volume_line = help_output
+    .lines()
+    .find(|line| line.contains("-v ") || line.contains("-v,") || line.contains("--volume"));
+
+  WayvibesCapabilities {
+    probed: !flags.is_empty(),
+    version,
+    volume: has("-v") || has("--volume"),
+    volume_max: volume_line.and_then(parse_range_max),
+    background: has("--background"),
+    device_selection: DEVICE_FLAGS.iter().any(|flag| has(flag)),
+    live_control: has("--control"),
+    keyup: has("--keyup") || mentions(help_output, &["key-up", "keyup", "key release"]),
+    variations: mentions(help_output, &["variation"]),
+    flags,
+  }
+}
+
+fn mentions(help_output: &str, words: &[&str]) -> bool {
+  let help = help_output.to_lowercase();
+  words.iter().any(|word| help.contains(word))
+}
+
+/// First dotted number such as `0.2.1` or `v1.0`
+fn parse_version(output: &str) -> Option<String> {
+  output
+    .split(|ch: char| ch.is_whitespace() || ch == ',' || ch == '(' || ch == ')')
+    .map(|token| token.trim_start_matches('v'))
+    .find(|token| {
+      let parts: Vec<&str> = token.split('.').collect();
+      parts.len() >= 2
+        && parts
+          .iter()
+          .all(|part| !part.is_empty() && part.chars().all(|ch| ch.is_ascii_digit()))
+    })
+    .map(|token| token.to_string())
+}
+
+fn parse_flags(help_output: &str) -> Vec<String> {
+  let mut flags = BTreeSet::new();
+  for token in help_output.split(|ch: char| ch.is_whitespace() || ",[]()|=<>".contains(ch)) {
+    let token = token.trim_end_matches(['.', ':', ';']);
+    let is_long = token.len() > 2
+      && token.starts_with("--")
+      && token[2..].chars().all(|ch| ch.is_ascii_alphanumeric() || ch == '-');
+    let is_short = token.len() == 2
+      && token.starts_with('-')
+      && token[1..].chars().all(|ch| ch.is_ascii_alphabetic());
+    if is_long || is_short {
+      flags.insert(token.to_string());
+    }
+  }
+  flags.into_iter().collect()
+}
+
+/// Upper bound of a range like `0.0-10.0` or `0 to 10` in a help line
+fn parse_range_max(line: &str) -> Option<f32> {
+  let numbers: Vec<f32> = line
+    .split(|ch: char| !(ch.is_ascii_digit() || ch == '.'))
+    .filter_map(|token| token.trim_matches('.').parse::<f32>().ok())
+    .collect();
+  if numbers.len() < 2 {
+    return None;
+  }
+  numbers
+    .into_iter()
+    .reduce(f32::max)
+    .filter(|max| *max > 0.0)
+}
+
+/// Run the binary with a single flag and return stdout and stderr together
+fn run_with_timeout(binary: &WayvibesBinary, flag: &str) -> Option<String> {
+  let mut child = binary
+    .command()
+    .arg(flag)
+    .stdin(Stdio::null())
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped())
+    .spawn()
+    .ok()?;
+
+  let deadline = Instant::now() + PROBE_TIMEOUT;
+  loop {
+    match child.try_wait() {
+      Ok(Some(_)) => break,
+      Ok(None) if Instant::now() < deadline => thread::sleep(POLL_INTERVAL),
+      _ => {
+        let _ = child.kill();
+        let _ = child.wait();
+        return None;
+      }
+    }
+  }
+
+  let mut output = String::new();
+  if let Some(mut stdout) = child.stdout.take() {
+    let _ = stdout.read_to_string(&mut output);
+  }
+  if let Some(mut stderr) = child.stderr.take() {
+    let _ = stderr.read_to_string(&mut output);
+  }
+  Some(output)
+}
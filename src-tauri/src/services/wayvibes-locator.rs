@@ -0,0 +1,144 @@
+use std::collections::{BTreeMap, HashSet};
+use std::env;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::error::AppError;
+use crate::models::{WayvibesCandidate, WayvibesLocation, WayvibesSettings};
+
+const BINARY_NAME: &str = "wayvibes";
+
+/// Install locations that are often missing from `PATH` under autostart or a desktop launcher
+const COMMON_DIRS: &[&str] = &[
+  "~/.cargo/bin",
+  "~/.local/bin",
+  "~/bin",
+  "~/.nix-profile/bin",
+  "/usr/local/bin",
+  "/usr/bin",
+  "/run/current-system/sw/bin",
+  "/home/linuxbrew/.linuxbrew/bin",
+];
+
+/// Resolved wayvibes executable plus the user's extra arguments and environment
+#[derive(Debug, Clone)]
+pub struct WayvibesBinary {
+  pub path: PathBuf,
+  pub extra_args: Vec<String>,
+  pub env: BTreeMap<String, String>,
+}
+
+impl WayvibesBinary {
+  /// Command for the binary with the configured environment; extra args are left to the caller
+  pub fn command(&self) -> Command {
+    let mut command = Command::new(&self.path);
+    command.envs(&self.env);
+    command
+  }
+
+  /// Name the kernel reports for the process, as matched by `pgrep -x`/`pkill -x`
+  pub fn process_name(&self) -> String {
+    let name = self
+      .path
+      .file_name()
+      .and_then(|name| name.to_str())
+      .unwrap_or(BINARY_NAME);
+    name.chars().take(15).collect()
+  }
+}
+
+/// The configured binary, or the first executable candidate when none is configured
+pub fn resolve(settings: &WayvibesSettings) -> Result<WayvibesBinary, AppError> {
+  let path = match configured_path(settings) {
+    Some(path) if is_executable(&path) => path,
+    Some(_) => return Err(AppError::WayvibesMissing),
+    None => find_candidates()
+      .into_iter()
+      .find(|(path, _)| is_executable(path))
+      .map(|(path, _)| path)
+      .ok_or(AppError::WayvibesMissing)?,
+  };
+
+  Ok(WayvibesBinary {
+    path,
+    extra_args: settings.extra_args.clone(),
+    env: settings.env.clone(),
+  })
+}
+
+/// Every place wayvibes was looked up, for troubleshooting the resolution
+pub fn locate(settings: &WayvibesSettings) -> WayvibesLocation {
+  let mut candidates = Vec::new();
+  if let Some(path) = configured_path(settings) {
+    candidates.push(candidate(&path, "config"));
+  }
+  candidates.extend(
+    find_candidates()
+      .iter()
+      .map(|(path, source)| candidate(path, source)),
+  );
+
+  WayvibesLocation {
+    resolved: resolve(settings)
+      .ok()
+      .map(|binary| binary.path.to_string_lossy().to_string()),
+    candidates,
+  }
+}
+
+fn configured_path(settings: &WayvibesSettings) -> Option<PathBuf> {
+  settings
+    .binary_path
+    .as_deref()
+    .map(str::trim)
+    .filter(|path| !path.is_empty())
+    .map(expand_home)
+}
+
+/// Existing `wayvibes` files on `PATH` and in the common install locations, without duplicates
+fn find_candidates() -> Vec<(PathBuf, &'static str)> {
+  let path_dirs = env::var_os("PATH")
+    .map(|value| env::split_paths(&value).collect::<Vec<_>>())
+    .unwrap_or_default();
+  let dirs = path_dirs
+    .into_iter()
+    .map(|dir| (dir, "path"))
+    .chain(COMMON_DIRS.iter().map(|dir| (expand_home(dir), "common")));
+
+  let mut seen = HashSet::new();
+  let mut found = Vec::new();
+  for (dir, source) in dirs {
+    let path = dir.join(BINARY_NAME);
+    if !path.is_file() {
+      continue;
+    }
+    let key = path.canonicalize().unwrap_or_else(|_| path.clone());
+    if seen.insert(key) {
+      found.push((path, source));
+    }
+  }
+  found
+}
+
+fn candidate(path: &Path, source: &str) -> WayvibesCandidate {
+  WayvibesCandidate {
+    path: path.to_string_lossy().to_string(),
+    source: source.to_string(),
+    executable: is_executable(path),
+  }
+}
+
+fn is_executable(path: &Path) -> bool {
+  path
+    .metadata()
+    .map(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+    .unwrap_or(false)
+}
+
+fn expand_home(path: &str) -> PathBuf {
+  match (path.strip_prefix("~/"), env::var_os("HOME")) {
+    (Some(rest), Some(home)) => PathBuf::from(home).join(rest),
+    _ => PathBuf::from(path),
+  }
+}
@@ -0,0 +1,136 @@
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+use crate::models::{LogLine, LogStream};
+use crate::rotating_file::RotatingFile;
+
+/// Lines kept in memory for `get_wayvibes_logs`
+const BUFFER_CAPACITY: usize = 2000;
+const EVENT_CAPACITY: usize = 256;
+const LOG_FILE_MAX_BYTES: u64 = 1024 * 1024;
+const LOG_FILE_KEEP: usize = 3;
+
+/// Output of the supervised wayvibes process: a bounded ring buffer,
+/// a rotating file on disk and a live stream of new lines
+pub struct WayvibesLogs {
+  lines: Mutex<VecDeque<LogLine>>,
+  file: Mutex<RotatingFile>,
+  events: broadcast::Sender<LogLine>,
+  next_seq: AtomicU64,
+}
+
+impl WayvibesLogs {
+  pub fn new(logs_dir: PathBuf) -> Self {
+    let (events, _) = broadcast::channel(EVENT_CAPACITY);
+    Self {
+      lines: Mutex::new(VecDeque::with_capacity(BUFFER_CAPACITY)),
+      file: Mutex::new(RotatingFile::new(
+        logs_dir.join("wayvibes.log"),
+        LOG_FILE_MAX_BYTES,
+        LOG_FILE_KEEP,
+      )),
+      events,
+      next_seq: AtomicU64::new(1),
+    }
+  }
+
+  pub fn subscribe(&self) -> broadcast::Receiver<LogLine> {
+    self.events.subscribe()
+  }
+
+  /// Sequence number the next pushed line will get
+  pub fn next_seq(&self) -> u64 {
+    self.next_seq.load(Ordering::SeqCst)
+  }
+
+  pub fn push(&self, stream: LogStream, text: impl Into<String>) {
+    let line = LogLine {
+      seq: self.next_seq.fetch_add(1, Ordering::SeqCst),
+      timestamp: chrono::Local::now().to_rfc3339(),
+      stream,
+      text: text.into(),
+    };
+
+    if let Ok(mut file) = self.file.lock() {
+      let tag = match line.stream {
+        LogStream::Stdout => "out",
+        LogStream::Stderr => "err",
+        LogStream::Supervisor => "app",
+      };
+      if let Err(err) = file.write_line(&format!("{} [{}] {}", line.timestamp, tag, line.text)) {
+        log::warn!("Failed to write {}: {}", file.path().display(), err);
+      }
+    }
+
+    if let Ok(mut lines) = self.lines.lock() {
+      if lines.len() == BUFFER_CAPACITY {
+        lines.pop_front();
+      }
+      lines.push_back(line.clone());
+    }
+
+    let _ = self.events.send(line);
+  }
+
+  /// Buffered lines matching `stream` and the case-insensitive `filter`, limited to the last `tail`
+  pub fn query(
+    &self,
+    tail: Option<usize>,
+    filter: Option<&str>,
+    stream: Option<LogStream>,
+    since_seq: Option<u64>,
+  ) -> Vec<LogLine> {
+    let lines = match self.lines.lock() {
+      Ok(lines) => lines,
+      Err(_) => return Vec::new(),
+    };
+    let filter = filter
+      .map(|value| value.trim().to_lowercase())
+      .filter(|value| !value.is_empty());
+
+    let mut matched: Vec<LogLine> = lines
+      .iter()
+      .filter(|line| since_seq.is_none_or(|seq| line.seq >= seq))
+      .filter(|line| stream.is_none_or(|stream| line.stream == stream))
+      .filter(|line| {
+        filter
+          .as_ref()
+          .is_none_or(|filter| line.text.to_lowercase().contains(filter))
+      })
+      .cloned()
+      .collect();
+
+    if let Some(tail) = tail {
+      let skip = matched.len().saturating_sub(tail);
+      matched.drain(..skip);
+    }
+    matched
+  }
+
+  /// Forward every line of a child pipe into the log until it closes
+  pub fn attach<R>(self: &Arc<Self>, reader: R, stream: LogStream) -> JoinHandle<()>
+  where
+    R: AsyncRead + Unpin + Send + 'static,
+  {
+    let logs = self.clone();
+    tokio::spawn(async move {
+      let mut lines = BufReader::new(reader).lines();
+      loop {
+        match lines.next_line().await {
+          Ok(Some(line)) => logs.push(stream, line),
+          Ok(None) => break,
+          Err(err) => {
+            logs.push(LogStream::Supervisor, format!("Falha ao ler saída: {}", err));
+            break;
+          }
+        }
+      }
+    })
+  }
+}
@@ -1,119 +1,596 @@
-use std::path::Path;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
 
-use tokio::process::Command;
+use tokio::io::AsyncWriteExt;
+use tokio::process::{Child, ChildStdin, Command};
+use tokio::sync::{mpsc, oneshot, watch};
 
 use crate::error::AppError;
-use crate::models::WayvibesStatus;
+use crate::models::{
+  CheckStatus, LifecycleState, LogStream, PreflightReport, WayvibesCapabilities, WayvibesSettings,
+  WayvibesStatus,
+};
+use crate::services::{preflight_service, sound_pack_service, wayvibes_capabilities};
+use crate::services::wayvibes_locator::{self, WayvibesBinary};
+use crate::services::wayvibes_logs::WayvibesLogs;
+
+/// Volume ceiling of wayvibes releases that don't document their range
+const DEFAULT_VOLUME_MAX: f32 = 10.0;
+/// How long a fresh process must survive before the start counts as successful
+const STARTUP_GRACE: Duration = Duration::from_millis(400);
+const STOP_TIMEOUT: Duration = Duration::from_secs(2);
+/// Quiet period after the last volume/pack change before it is applied
+const RESTART_DEBOUNCE: Duration = Duration::from_millis(250);
+/// How often an idle supervisor checks whether the child exited on its own
+const EXIT_POLL: Duration = Duration::from_secs(1);
 
 /// Get the current status of wayvibes
-pub async fn get_status() -> Result<WayvibesStatus, AppError> {
-  let installed = is_installed().await;
-  let running = if installed {
-    is_running().await.unwrap_or(false)
-  } else {
-    false
+pub async fn get_status(
+  settings: &WayvibesSettings,
+  lifecycle: LifecycleState,
+) -> Result<WayvibesStatus, AppError> {
+  let binary = wayvibes_locator::resolve(settings).ok();
+  let installed = binary.is_some();
+  let running = match &binary {
+    Some(binary) => is_running(binary).await.unwrap_or(false),
+    None => false,
   };
-  let pid = if running {
-    get_pid().await.unwrap_or(None)
-  } else {
-    None
+  let pid = match &binary {
+    Some(binary) if running => get_pid(binary).await.unwrap_or(None),
+    _ => None,
+  };
+  let capabilities = match binary {
+    Some(binary) => Some(capabilities(binary).await),
+    None => None,
   };
+  let preflight = preflight(settings.clone()).await.summary;
 
   Ok(WayvibesStatus {
     installed,
     running,
-    version: None,
+    version: capabilities.as_ref().and_then(|caps| caps.version.clone()),
     pid,
+    capabilities,
+    preflight: Some(preflight),
+    lifecycle: Some(lifecycle),
   })
 }
 
-/// Start wayvibes with a sound pack and volume
-/// Usage: wayvibes [soundpack_path] -v <volume> --background
-pub async fn start(pack_path: &Path, volume: f32) -> Result<(), AppError> {
-  ensure_installed().await?;
+/// Run the prerequisite checks off the async runtime
+pub async fn preflight(settings: WayvibesSettings) -> PreflightReport {
+  tokio::task::spawn_blocking(move || preflight_service::run(&settings))
+    .await
+    .unwrap_or_else(|_| PreflightReport {
+      summary: preflight_service::summarize(&[]),
+      checks: Vec::new(),
+    })
+}
 
-  // Stop any existing instance first
-  let _ = stop().await;
+/// Probe (or reuse the cached probe of) the binary without blocking the runtime
+pub async fn capabilities(binary: WayvibesBinary) -> WayvibesCapabilities {
+  tokio::task::spawn_blocking(move || wayvibes_capabilities::probe(&binary))
+    .await
+    .unwrap_or_default()
+}
 
-  let path_str = pack_path
-    .to_str()
-    .ok_or_else(|| AppError::WayvibesCommand("Caminho inválido".into()))?;
+/// A wayvibes child launched by the app and what it is playing
+struct Running {
+  child: Child,
+  /// Command channel on stdin when the binary was started with `--control`
+  control: Option<ChildStdin>,
+  settings: WayvibesSettings,
+  pack_path: PathBuf,
+  volume: f32,
+}
 
-  // Volume range is 0.0-10.0 in wayvibes, but our UI uses 0.0-1.0
-  // Convert: UI 0.0-1.0 -> wayvibes 0.0-10.0
-  let wayvibes_volume = volume * 10.0;
+/// Pack and volume a start or restart should end up playing
+struct Target {
+  settings: WayvibesSettings,
+  pack_path: PathBuf,
+  volume: f32,
+}
 
-  println!(
-    "[wayvibes] Starting with pack: {} volume: {}",
-    path_str, wayvibes_volume
-  );
+type Reply = oneshot::Sender<Result<(), AppError>>;
 
-  // Start wayvibes in background mode
-  let output = Command::new("wayvibes")
-    .arg(path_str)
-    .arg("-v")
-    .arg(format!("{:.1}", wayvibes_volume))
-    .arg("--background")
-    .output()
-    .await?;
+/// Lifecycle operations, applied one at a time in the order they were queued
+enum Request {
+  Start { target: Target, reply: Reply },
+  /// Switch pack and/or volume; coalesced with the requests queued right after it
+  Apply { target: Target, reply: Reply },
+  Stop { settings: WayvibesSettings, reply: Reply },
+  /// Trial run of an imported pack, kept apart from our own process transitions
+  Validate { binary: WayvibesBinary, pack_path: PathBuf, reply: Reply },
+}
 
-  if !output.status.success() {
-    let error = String::from_utf8_lossy(&output.stderr).to_string();
-    println!("[wayvibes] Failed to start: {}", error);
-    return Err(AppError::WayvibesCommand(error));
+/// Handle to the supervisor task that owns the wayvibes child and its captured output
+pub struct WayvibesProcess {
+  requests: mpsc::UnboundedSender<Request>,
+  lifecycle: watch::Receiver<LifecycleState>,
+  pub logs: Arc<WayvibesLogs>,
+}
+
+impl WayvibesProcess {
+  pub fn new(logs_dir: PathBuf) -> Self {
+    let logs = Arc::new(WayvibesLogs::new(logs_dir));
+    let (requests, queue) = mpsc::unbounded_channel();
+    let (lifecycle_tx, lifecycle) = watch::channel(LifecycleState::Stopped);
+
+    let supervisor = Supervisor {
+      queue,
+      lifecycle: lifecycle_tx,
+      logs: logs.clone(),
+      running: None,
+    };
+    tauri::async_runtime::spawn(supervisor.run());
+
+    Self {
+      requests,
+      lifecycle,
+      logs,
+    }
   }
 
-  println!("[wayvibes] Started successfully");
-  Ok(())
+  pub fn lifecycle(&self) -> LifecycleState {
+    *self.lifecycle.borrow()
+  }
+
+  /// Notified on every lifecycle transition
+  pub fn watch_lifecycle(&self) -> watch::Receiver<LifecycleState> {
+    self.lifecycle.clone()
+  }
+
+  fn submit(&self, request: impl FnOnce(Reply) -> Request) -> Pending {
+    let (reply, done) = oneshot::channel();
+    // If the supervisor is gone the reply sender is dropped with the request and `Pending`
+    // reports it
+    let _ = self.requests.send(request(reply));
+    Pending(done)
+  }
 }
 
-/// Stop the running wayvibes process
-pub async fn stop() -> Result<(), AppError> {
-  println!("[wayvibes] Stopping...");
+/// Outcome of a queued lifecycle request. The request is queued when this is created, not
+/// when it is awaited, so callers can enqueue while holding the config lock and keep the
+/// process in step with the persisted state.
+pub struct Pending(oneshot::Receiver<Result<(), AppError>>);
 
-  let output = Command::new("pkill")
-    .arg("-x")
-    .arg("wayvibes")
-    .output()
-    .await?;
+impl Future for Pending {
+  type Output = Result<(), AppError>;
 
-  // pkill returns 1 if no process was found, which is fine
-  if output.status.success() {
-    println!("[wayvibes] Stopped successfully");
-  } else {
-    println!("[wayvibes] No running process found");
+  fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+    Pin::new(&mut self.0).poll(cx).map(|reply| {
+      reply.unwrap_or_else(|_| {
+        Err(AppError::WayvibesCommand("Supervisor do wayvibes indisponível".into()))
+      })
+    })
   }
+}
+
+/// Start wayvibes with a sound pack and volume, supervised in the foreground.
+/// Cancels a pack or volume change that is still waiting to be applied.
+/// Usage: wayvibes [soundpack_path] -v <volume> [--control]
+pub fn start(
+  process: &WayvibesProcess,
+  settings: &WayvibesSettings,
+  pack_path: &Path,
+  volume: f32,
+) -> Pending {
+  let target = Target {
+    settings: settings.clone(),
+    pack_path: pack_path.to_path_buf(),
+    volume,
+  };
+  process.submit(|reply| Request::Start { target, reply })
+}
+
+/// Stop the running wayvibes process, cancelling any change still waiting to be applied
+pub fn stop(process: &WayvibesProcess, settings: &WayvibesSettings) -> Pending {
+  let settings = settings.clone();
+  process.submit(|reply| Request::Stop { settings, reply })
+}
+
+/// Apply a new pack and/or volume. Rapid calls are coalesced: only the last one within
+/// `RESTART_DEBOUNCE` is applied, in place when wayvibes accepts commands, otherwise by
+/// relaunching. Superseded calls resolve to `Ok(())`.
+pub fn restart(
+  process: &WayvibesProcess,
+  settings: &WayvibesSettings,
+  pack_path: &Path,
+  volume: f32,
+) -> Pending {
+  let target = Target {
+    settings: settings.clone(),
+    pack_path: pack_path.to_path_buf(),
+    volume,
+  };
+  process.submit(|reply| Request::Apply { target, reply })
+}
+
+/// Check that wayvibes can load a pack, between lifecycle transitions
+pub fn validate_pack(
+  process: &WayvibesProcess,
+  binary: &WayvibesBinary,
+  pack_path: &Path,
+) -> Pending {
+  let binary = binary.clone();
+  let pack_path = pack_path.to_path_buf();
+  process.submit(|reply| Request::Validate {
+    binary,
+    pack_path,
+    reply,
+  })
+}
 
+/// Owns the child and is the only place it is started, changed or stopped
+struct Supervisor {
+  queue: mpsc::UnboundedReceiver<Request>,
+  lifecycle: watch::Sender<LifecycleState>,
+  logs: Arc<WayvibesLogs>,
+  running: Option<Running>,
+}
+
+impl Supervisor {
+  async fn run(mut self) {
+    let mut next: Option<Request> = None;
+    loop {
+      let request = match next.take() {
+        Some(request) => request,
+        None => match tokio::time::timeout(EXIT_POLL, self.queue.recv()).await {
+          Ok(Some(request)) => request,
+          Ok(None) => break,
+          Err(_) => {
+            self.check_exited();
+            continue;
+          }
+        },
+      };
+      next = self.handle(request).await;
+    }
+  }
+
+  /// Handle one request, returning a request that arrived while coalescing and must run next
+  async fn handle(&mut self, request: Request) -> Option<Request> {
+    match request {
+      Request::Start { target, reply } => {
+        let _ = reply.send(self.start(&target).await);
+        None
+      }
+      Request::Stop { settings, reply } => {
+        let _ = reply.send(self.stop(&settings).await);
+        None
+      }
+      Request::Validate {
+        binary,
+        pack_path,
+        reply,
+      } => {
+        let owned = self.running.as_ref().and_then(|running| running.child.id());
+        let result = tokio::task::spawn_blocking(move || {
+          sound_pack_service::validate_pack_with_wayvibes(&binary, &pack_path, owned)
+        })
+        .await
+        .unwrap_or_else(|err| Err(AppError::WayvibesCommand(err.to_string())));
+        let _ = reply.send(result);
+        None
+      }
+      Request::Apply { target, reply } => {
+        let (mut target, mut reply) = (target, reply);
+        let next = loop {
+          match tokio::time::timeout(RESTART_DEBOUNCE, self.queue.recv()).await {
+            Ok(Some(Request::Apply {
+              target: newer,
+              reply: newer_reply,
+            })) => {
+              log::debug!("Restart superseded by a newer request");
+              let _ = reply.send(Ok(()));
+              target = newer;
+              reply = newer_reply;
+            }
+            Ok(Some(request @ (Request::Start { .. } | Request::Stop { .. }))) => {
+              // An explicit start or stop replaces the change that was waiting
+              log::debug!("Restart superseded by a start or stop");
+              let _ = reply.send(Ok(()));
+              return Some(request);
+            }
+            Ok(other) => break other,
+            Err(_) => break None,
+          }
+        };
+        let _ = reply.send(self.apply(&target).await);
+        next
+      }
+    }
+  }
+
+  fn set_state(&self, state: LifecycleState) {
+    self.lifecycle.send_if_modified(|current| {
+      let changed = *current != state;
+      if changed {
+        log::debug!("Lifecycle {:?} -> {:?}", current, state);
+        *current = state;
+      }
+      changed
+    });
+  }
+
+  /// Notice a child that exited on its own, e.g. after losing the audio device
+  fn check_exited(&mut self) {
+    let status = match self.running.as_mut().map(|running| running.child.try_wait()) {
+      Some(Ok(Some(status))) => status,
+      _ => return,
+    };
+    self.running = None;
+    log::warn!("wayvibes exited unexpectedly ({})", status);
+    self
+      .logs
+      .push(LogStream::Supervisor, format!("Processo encerrou inesperadamente ({})", status));
+    self.set_state(LifecycleState::Failed);
+  }
+
+  async fn start(&mut self, target: &Target) -> Result<(), AppError> {
+    let binary = wayvibes_locator::resolve(&target.settings)?;
+    let caps = capabilities(binary.clone()).await;
+    wayvibes_capabilities::require(&caps, caps.volume, "ajuste de volume (-v)")?;
+    self.launch(&binary, &caps, target).await
+  }
+
+  async fn apply(&mut self, target: &Target) -> Result<(), AppError> {
+    let binary = wayvibes_locator::resolve(&target.settings)?;
+    let caps = capabilities(binary.clone()).await;
+    wayvibes_capabilities::require(&caps, caps.volume, "ajuste de volume (-v)")?;
+
+    if let Some(current) = self.running.as_mut() {
+      let alive = matches!(current.child.try_wait(), Ok(None));
+      if alive && current.settings == target.settings {
+        if current.pack_path == target.pack_path && current.volume == target.volume {
+          return Ok(());
+        }
+        if current.control.is_some() {
+          match apply_in_place(current, &caps, target).await {
+            Ok(()) => {
+              self.logs.push(
+                LogStream::Supervisor,
+                format!(
+                  "Alterado sem reiniciar: pacote {} (volume {:.2})",
+                  target.pack_path.display(),
+                  target.volume
+                ),
+              );
+              return Ok(());
+            }
+            Err(err) => log::warn!("Live update failed, relaunching: {}", err),
+          }
+        }
+      }
+    }
+
+    self.launch(&binary, &caps, target).await
+  }
+
+  async fn launch(
+    &mut self,
+    binary: &WayvibesBinary,
+    caps: &WayvibesCapabilities,
+    target: &Target,
+  ) -> Result<(), AppError> {
+    // Stop any existing instance first
+    self.stop(&target.settings).await?;
+
+    self.set_state(LifecycleState::Starting);
+    match spawn_checked(&self.logs, binary, caps, target).await {
+      Ok(running) => {
+        self.running = Some(running);
+        self.set_state(LifecycleState::Running);
+        Ok(())
+      }
+      Err(err) => {
+        self.set_state(LifecycleState::Failed);
+        Err(err)
+      }
+    }
+  }
+
+  async fn stop(&mut self, settings: &WayvibesSettings) -> Result<(), AppError> {
+    log::info!("Stopping...");
+    self.set_state(LifecycleState::Stopping);
+
+    if let Some(Running { mut child, control, .. }) = self.running.take() {
+      drop(control);
+      // Ask nicely so wayvibes can release the audio device, then force it
+      if let Some(pid) = child.id() {
+        let _ = Command::new("kill").arg(pid.to_string()).output().await;
+      }
+      let status = match tokio::time::timeout(STOP_TIMEOUT, child.wait()).await {
+        Ok(status) => status.ok(),
+        Err(_) => {
+          let _ = child.kill().await;
+          child.wait().await.ok()
+        }
+      };
+      let status = status
+        .map(|status| status.to_string())
+        .unwrap_or_else(|| "status desconhecido".into());
+      self
+        .logs
+        .push(LogStream::Supervisor, format!("Processo encerrado ({})", status));
+    }
+
+    // Also catch instances the app didn't start, e.g. left over from a previous session
+    let process_name = wayvibes_locator::resolve(settings)
+      .map(|binary| binary.process_name())
+      .unwrap_or_else(|_| "wayvibes".into());
+
+    let output = match Command::new("pkill").arg("-x").arg(process_name).output().await {
+      Ok(output) => output,
+      Err(err) => {
+        self.set_state(LifecycleState::Failed);
+        return Err(err.into());
+      }
+    };
+
+    // pkill returns 1 if no process was found, which is fine
+    if output.status.success() {
+      log::info!("Stopped successfully");
+    } else {
+      log::debug!("No running process found");
+    }
+
+    self.set_state(LifecycleState::Stopped);
+    Ok(())
+  }
+}
+
+/// Send `pack <path>` and `volume <value>` lines to a wayvibes started with `--control`
+async fn apply_in_place(
+  current: &mut Running,
+  caps: &WayvibesCapabilities,
+  target: &Target,
+) -> Result<(), AppError> {
+  let control = current
+    .control
+    .as_mut()
+    .ok_or_else(|| AppError::WayvibesCommand("Canal de controle indisponível".into()))?;
+
+  let mut commands = String::new();
+  if current.pack_path != target.pack_path {
+    let path = target
+      .pack_path
+      .to_str()
+      .ok_or_else(|| AppError::WayvibesCommand("Caminho inválido".into()))?;
+    commands.push_str(&format!("pack {}\n", path));
+  }
+  if current.volume != target.volume {
+    commands.push_str(&format!("volume {:.1}\n", wayvibes_volume(caps, target.volume)));
+  }
+
+  control.write_all(commands.as_bytes()).await?;
+  control.flush().await?;
+  log::debug!("Sent to wayvibes: {}", commands.trim_end().replace('\n', "; "));
+
+  current.pack_path = target.pack_path.clone();
+  current.volume = target.volume;
   Ok(())
 }
 
-/// Restart wayvibes with new settings (pack and/or volume)
-pub async fn restart(pack_path: &Path, volume: f32) -> Result<(), AppError> {
-  start(pack_path, volume).await
+/// Spawn wayvibes and wait out `STARTUP_GRACE` to make sure it keeps running
+async fn spawn_checked(
+  logs: &WayvibesLogs,
+  binary: &WayvibesBinary,
+  caps: &WayvibesCapabilities,
+  target: &Target,
+) -> Result<Running, AppError> {
+  let path_str = target
+    .pack_path
+    .to_str()
+    .ok_or_else(|| AppError::WayvibesCommand("Caminho inválido".into()))?;
+
+  let wayvibes_volume = wayvibes_volume(caps, target.volume);
+
+  log::info!(
+    "Starting {} with pack: {} volume: {}",
+    binary.path.display(),
+    path_str,
+    wayvibes_volume
+  );
+  let first_seq = logs.next_seq();
+  logs.push(
+    LogStream::Supervisor,
+    format!(
+      "Iniciando {} com pacote {} (volume {:.1})",
+      binary.path.display(),
+      path_str,
+      wayvibes_volume
+    ),
+  );
+
+  let mut command = Command::from(binary.command());
+  command
+    .arg(path_str)
+    .arg("-v")
+    .arg(format!("{:.1}", wayvibes_volume))
+    .args(&binary.extra_args)
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped());
+  if caps.live_control {
+    command.arg("--control").stdin(Stdio::piped());
+  } else {
+    command.stdin(Stdio::null());
+  }
+  let mut child = command.spawn()?;
+
+  if let Some(stdout) = child.stdout.take() {
+    logs.attach(stdout, LogStream::Stdout);
+  }
+  let stderr_reader = child
+    .stderr
+    .take()
+    .map(|stderr| logs.attach(stderr, LogStream::Stderr));
+
+  // A bad pack or missing input permissions make wayvibes exit right away
+  tokio::time::sleep(STARTUP_GRACE).await;
+  if let Some(status) = child.try_wait()? {
+    if let Some(reader) = stderr_reader {
+      let _ = tokio::time::timeout(STARTUP_GRACE, reader).await;
+    }
+    let stderr: Vec<String> = logs
+      .query(None, None, Some(LogStream::Stderr), Some(first_seq))
+      .into_iter()
+      .map(|line| line.text)
+      .collect();
+    let mut error = if stderr.is_empty() {
+      format!("wayvibes encerrou ao iniciar ({})", status)
+    } else {
+      stderr.join("\n")
+    };
+    // Point at the likely cause instead of leaving only wayvibes' own message
+    let report = preflight(target.settings.clone()).await;
+    if let Some(check) = report.checks.iter().find(|check| check.status == CheckStatus::Fail) {
+      error.push_str(&format!("\n{}: {}", check.label, check.detail));
+      if let Some(remediation) = &check.remediation {
+        error.push_str(&format!(" — {}", remediation));
+      }
+    }
+    log::error!("Failed to start: {}", error);
+    logs.push(LogStream::Supervisor, format!("Falha ao iniciar ({})", status));
+    return Err(AppError::WayvibesCommand(error));
+  }
+
+  if let Some(pid) = child.id() {
+    logs.push(LogStream::Supervisor, format!("Processo iniciado (pid {})", pid));
+  }
+
+  log::info!("Started successfully");
+  Ok(Running {
+    control: child.stdin.take(),
+    child,
+    settings: target.settings.clone(),
+    pack_path: target.pack_path.clone(),
+    volume: target.volume,
+  })
 }
 
-async fn is_installed() -> bool {
-  Command::new("which")
-    .arg("wayvibes")
-    .output()
-    .await
-    .map(|output| output.status.success())
-    .unwrap_or(false)
+/// Volume range is 0.0-10.0 in wayvibes (or whatever its --help reports), but our UI uses 0.0-1.0
+fn wayvibes_volume(caps: &WayvibesCapabilities, volume: f32) -> f32 {
+  volume * caps.volume_max.unwrap_or(DEFAULT_VOLUME_MAX)
 }
 
-async fn is_running() -> Result<bool, AppError> {
+async fn is_running(binary: &WayvibesBinary) -> Result<bool, AppError> {
   let output = Command::new("pgrep")
     .arg("-x")
-    .arg("wayvibes")
+    .arg(binary.process_name())
     .output()
     .await?;
   Ok(output.status.success())
 }
 
-async fn get_pid() -> Result<Option<u32>, AppError> {
+async fn get_pid(binary: &WayvibesBinary) -> Result<Option<u32>, AppError> {
   let output = Command::new("pgrep")
     .arg("-nx")
-    .arg("wayvibes")
+    .arg(binary.process_name())
     .output()
     .await?;
   if !output.status.success() {
@@ -128,9 +605,100 @@ async fn get_pid() -> Result<Option<u32>, AppError> {
     .map_err(|_| AppError::WayvibesCommand("PID inválido".into()))
 }
 
-async fn ensure_installed() -> Result<(), AppError> {
-  if !is_installed().await {
-    return Err(AppError::WayvibesMissing);
+#[cfg(test)]
+mod tests {
+  use std::collections::BTreeMap;
+  use std::fs;
+  use std::os::unix::fs::PermissionsExt;
+
+  use super::*;
+
+  const FAKE_WAYVIBES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../scripts/fake-wayvibes.sh");
+
+  /// A copy of `scripts/fake-wayvibes.sh` under its own name, so the `pkill -x` of one test's
+  /// stop can't reach another test's child, and the settings that log its launches
+  fn fake_wayvibes(name: &str) -> (PathBuf, WayvibesSettings) {
+    let dir = std::env::temp_dir().join(format!("wayvibes-ui-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("pack")).unwrap();
+    let binary = dir.join(name);
+    fs::copy(FAKE_WAYVIBES, &binary).unwrap();
+    fs::set_permissions(&binary, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let log = dir.join("launches.log");
+    let settings = WayvibesSettings {
+      binary_path: Some(binary.to_string_lossy().to_string()),
+      extra_args: Vec::new(),
+      env: BTreeMap::from([("FAKE_WAYVIBES_LOG".into(), log.to_string_lossy().to_string())]),
+    };
+    (dir, settings)
+  }
+
+  fn launches(dir: &Path) -> Vec<String> {
+    fs::read_to_string(dir.join("launches.log"))
+      .unwrap_or_default()
+      .lines()
+      .filter(|line| line.starts_with("launch "))
+      .map(str::to_string)
+      .collect()
+  }
+
+  #[tokio::test]
+  async fn restart_storm_launches_at_most_twice() {
+    let (dir, settings) = fake_wayvibes("fake-wv-storm");
+    let process = WayvibesProcess::new(dir.join("logs"));
+    let pack = dir.join("pack");
+
+    let pending: Vec<Pending> = (1..=40)
+      .map(|step| restart(&process, &settings, &pack, step as f32 / 40.0))
+      .collect();
+    // Already queued, so awaiting them one by one doesn't change the order
+    for pending in pending {
+      pending.await.unwrap();
+    }
+
+    let launched = launches(&dir);
+    assert!(!launched.is_empty() && launched.len() <= 2, "launches: {:?}", launched);
+    // The last volume wins: 1.0 of a 10.0 range
+    assert!(launched.last().unwrap().ends_with("-v 10.0"), "launches: {:?}", launched);
+
+    stop(&process, &settings).await.unwrap();
+    let _ = fs::remove_dir_all(&dir);
+  }
+
+  #[tokio::test]
+  async fn stop_supersedes_pending_restart() {
+    let (dir, settings) = fake_wayvibes("fake-wv-stop");
+    let process = WayvibesProcess::new(dir.join("logs"));
+
+    let restarted = restart(&process, &settings, &dir.join("pack"), 0.5);
+    let stopped = stop(&process, &settings);
+    restarted.await.unwrap();
+    stopped.await.unwrap();
+
+    assert!(launches(&dir).is_empty());
+    assert_eq!(process.lifecycle(), LifecycleState::Stopped);
+    let _ = fs::remove_dir_all(&dir);
+  }
+
+  #[tokio::test]
+  async fn start_supersedes_pending_restart() {
+    let (dir, settings) = fake_wayvibes("fake-wv-start");
+    let process = WayvibesProcess::new(dir.join("logs"));
+    let started_pack = dir.join("started");
+    fs::create_dir_all(&started_pack).unwrap();
+
+    let restarted = restart(&process, &settings, &dir.join("pack"), 0.5);
+    let started = start(&process, &settings, &started_pack, 0.2);
+    restarted.await.unwrap();
+    started.await.unwrap();
+
+    let launched = launches(&dir);
+    assert_eq!(launched.len(), 1, "launches: {:?}", launched);
+    assert!(launched[0].contains(&format!("{} -v 2.0", started_pack.display())));
+    assert_eq!(process.lifecycle(), LifecycleState::Running);
+
+    stop(&process, &settings).await.unwrap();
+    let _ = fs::remove_dir_all(&dir);
   }
-  Ok(())
 }
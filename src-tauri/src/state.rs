@@ -2,40 +2,113 @@ use std::fs;
 use std::path::PathBuf;
 use std::sync::Mutex;
 
-use directories::ProjectDirs;
+use tokio::sync::broadcast;
 
 use crate::error::AppError;
-use crate::models::AppConfig;
-use crate::services::config_service;
+use crate::models::{AppConfig, AppEvent, SoundPack, WayvibesCapabilities};
+use crate::paths;
+use crate::scheduler::ScheduleOverride;
+use crate::services::wayvibes_service::{self, WayvibesProcess};
+use crate::services::scheduler_service::{self, Playback};
+use crate::services::{
+  config_service, sound_pack_service, wayvibes_capabilities, wayvibes_locator,
+};
+
+const EVENT_CAPACITY: usize = 64;
 
 pub struct AppState {
   pub packs_dir: PathBuf,
   pub config_path: PathBuf,
   pub config: Mutex<AppConfig>,
+  pub wayvibes: WayvibesProcess,
+  pub events: broadcast::Sender<AppEvent>,
+  /// Taken after `config` when both are needed
+  pub schedule_override: Mutex<Option<ScheduleOverride>>,
 }
 
 impl AppState {
   pub fn new() -> Result<Self, AppError> {
-    let project_dirs = ProjectDirs::from("", "", "wayvibes-ui")
-      .ok_or_else(|| AppError::InvalidConfig("Diretório do app indisponível".into()))?;
-    let data_dir = project_dirs.data_dir().to_path_buf();
+    let project_dirs = paths::project_dirs()?;
     let config_dir = project_dirs.config_dir().to_path_buf();
-    let packs_dir = data_dir.join("packs");
+    let packs_dir = paths::packs_dir()?;
     let config_path = config_dir.join("config.json");
+    let logs_dir = paths::logs_dir()?;
 
     fs::create_dir_all(&packs_dir)?;
     fs::create_dir_all(&config_dir)?;
+    fs::create_dir_all(&logs_dir)?;
 
     let config = config_service::load_config(&config_path)?;
+    let (events, _) = broadcast::channel(EVENT_CAPACITY);
 
     Ok(Self {
       packs_dir,
       config_path,
       config: Mutex::new(config),
+      wayvibes: WayvibesProcess::new(logs_dir),
+      events,
+      schedule_override: Mutex::new(None),
     })
   }
 
   pub fn save_config(&self, config: &AppConfig) -> Result<(), AppError> {
     config_service::save_config(&self.config_path, config)
   }
+
+  /// What should play given the manual state in `config` and the schedule in effect
+  pub fn playback(&self, config: &AppConfig) -> Playback {
+    let current = self.schedule_override.lock().ok();
+    let action = current.as_deref().and_then(Option::as_ref).map(|schedule| &schedule.action);
+    scheduler_service::playback(config, action)
+  }
+
+  pub fn subscribe(&self) -> broadcast::Receiver<AppEvent> {
+    self.events.subscribe()
+  }
+
+  /// Broadcast the current config together with a fresh wayvibes status.
+  /// Must not be called while holding the config lock.
+  pub fn publish_state(&self) {
+    let config = match self.config.lock() {
+      Ok(config) => config.clone(),
+      Err(_) => return,
+    };
+    let lifecycle = self.wayvibes.lifecycle();
+    let events = self.events.clone();
+    tauri::async_runtime::spawn(async move {
+      if let Ok(status) = wayvibes_service::get_status(&config.wayvibes, lifecycle).await {
+        let _ = events.send(AppEvent::StateChanged { config, status });
+      }
+    });
+  }
+
+  /// Must not be called while holding the config lock.
+  pub fn publish_packs(&self) {
+    if let Ok(packs) = self.list_packs() {
+      let _ = self.events.send(AppEvent::PacksChanged { packs });
+    }
+  }
+
+  /// Installed packs, each flagged with the features the configured wayvibes can't play.
+  /// Must not be called while holding the config lock.
+  pub fn list_packs(&self) -> Result<Vec<SoundPack>, AppError> {
+    let mut packs = sound_pack_service::list_packs(&self.packs_dir)?;
+    let capabilities = self.wayvibes_capabilities();
+    for pack in &mut packs {
+      pack.warnings = sound_pack_service::unsupported_features(pack, &capabilities);
+    }
+    Ok(packs)
+  }
+
+  /// Probed features of the configured wayvibes; when it can't be found every feature is
+  /// assumed available
+  pub fn wayvibes_capabilities(&self) -> WayvibesCapabilities {
+    let settings = match self.config.lock() {
+      Ok(config) => config.wayvibes.clone(),
+      Err(_) => return WayvibesCapabilities::default(),
+    };
+    wayvibes_locator::resolve(&settings)
+      .map(|binary| wayvibes_capabilities::probe(&binary))
+      .unwrap_or_default()
+  }
 }
@@ -3,17 +3,48 @@ mod events;
 #[path = "tray/menu.rs"]
 mod menu;
 
+use tauri::menu::MenuItem;
 use tauri::tray::TrayIconBuilder;
-use tauri::{App, Result};
+use tauri::{App, AppHandle, Manager, Result, Wry};
+
+use crate::models::ActiveSchedule;
+
+pub use events::show_main_window;
+
+const TRAY_ID: &str = "main";
+const TOOLTIP: &str = "WayVibes";
+
+/// Menu entry showing which schedule, if any, is overriding the manual state
+struct ScheduleStatusItem(MenuItem<Wry>);
 
 pub fn setup_tray(app: &App) -> Result<()> {
-  let menu = menu::build_menu(app)?;
+  let (menu, schedule_item) = menu::build_menu(app)?;
+  app.manage(ScheduleStatusItem(schedule_item));
 
-  TrayIconBuilder::new()
+  TrayIconBuilder::with_id(TRAY_ID)
     .icon(app.default_window_icon().cloned().expect("ícone padrão ausente"))
+    .tooltip(TOOLTIP)
     .menu(&menu)
     .on_menu_event(events::handle_menu_event)
     .build(app)?;
 
+  log::debug!("Tray icon ready");
   Ok(())
 }
+
+pub fn set_schedule_status(app: &AppHandle, active: Option<&ActiveSchedule>) {
+  let (tooltip, label) = match active {
+    Some(active) => (
+      format!("{} — agenda ativa: {}", TOOLTIP, active.rule_name),
+      format!("Agenda ativa: {}", active.rule_name),
+    ),
+    None => (TOOLTIP.to_string(), menu::SCHEDULE_IDLE_LABEL.to_string()),
+  };
+
+  if let Some(tray) = app.tray_by_id(TRAY_ID) {
+    let _ = tray.set_tooltip(Some(tooltip));
+  }
+  if let Some(item) = app.try_state::<ScheduleStatusItem>() {
+    let _ = item.0.set_text(label);
+  }
+}
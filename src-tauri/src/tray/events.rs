@@ -4,6 +4,7 @@ use tauri::menu::MenuEvent;
 use super::menu::{MENU_IMPORT, MENU_OPEN, MENU_QUIT};
 
 pub fn handle_menu_event(app: &AppHandle, event: MenuEvent) {
+  log::debug!("Menu item '{}' clicked", event.id().as_ref());
   match event.id().as_ref() {
     MENU_OPEN => {
       show_main_window(app);
@@ -13,13 +14,14 @@ pub fn handle_menu_event(app: &AppHandle, event: MenuEvent) {
       let _ = app.emit("tray-import", ());
     }
     MENU_QUIT => {
+      log::info!("Quitting from tray");
       app.exit(0);
     }
     _ => {}
   }
 }
 
-fn show_main_window(app: &AppHandle) {
+pub fn show_main_window(app: &AppHandle) {
   if let Some(window) = app.get_webview_window("main") {
     let _ = window.show();
     let _ = window.set_focus();
@@ -1,11 +1,14 @@
-use tauri::menu::{Menu, MenuItem};
+use tauri::menu::{Menu, MenuItem, PredefinedMenuItem};
 use tauri::{App, Result, Runtime};
 
 pub const MENU_OPEN: &str = "tray-open";
 pub const MENU_IMPORT: &str = "tray-import";
+pub const MENU_SCHEDULE: &str = "tray-schedule";
 pub const MENU_QUIT: &str = "tray-quit";
 
-pub fn build_menu<R: Runtime>(app: &App<R>) -> Result<Menu<R>> {
+pub const SCHEDULE_IDLE_LABEL: &str = "Nenhuma agenda ativa";
+
+pub fn build_menu<R: Runtime>(app: &App<R>) -> Result<(Menu<R>, MenuItem<R>)> {
   let open =
     MenuItem::with_id(app, MENU_OPEN, "Abrir WayVibes", true, None::<&str>)?;
   let import = MenuItem::with_id(
@@ -15,7 +18,20 @@ pub fn build_menu<R: Runtime>(app: &App<R>) -> Result<Menu<R>> {
     true,
     None::<&str>,
   )?;
+  let schedule =
+    MenuItem::with_id(app, MENU_SCHEDULE, SCHEDULE_IDLE_LABEL, false, None::<&str>)?;
   let quit = MenuItem::with_id(app, MENU_QUIT, "Sair", true, None::<&str>)?;
 
-  Menu::with_items(app, &[&open, &import, &quit])
+  let menu = Menu::with_items(
+    app,
+    &[
+      &open,
+      &import,
+      &PredefinedMenuItem::separator(app)?,
+      &schedule,
+      &PredefinedMenuItem::separator(app)?,
+      &quit,
+    ],
+  )?;
+  Ok((menu, schedule))
 }
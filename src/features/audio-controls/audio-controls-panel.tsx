@@ -15,6 +15,8 @@ export function AudioControlsPanel() {
     isLoading,
   } = useAppStore();
   const showSkeleton = isLoading && !wayvibesStatus.installed;
+  const capabilities = wayvibesStatus.capabilities;
+  const supportsVolume = !capabilities?.probed || capabilities.volume;
 
   if (showSkeleton) {
     return (
@@ -63,7 +65,7 @@ export function AudioControlsPanel() {
       </div>
       <VolumeSlider
         volume={volume}
-        disabled={!wayvibesStatus.installed || paused}
+        disabled={!wayvibesStatus.installed || paused || !supportsVolume}
         onChange={(nextValue) => void setVolume(nextValue)}
       />
     </div>
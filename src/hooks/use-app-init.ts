@@ -1,11 +1,40 @@
 import { useEffect } from "react";
+import { listen } from "@tauri-apps/api/event";
 
+import type { AppEvent } from "../types";
 import { useAppStore } from "../stores/app-store";
 
 export function useAppInit() {
   const refreshAll = useAppStore((state) => state.refreshAll);
+  const setLastError = useAppStore((state) => state.setLastError);
+  const applyAppEvent = useAppStore((state) => state.applyAppEvent);
 
   useEffect(() => {
     void refreshAll();
   }, [refreshAll]);
+
+  useEffect(() => {
+    const unlisteners: Array<() => void> = [];
+    const setup = async () => {
+      unlisteners.push(
+        await listen("sound-pack-imported", () => {
+          void refreshAll();
+        }),
+      );
+      unlisteners.push(
+        await listen<AppEvent>("app-event", (event) => {
+          applyAppEvent(event.payload);
+        }),
+      );
+      unlisteners.push(
+        await listen<string>("app-error", (event) => {
+          setLastError(event.payload);
+        }),
+      );
+    };
+    void setup();
+    return () => {
+      unlisteners.forEach((unlisten) => unlisten());
+    };
+  }, [applyAppEvent, refreshAll, setLastError]);
 }
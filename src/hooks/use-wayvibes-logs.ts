@@ -0,0 +1,50 @@
+import { useEffect, useState } from "react";
+import { listen } from "@tauri-apps/api/event";
+
+import type { LogLine } from "../types";
+import { getWayvibesLogs } from "../services/wayvibes-service";
+
+const MAX_LINES = 500;
+
+export function useWayvibesLogs() {
+  const [lines, setLines] = useState<LogLine[]>([]);
+
+  useEffect(() => {
+    let active = true;
+    let unlisten: (() => void) | null = null;
+
+    const append = (incoming: LogLine[]) => {
+      setLines((prev) => {
+        const lastSeq = prev.length > 0 ? prev[prev.length - 1].seq : 0;
+        const fresh = incoming.filter((line) => line.seq > lastSeq);
+        return [...prev, ...fresh].slice(-MAX_LINES);
+      });
+    };
+
+    const setup = async () => {
+      const stop = await listen<LogLine>("wayvibes-log", (event) => {
+        append([event.payload]);
+      });
+      if (!active) {
+        stop();
+        return;
+      }
+      unlisten = stop;
+      const backlog = await getWayvibesLogs({ tail: MAX_LINES });
+      if (active) {
+        setLines((prev) => {
+          const firstSeq = prev.length > 0 ? prev[0].seq : Number.POSITIVE_INFINITY;
+          return [...backlog.filter((line) => line.seq < firstSeq), ...prev].slice(-MAX_LINES);
+        });
+      }
+    };
+    void setup();
+
+    return () => {
+      active = false;
+      unlisten?.();
+    };
+  }, []);
+
+  return { lines, clear: () => setLines([]) };
+}
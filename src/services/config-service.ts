@@ -1,7 +1,15 @@
 import { invoke } from "@tauri-apps/api/core";
 
-import type { AppConfig } from "../types";
+import type { AppConfig, LogLevel } from "../types";
 
 export async function getConfig(): Promise<AppConfig> {
   return invoke<AppConfig>("get_config");
 }
+
+export async function setLogLevel(level: LogLevel): Promise<void> {
+  await invoke("set_log_level", { level });
+}
+
+export async function openLogFolder(): Promise<void> {
+  await invoke("open_log_folder");
+}
@@ -0,0 +1,5 @@
+import { invoke } from "@tauri-apps/api/core";
+
+export async function exportDiagnostics(path?: string): Promise<string> {
+  return invoke<string>("export_diagnostics", { path: path ?? null });
+}
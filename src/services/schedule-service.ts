@@ -0,0 +1,11 @@
+import { invoke } from "@tauri-apps/api/core";
+
+import type { ActiveSchedule, ScheduleRule } from "../types";
+
+export async function setSchedules(schedules: ScheduleRule[]): Promise<void> {
+  await invoke("set_schedules", { schedules });
+}
+
+export async function getActiveSchedule(): Promise<ActiveSchedule | null> {
+  return invoke<ActiveSchedule | null>("get_active_schedule");
+}
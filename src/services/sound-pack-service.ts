@@ -1,14 +1,69 @@
 import { invoke } from "@tauri-apps/api/core";
 import { revealItemInDir } from "@tauri-apps/plugin-opener";
 
-import type { SoundPack } from "../types";
+import type {
+  ComposedPack,
+  KeyChange,
+  KeyboardLayout,
+  NewPack,
+  PackCoverage,
+  PackLoudness,
+  PackMapping,
+  PackSettings,
+  PreviewRequest,
+  ProcessOptions,
+  ProcessReport,
+  SoundPack,
+  TranscodeOptions,
+  TranscodeReport,
+} from "../types";
 
 export async function getSoundPacks(): Promise<SoundPack[]> {
   return invoke<SoundPack[]>("get_sound_packs");
 }
 
-export async function importSoundPack(path: string): Promise<SoundPack> {
-  return invoke<SoundPack>("import_sound_pack", { path });
+export async function importSoundPack(
+  path: string,
+  transcode?: TranscodeOptions,
+): Promise<SoundPack> {
+  return invoke<SoundPack>("import_sound_pack", { path, transcode });
+}
+
+export async function createSoundPack(request: NewPack): Promise<SoundPack> {
+  return invoke<SoundPack>("create_sound_pack", { request });
+}
+
+export async function composeSoundPack(request: ComposedPack): Promise<SoundPack> {
+  return invoke<SoundPack>("compose_sound_pack", { request });
+}
+
+export async function renderPackPreview(request: PreviewRequest): Promise<ArrayBuffer> {
+  return invoke<ArrayBuffer>("render_pack_preview", { request });
+}
+
+export async function convertSoundPack(packId: string): Promise<SoundPack> {
+  return invoke<SoundPack>("convert_sound_pack", { packId });
+}
+
+export async function normalizeSoundPack(
+  packId: string,
+  options: TranscodeOptions,
+): Promise<TranscodeReport> {
+  return invoke<TranscodeReport>("normalize_sound_pack", { packId, options });
+}
+
+export async function processSoundPack(
+  packId: string,
+  options: ProcessOptions,
+): Promise<ProcessReport> {
+  return invoke<ProcessReport>("process_sound_pack", { packId, options });
+}
+
+export async function getPackCoverage(
+  packId: string,
+  layout: KeyboardLayout,
+): Promise<PackCoverage> {
+  return invoke<PackCoverage>("pack_coverage", { packId, layout });
 }
 
 export async function deleteSoundPack(packId: string): Promise<void> {
@@ -19,6 +74,32 @@ export async function getPackPath(packId: string): Promise<string> {
   return invoke<string>("get_pack_path", { packId });
 }
 
+export async function setPackSettings(
+  packId: string,
+  settings: PackSettings,
+): Promise<void> {
+  await invoke("set_pack_settings", { packId, settings });
+}
+
+export async function analyzePackLoudness(packId: string): Promise<PackLoudness> {
+  return invoke<PackLoudness>("analyze_pack_loudness", { packId });
+}
+
+export async function getPackMapping(packId: string): Promise<PackMapping> {
+  return invoke<PackMapping>("get_pack_mapping", { packId });
+}
+
+export async function setKeySounds(
+  packId: string,
+  changes: KeyChange[],
+): Promise<PackMapping> {
+  return invoke<PackMapping>("set_key_sounds", { packId, changes });
+}
+
+export async function addPackSounds(packId: string, files: string[]): Promise<string[]> {
+  return invoke<string[]>("add_pack_sounds", { packId, files });
+}
+
 export async function openPackInExplorer(packId: string): Promise<void> {
   const path = await getPackPath(packId);
   await revealItemInDir(path);
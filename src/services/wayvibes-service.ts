@@ -1,6 +1,15 @@
 import { invoke } from "@tauri-apps/api/core";
 
-import type { WayvibesStatus } from "../types";
+import type {
+  LogLine,
+  PreflightReport,
+  Profile,
+  VolumeCurve,
+  WayvibesLocation,
+  WayvibesLogQuery,
+  WayvibesSettings,
+  WayvibesStatus,
+} from "../types";
 
 export async function getWayvibesStatus(): Promise<WayvibesStatus> {
   return invoke<WayvibesStatus>("get_wayvibes_status");
@@ -14,6 +23,22 @@ export async function togglePause(): Promise<void> {
   await invoke("toggle_pause");
 }
 
+export async function setPaused(paused: boolean): Promise<void> {
+  await invoke("set_paused", { paused });
+}
+
+export async function setMuted(muted: boolean): Promise<void> {
+  await invoke("set_muted", { muted });
+}
+
+export async function toggleMute(): Promise<void> {
+  await invoke("toggle_mute");
+}
+
+export async function setVolumeCurve(curve: VolumeCurve): Promise<void> {
+  await invoke("set_volume_curve", { curve });
+}
+
 export async function stopWayvibes(): Promise<void> {
   await invoke("stop_wayvibes");
 }
@@ -21,3 +46,33 @@ export async function stopWayvibes(): Promise<void> {
 export async function setActivePack(packId: string): Promise<void> {
   await invoke("set_active_pack", { packId });
 }
+
+export async function setProfiles(profiles: Profile[]): Promise<void> {
+  await invoke("set_profiles", { profiles });
+}
+
+export async function setActiveProfile(profileId: string | null): Promise<void> {
+  await invoke("set_active_profile", { profileId });
+}
+
+export async function locateWayvibes(): Promise<WayvibesLocation> {
+  return invoke<WayvibesLocation>("locate_wayvibes");
+}
+
+export async function setWayvibesSettings(
+  settings: WayvibesSettings,
+): Promise<WayvibesLocation> {
+  return invoke<WayvibesLocation>("set_wayvibes_settings", { settings });
+}
+
+export async function getWayvibesLogs(query: WayvibesLogQuery = {}): Promise<LogLine[]> {
+  return invoke<LogLine[]>("get_wayvibes_logs", {
+    tail: query.tail ?? null,
+    filter: query.filter ?? null,
+    stream: query.stream ?? null,
+  });
+}
+
+export async function runPreflight(): Promise<PreflightReport> {
+  return invoke<PreflightReport>("run_preflight");
+}
@@ -1,6 +1,6 @@
 import { create } from "zustand";
 
-import type { AppConfig, SoundPack, WayvibesStatus } from "../types";
+import type { AppConfig, AppEvent, SoundPack, WayvibesStatus } from "../types";
 import { getAutostartEnabled, setAutostartEnabled } from "../services/autostart-service";
 import { getConfig } from "../services/config-service";
 import {
@@ -34,6 +34,7 @@ interface AppState {
   stopWayvibes: () => Promise<void>;
   setAutostartEnabled: (enabled: boolean) => Promise<void>;
   setLastError: (message: string | null) => void;
+  applyAppEvent: (event: AppEvent) => void;
 }
 
 const defaultStatus: WayvibesStatus = {
@@ -43,11 +44,13 @@ const defaultStatus: WayvibesStatus = {
   pid: null,
 };
 
+/** A pack with its own volume shows it on the slider instead of the global volume */
 function applyConfig(state: AppState, config: AppConfig) {
+  const activePack = state.soundPacks.find((pack) => pack.id === config.activePackId);
   return {
     ...state,
     activePackId: config.activePackId,
-    volume: config.volume,
+    volume: activePack?.settings?.defaultVolume ?? config.volume,
     paused: config.paused,
   };
 }
@@ -62,6 +65,19 @@ export const useAppStore = create<AppState>((set) => ({
   isLoading: false,
   lastError: null,
   setLastError: (message) => set({ lastError: message }),
+  applyAppEvent: (event) => {
+    switch (event.type) {
+      case "stateChanged":
+        set((state) => ({
+          ...applyConfig(state, event.config),
+          wayvibesStatus: event.status,
+        }));
+        break;
+      case "packsChanged":
+        set({ soundPacks: event.packs });
+        break;
+    }
+  },
   refreshAll: async () => {
     set({ isLoading: true, lastError: null });
     try {
@@ -72,9 +88,8 @@ export const useAppStore = create<AppState>((set) => ({
         getAutostartEnabled(),
       ]);
       set((state) => ({
-        ...applyConfig(state, config),
+        ...applyConfig({ ...state, soundPacks: packs }, config),
         wayvibesStatus: status,
-        soundPacks: packs,
         autostartEnabled,
         isLoading: false,
       }));
@@ -95,8 +110,7 @@ export const useAppStore = create<AppState>((set) => ({
       console.log('is executing here?')
       const [packs, config] = await Promise.all([getSoundPacks(), getConfig()]);
       set((state) => ({
-        ...applyConfig(state, config),
-        soundPacks: packs,
+        ...applyConfig({ ...state, soundPacks: packs }, config),
         isLoading: false,
       }));
     } catch (error) {
@@ -113,8 +127,7 @@ export const useAppStore = create<AppState>((set) => ({
       await deleteSoundPack(packId);
       const [packs, config] = await Promise.all([getSoundPacks(), getConfig()]);
       set((state) => ({
-        ...applyConfig(state, config),
-        soundPacks: packs,
+        ...applyConfig({ ...state, soundPacks: packs }, config),
         isLoading: false,
       }));
     } catch (error) {
@@ -3,6 +3,61 @@ export interface WayvibesStatus {
   running: boolean;
   version: string | null;
   pid?: number | null;
+  capabilities?: WayvibesCapabilities | null;
+  preflight?: PreflightSummary | null;
+  lifecycle?: LifecycleState | null;
+}
+
+export type LifecycleState = "stopped" | "starting" | "running" | "stopping" | "failed";
+
+export type CheckStatus = "pass" | "warn" | "fail";
+
+export interface PreflightCheck {
+  id: string;
+  label: string;
+  status: CheckStatus;
+  detail: string;
+  remediation: string | null;
+}
+
+export interface PreflightSummary {
+  status: CheckStatus;
+  passed: number;
+  warnings: number;
+  failures: number;
+}
+
+export interface PreflightReport {
+  summary: PreflightSummary;
+  checks: PreflightCheck[];
+}
+
+export interface WayvibesCapabilities {
+  probed: boolean;
+  version: string | null;
+  flags: string[];
+  volume: boolean;
+  volumeMax: number | null;
+  background: boolean;
+  deviceSelection: boolean;
+  liveControl?: boolean;
+  keyup?: boolean;
+  variations?: boolean;
+}
+
+export type LogStream = "stdout" | "stderr" | "supervisor";
+
+export interface LogLine {
+  seq: number;
+  timestamp: string;
+  stream: LogStream;
+  text: string;
+}
+
+export interface WayvibesLogQuery {
+  tail?: number;
+  filter?: string;
+  stream?: LogStream;
 }
 
 export interface SoundPack {
@@ -11,10 +66,231 @@ export interface SoundPack {
   version: string;
   author?: string | null;
   description?: string | null;
+  configVersion?: string | null;
+  supportsKeyup?: boolean;
+  variantKeys?: number;
+  maxVariants?: number;
+  warnings?: string[];
+  settings?: PackSettings;
+  transcode?: TranscodeReport;
+}
+
+export interface TranscodeOptions {
+  sampleRate?: number | null;
+}
+
+export interface TrimOptions {
+  thresholdDb: number;
+  paddingMs?: number;
+}
+
+export type NormalizeTarget =
+  | { type: "peak"; db: number }
+  | { type: "rms"; db: number };
+
+export interface ProcessOptions {
+  trim?: TrimOptions | null;
+  normalize?: NormalizeTarget | null;
+}
+
+export interface SoundChange {
+  source: KeySound;
+  keys: number;
+  trimmedStartMs: number;
+  trimmedEndMs: number;
+  gainDb: number;
+}
+
+export interface ProcessReport {
+  sourcePackId: string;
+  pack: SoundPack;
+  changes: SoundChange[];
+}
+
+export interface TranscodeReport {
+  packId: string;
+  files: number;
+  converted: number;
+  bytesBefore: number;
+  bytesAfter: number;
+}
+
+export interface PackSettings {
+  gainDb: number;
+  defaultVolume?: number | null;
+}
+
+export type KeyDefineType = "single" | "multi";
+
+export type KeySound =
+  | { type: "file"; file: string }
+  | { type: "slice"; offsetMs: number; durationMs: number }
+  | { type: "variants"; sounds: KeySound[] };
+
+export interface KeyMapping {
+  code: number;
+  label: string;
+  evdevName: string | null;
+  coverage: Coverage;
+  sound: KeySound | null;
+}
+
+export type KeyboardLayout =
+  | { type: "ansi104" }
+  | { type: "iso105" }
+  | { type: "tkl" }
+  | { type: "sixty" }
+  | { type: "custom"; evdevCodes: number[] };
+
+export type Coverage = "mapped" | "fallback" | "silent" | "unmapped";
+
+export interface KeyCoverage {
+  evdev: number;
+  evdevName: string | null;
+  label: string;
+  code: number | null;
+  coverage: Coverage;
+  sound: KeySound | null;
+}
+
+export interface PackCoverage {
+  packId: string;
+  layout: KeyboardLayout;
+  mapped: number;
+  fallback: number;
+  silent: number;
+  unmapped: number;
+  keys: KeyCoverage[];
+}
+
+export interface PackMapping {
+  packId: string;
+  keyDefineType: KeyDefineType;
+  sound: string | null;
+  files: string[];
+  keys: KeyMapping[];
+}
+
+export interface KeyChange {
+  code: number;
+  sound: KeySound | null;
+}
+
+export type MappingStrategy = "single" | "roundRobin" | "byName";
+
+export interface NewPack {
+  name: string;
+  author?: string | null;
+  description?: string | null;
+  files: string[];
+  strategy: MappingStrategy;
+}
+
+export type KeyRange =
+  | { type: "codes"; codes: number[] }
+  | { type: "range"; first: number; last: number };
+
+export interface KeyOverride {
+  packId: string;
+  keys: KeyRange;
+}
+
+export interface ComposedPack {
+  name: string;
+  basePackId: string;
+  overrides: KeyOverride[];
+}
+
+export interface KeyStroke {
+  code: number;
+  atMs: number;
+  holdMs?: number | null;
+}
+
+export type PreviewInput =
+  | { type: "text"; text: string; intervalMs?: number | null }
+  | { type: "keys"; strokes: KeyStroke[] };
+
+export interface PreviewRequest {
+  packId: string;
+  input: PreviewInput;
+}
+
+export interface PackLoudness {
+  packId: string;
+  files: number;
+  peakDb: number;
+  rmsDb: number;
+  suggestedGainDb: number;
 }
 
 export interface AppConfig {
   activePackId: string | null;
   volume: number;
   paused: boolean;
+  schedules: ScheduleRule[];
+  wayvibes: WayvibesSettings;
+  logLevel: LogLevel;
+  muted: boolean;
+  volumeCurve: VolumeCurve;
+  profiles: Profile[];
+  activeProfileId: string | null;
 }
+
+export interface Profile {
+  id: string;
+  name: string;
+  packId?: string | null;
+  gainDb?: number;
+}
+
+export type VolumeCurve =
+  | { type: "linear" }
+  | { type: "logarithmic" }
+  | { type: "exponent"; exponent: number };
+
+export type LogLevel = "error" | "warn" | "info" | "debug" | "trace";
+
+export interface WayvibesSettings {
+  binaryPath: string | null;
+  extraArgs: string[];
+  env: Record<string, string>;
+}
+
+export interface WayvibesCandidate {
+  path: string;
+  source: "config" | "path" | "common";
+  executable: boolean;
+}
+
+export interface WayvibesLocation {
+  resolved: string | null;
+  candidates: WayvibesCandidate[];
+}
+
+export type Weekday = "mon" | "tue" | "wed" | "thu" | "fri" | "sat" | "sun";
+
+export type ScheduleAction =
+  | { type: "pause" }
+  | { type: "setVolume"; volume: number }
+  | { type: "setPack"; packId: string }
+  | { type: "switchProfile"; profileId: string };
+
+export interface ScheduleRule {
+  id: string;
+  name: string;
+  enabled: boolean;
+  days: Weekday[];
+  start: string;
+  end: string;
+  action: ScheduleAction;
+}
+
+export interface ActiveSchedule {
+  ruleId: string;
+  ruleName: string;
+}
+
+export type AppEvent =
+  | { type: "stateChanged"; config: AppConfig; status: WayvibesStatus }
+  | { type: "packsChanged"; packs: SoundPack[] };